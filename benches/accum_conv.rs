@@ -68,8 +68,10 @@ impl Circuit<Fr> for MyCircuit {
                         Box::new(PolyOp::Conv {
                             kernel: self.kernel.clone(),
                             bias: Some(self.bias.clone()),
-                            padding: (0, 0),
-                            stride: (1, 1),
+                            padding: vec![(0, 0), (0, 0)],
+                            stride: vec![1, 1],
+                            dilation: vec![1, 1],
+                            groups: 1,
                         }),
                     )
                     .unwrap();
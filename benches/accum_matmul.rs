@@ -58,7 +58,12 @@ impl Circuit<Fr> for MyCircuit {
                         Some(&mut region),
                         &self.inputs,
                         &mut 0,
-                        Box::new(PolyOp::Matmul { a: None }),
+                        Box::new(PolyOp::Matmul {
+                            a: None,
+                            a_trans: false,
+                            b_trans: false,
+                            c_trans: false,
+                        }),
                     )
                     .unwrap();
                 Ok(())
@@ -67,7 +67,12 @@ impl Circuit<Fr> for MyCircuit {
         layouter.assign_region(
             || "",
             |mut region| {
-                let op = PolyOp::Matmul { a: None };
+                let op = PolyOp::Matmul {
+                    a: None,
+                    a_trans: false,
+                    b_trans: false,
+                    c_trans: false,
+                };
                 let mut offset = 0;
                 let output = config
                     .base_config
@@ -176,8 +176,10 @@ where
                     let op = PolyOp::Conv {
                         kernel: self.l0_params[0].clone(),
                         bias: Some(self.l0_params[1].clone()),
-                        padding: (PADDING, PADDING),
-                        stride: (STRIDE, STRIDE),
+                        padding: vec![(PADDING, PADDING), (PADDING, PADDING)],
+                        stride: vec![STRIDE, STRIDE],
+                        dilation: vec![1, 1],
+                        groups: 1,
                     };
                     let x = config
                         .layer_config
@@ -0,0 +1,18 @@
+import torch
+from torch import nn
+from ezkl import export
+
+class Model(nn.Module):
+    def __init__(self):
+        super(Model, self).__init__()
+        self.aff1 = nn.Linear(4, 3)
+        self.relu = nn.ReLU()
+
+    def forward(self, x):
+        x = self.aff1(x)
+        x = self.relu(x)
+        return torch.argmax(x, dim=1, keepdim=True)
+
+
+circuit = Model()
+export(circuit, input_shape = [4])
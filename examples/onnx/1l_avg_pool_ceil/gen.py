@@ -0,0 +1,15 @@
+from torch import nn
+from ezkl import export
+
+class Model(nn.Module):
+    def __init__(self):
+        super(Model, self).__init__()
+        self.layer = nn.AvgPool2d(3, stride=2, padding=1, ceil_mode=True, count_include_pad=False)
+
+    def forward(self, x):
+        return self.layer(x)
+
+
+
+circuit = Model()
+export(circuit, input_shape = [1, 6, 6])
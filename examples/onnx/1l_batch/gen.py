@@ -0,0 +1,32 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, TensorProto
+
+# a single ReLU over inputs with a dynamic (symbolic) batch dim, run on a batch of 2 samples
+batch, inp = 2, 3
+
+def main():
+    rng = np.random.default_rng(3)
+    x = rng.uniform(-0.5, 0.5, (batch, inp)).astype(np.float32)
+
+    node = helper.make_node('Relu', ['input'], ['output'])
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, ['batch_size', inp])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, ['batch_size', inp])],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    # outputs are quantized at the default scale of 7, with one output per sample of the batch
+    y = np.round(np.maximum(x, 0) * 128) / 128
+    data = dict(input_shapes = [[batch, inp]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [s.tolist() for s in y])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,44 @@
+import json
+import math
+import random
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+import numpy as np
+
+# an inference mode BatchNormalization over a [3, 2, 3] input with non-trivial per channel
+# scale, bias and running statistics
+C, H, W = 3, 2, 3
+eps = 1e-5
+
+def main():
+    random.seed(3)
+    x = [random.uniform(-2, 2) for _ in range(C * H * W)]
+    gamma = [random.uniform(0.5, 1.5) for _ in range(C)]
+    beta = [random.uniform(-0.5, 0.5) for _ in range(C)]
+    mean = [random.uniform(-0.5, 0.5) for _ in range(C)]
+    var = [random.uniform(0.5, 2.0) for _ in range(C)]
+
+    node = helper.make_node('BatchNormalization', ['input', 'scale', 'B', 'mean', 'var'], ['output'], epsilon=eps)
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [1, C, H, W])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, [1, C, H, W])],
+        [numpy_helper.from_array(np.array(p, dtype=np.float32), name)
+         for p, name in [(gamma, 'scale'), (beta, 'B'), (mean, 'mean'), (var, 'var')]],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    y = []
+    for c in range(C):
+        row = x[c * H * W:(c + 1) * H * W]
+        y += [(v - mean[c]) / math.sqrt(var[c] + eps) * gamma[c] + beta[c] for v in row]
+    data = dict(input_shapes = [[C, H, W]],
+                input_data = [x],
+                output_data = [y])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,17 @@
+import torch
+from torch import nn
+from ezkl import export
+
+class Model(nn.Module):
+    def __init__(self):
+        super(Model, self).__init__()
+        # a per-channel bias of shape [C], broadcast over the spatial dims
+        self.bias = nn.Parameter(torch.tensor([0.5, -0.25, 1.0]))
+
+    def forward(self, x):
+        return x + self.bias.view(-1, 1, 1)
+
+
+
+circuit = Model()
+export(circuit, input_shape = [3, 2, 2])
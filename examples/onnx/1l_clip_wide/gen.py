@@ -0,0 +1,31 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, TensorProto
+
+# a clip whose upper bound lies beyond the range of the 16 bit lookup tables at scale 7 (i.e 256), which is laid
+# out with comparisons rather than a single lookup
+size = 8
+
+def main():
+    rng = np.random.default_rng(1)
+    x = rng.uniform(100, 250, (1, size)).astype(np.float32)
+
+    node = helper.make_node('Clip', ['input'], ['output'], min=200.0, max=300.0)
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [1, size])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, [1, size])],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    data = dict(input_shapes = [[size]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [np.clip(x, 200.0, 300.0).reshape([-1]).tolist()])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
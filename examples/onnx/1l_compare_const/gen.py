@@ -0,0 +1,37 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+
+# compares a constant against the input, with the constant as the left operand, i.e c > x and c < x
+c = np.array([[0.5, -0.25, 1.0, 0.0]], dtype=np.float32)
+
+def main():
+    x = np.array([[1.0, -1.0, 0.5, 0.25]], dtype=np.float32)
+
+    nodes = [
+        helper.make_node('Greater', ['c', 'input'], ['gt']),
+        helper.make_node('Cast', ['gt'], ['greater'], to=TensorProto.FLOAT),
+        helper.make_node('Less', ['c', 'input'], ['lt']),
+        helper.make_node('Cast', ['lt'], ['less'], to=TensorProto.FLOAT),
+    ]
+    graph = helper.make_graph(
+        nodes,
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [1, 4])],
+        [helper.make_tensor_value_info('greater', TensorProto.FLOAT, [1, 4]),
+         helper.make_tensor_value_info('less', TensorProto.FLOAT, [1, 4])],
+        [numpy_helper.from_array(c, 'c')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    data = dict(input_shapes = [[4]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [(c > x).astype(np.float32).reshape([-1]).tolist(),
+                               (c < x).astype(np.float32).reshape([-1]).tolist()])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,15 @@
+import torch
+from torch import nn
+from ezkl import export
+
+class Model(nn.Module):
+    def __init__(self):
+        super(Model, self).__init__()
+
+    def forward(self, x):
+        return torch.cat([x, x], dim=1)
+
+
+
+circuit = Model()
+export(circuit, input_shape = [3, 2, 2])
@@ -0,0 +1,42 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+
+# a single 1D convolution over inputs with a dynamic (symbolic) batch dim, run on a batch of 2 samples.
+# ezkl only supports a batch size of 1 for convolutions (of any rank), so loading this with a batch size of 2 is rejected.
+batch, out_ch, in_ch, size, k = 2, 2, 1, 4, 2
+
+def main():
+    rng = np.random.default_rng(6)
+    x = rng.uniform(-0.5, 0.5, (batch, in_ch, size)).astype(np.float32)
+    w = rng.uniform(-0.5, 0.5, (out_ch, in_ch, k)).astype(np.float32)
+    b = rng.uniform(-0.5, 0.5, (out_ch,)).astype(np.float32)
+
+    node = helper.make_node('Conv', ['input', 'weight', 'bias'], ['output'],
+                            kernel_shape=[k], strides=[1], pads=[0, 0],
+                            dilations=[1], group=1)
+    out = size - k + 1
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, ['batch_size', in_ch, size])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, ['batch_size', out_ch, out])],
+        [numpy_helper.from_array(w, 'weight'), numpy_helper.from_array(b, 'bias')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    y = np.stack([
+        np.stack([
+            np.array([np.sum(w[o, 0] * x[n, 0, i:i + k]) for i in range(out)]) + b[o]
+            for o in range(out_ch)])
+        for n in range(batch)]).astype(np.float32)
+    data = dict(input_shapes = [[batch, in_ch, size]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [s.reshape([-1]).tolist() for s in y])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,46 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+
+# a convolution and a max pool which are only padded after the image (as for "same" padding exported by tf),
+# such that both outputs keep the spatial dims of the input
+size = 4
+
+def main():
+    rng = np.random.default_rng(7)
+    # positive inputs, as ezkl pads max pools with 0s rather than -inf
+    x = rng.uniform(0, 1, (1, 1, size, size)).astype(np.float32)
+    w = rng.uniform(-0.5, 0.5, (1, 1, 2, 2)).astype(np.float32)
+    b = rng.uniform(-0.5, 0.5, (1,)).astype(np.float32)
+
+    nodes = [
+        helper.make_node('Conv', ['input', 'weight', 'bias'], ['conv'],
+                         kernel_shape=[2, 2], strides=[1, 1], pads=[0, 0, 1, 1]),
+        helper.make_node('MaxPool', ['input'], ['pool'],
+                         kernel_shape=[2, 2], strides=[1, 1], pads=[0, 0, 1, 1]),
+    ]
+    graph = helper.make_graph(
+        nodes,
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [1, 1, size, size])],
+        [helper.make_tensor_value_info('conv', TensorProto.FLOAT, [1, 1, size, size]),
+         helper.make_tensor_value_info('pool', TensorProto.FLOAT, [1, 1, size, size])],
+        [numpy_helper.from_array(w, 'weight'), numpy_helper.from_array(b, 'bias')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    padded = np.pad(x[0, 0], ((0, 1), (0, 1)))
+    conv = np.array([[np.sum(w[0, 0] * padded[i:i + 2, j:j + 2]) for j in range(size)]
+                     for i in range(size)]) + b[0]
+    pool = np.array([[np.max(padded[i:i + 2, j:j + 2]) for j in range(size)]
+                     for i in range(size)])
+    data = dict(input_shapes = [[1, size, size]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [conv.reshape([-1]).tolist(), pool.reshape([-1]).tolist()])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,50 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+
+# a single GRU layer over a (static) sequence of length 3, with the onnx [seq, batch, input] layout
+seq, inp, hid = 3, 2, 2
+
+def sigmoid(v):
+    return 1 / (1 + np.exp(-v))
+
+def gru(x, W, R, B):
+    h, y = np.zeros(hid), []
+    Wb, Rb = np.split(B[0], 2)
+    for xt in x[:, 0]:
+        # onnx gate order is z, r, h
+        wz, wr, wh = np.split(W[0] @ xt + Wb, 3)
+        rz, rr, _ = np.split(R[0] @ h + Rb, 3)
+        z, r = sigmoid(wz + rz), sigmoid(wr + rr)
+        hh = np.tanh(wh + R[0, 2 * hid:] @ (r * h) + Rb[2 * hid:])
+        h = (1 - z) * hh + z * h
+        y.append(h)
+    return np.array(y)
+
+def main():
+    rng = np.random.default_rng(11)
+    W = rng.uniform(-0.5, 0.5, (1, 3 * hid, inp)).astype(np.float32)
+    R = rng.uniform(-0.5, 0.5, (1, 3 * hid, hid)).astype(np.float32)
+    B = rng.uniform(-0.5, 0.5, (1, 6 * hid)).astype(np.float32)
+    x = rng.uniform(0, 0.5, (seq, 1, inp)).astype(np.float32)
+
+    node = helper.make_node('GRU', ['input', 'W', 'R', 'B'], ['output'], hidden_size=hid)
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [seq, 1, inp])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, [seq, 1, 1, hid])],
+        [numpy_helper.from_array(W, 'W'), numpy_helper.from_array(R, 'R'), numpy_helper.from_array(B, 'B')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    data = dict(input_shapes = [[seq, 1, inp]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [gru(x, W, R, B).reshape([-1]).tolist()])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
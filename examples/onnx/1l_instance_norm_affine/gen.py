@@ -0,0 +1,44 @@
+import json
+import math
+import random
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+import numpy as np
+
+# an InstanceNormalization over a [3, 2, 3] input with non-trivial per channel scale and bias,
+# and inputs spread widely enough for the quantized normalisation to be accurate
+C, H, W = 3, 2, 3
+eps = 1e-5
+
+def main():
+    random.seed(3)
+    x = [random.uniform(-2, 2) for _ in range(C * H * W)]
+    gamma = [random.uniform(0.5, 1.5) for _ in range(C)]
+    beta = [random.uniform(-0.5, 0.5) for _ in range(C)]
+
+    node = helper.make_node('InstanceNormalization', ['input', 'scale', 'B'], ['output'], epsilon=eps)
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [1, C, H, W])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, [1, C, H, W])],
+        [numpy_helper.from_array(np.array(gamma, dtype=np.float32), 'scale'),
+         numpy_helper.from_array(np.array(beta, dtype=np.float32), 'B')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    y = []
+    for c in range(C):
+        row = x[c * H * W:(c + 1) * H * W]
+        mean = sum(row) / len(row)
+        var = sum((v - mean) ** 2 for v in row) / len(row)
+        y += [(v - mean) / math.sqrt(var + eps) * gamma[c] + beta[c] for v in row]
+    data = dict(input_shapes = [[C, H, W]],
+                input_data = [x],
+                output_data = [y])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
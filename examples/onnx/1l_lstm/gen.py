@@ -0,0 +1,48 @@
+import json
+import numpy as np
+import onnx
+from onnx import helper, numpy_helper, TensorProto
+
+# a single LSTM layer over a (static) sequence of length 3, with the onnx [seq, batch, input] layout
+seq, inp, hid = 3, 2, 2
+
+def sigmoid(v):
+    return 1 / (1 + np.exp(-v))
+
+def lstm(x, W, R, B):
+    h, c, y = np.zeros(hid), np.zeros(hid), []
+    for xt in x[:, 0]:
+        # onnx gate order is i, o, f, c
+        gates = W[0] @ xt + R[0] @ h + B[0, :4 * hid] + B[0, 4 * hid:]
+        i, o, f, g = np.split(gates, 4)
+        c = sigmoid(f) * c + sigmoid(i) * np.tanh(g)
+        h = sigmoid(o) * np.tanh(c)
+        y.append(h)
+    return np.array(y)
+
+def main():
+    rng = np.random.default_rng(7)
+    W = rng.uniform(-0.5, 0.5, (1, 4 * hid, inp)).astype(np.float32)
+    R = rng.uniform(-0.5, 0.5, (1, 4 * hid, hid)).astype(np.float32)
+    B = rng.uniform(-0.5, 0.5, (1, 8 * hid)).astype(np.float32)
+    x = rng.uniform(0, 0.5, (seq, 1, inp)).astype(np.float32)
+
+    node = helper.make_node('LSTM', ['input', 'W', 'R', 'B'], ['output'], hidden_size=hid)
+    graph = helper.make_graph(
+        [node],
+        'torch_jit',
+        [helper.make_tensor_value_info('input', TensorProto.FLOAT, [seq, 1, inp])],
+        [helper.make_tensor_value_info('output', TensorProto.FLOAT, [seq, 1, 1, hid])],
+        [numpy_helper.from_array(W, 'W'), numpy_helper.from_array(R, 'R'), numpy_helper.from_array(B, 'B')],
+    )
+    onnx.save(helper.make_model(graph, opset_imports=[helper.make_opsetid('', 10)]), "network.onnx")
+
+    data = dict(input_shapes = [[seq, 1, inp]],
+                input_data = [x.reshape([-1]).tolist()],
+                output_data = [lstm(x, W, R, B).reshape([-1]).tolist()])
+
+    # Serialize data into file:
+    json.dump( data, open( "input.json", 'w' ) )
+
+if __name__ == "__main__":
+    main()
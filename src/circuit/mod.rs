@@ -48,6 +48,15 @@ pub enum CircuitError {
     /// This operation is unsupported
     #[error("unsupported operation in graph")]
     UnsupportedOp,
+    /// An embedding table has more rows than the lookup tables can index
+    #[error("embedding table of {0} rows exceeds the {1} rows {2} bit lookup tables can index")]
+    EmbeddingTooLarge(usize, usize, usize),
+    /// A value laid out as the input to a lookup falls outside the range of its table
+    #[error("{2} of {0} is outside the range of the {1} bit lookup tables")]
+    LookupOutOfRange(i128, usize, String),
+    /// A layout needs a lookup table which hasn't been configured
+    #[error("the {0} lookup table required by the layout isn't configured")]
+    MissingLookup(String),
 }
 
 #[allow(missing_docs)]
@@ -305,11 +314,26 @@ impl<F: FieldExt + TensorType> BaseConfig<F> {
     /// * `op` - The operation being represented.
     pub fn layout(
         &mut self,
-        mut region: Option<&mut Region<F>>,
+        region: Option<&mut Region<F>>,
         values: &[ValTensor<F>],
         offset: &mut usize,
         op: Box<dyn Op<F>>,
     ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+        Ok(self
+            .layout_outputs(region, values, offset, op)?
+            .into_iter()
+            .next())
+    }
+
+    /// Assigns variables to the regions created when calling `configure`, returning every output of `op`
+    /// (see [Op::layout_outputs]).
+    pub fn layout_outputs(
+        &mut self,
+        mut region: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        offset: &mut usize,
+        op: Box<dyn Op<F>>,
+    ) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
         let mut cp_values = vec![];
         for v in values.iter() {
             if let ValTensor::Instance { .. } = v {
@@ -323,7 +347,6 @@ impl<F: FieldExt + TensorType> BaseConfig<F> {
                 cp_values.push(v.clone());
             }
         }
-        let res = op.layout(self, region, &cp_values, offset);
-        res
+        op.layout_outputs(self, region, &cp_values, offset)
     }
 }
@@ -21,19 +21,96 @@ pub enum HybridOp<F: FieldExt + TensorType> {
     EltWiseMax {
         a: Option<ValTensor<F>>,
     },
+    EltWiseMin {
+        a: Option<ValTensor<F>>,
+    },
+    Clip {
+        min: ValTensor<F>,
+        max: ValTensor<F>,
+    },
     MaxPool2d {
-        padding: (usize, usize),
+        padding: [(usize, usize); 2],
         stride: (usize, usize),
         pool_dims: (usize, usize),
     },
+    AvgPool2d {
+        padding: [(usize, usize); 2],
+        stride: (usize, usize),
+        kernel_shape: (usize, usize),
+        ceil_mode: bool,
+        count_include_pad: bool,
+        image_dims: (usize, usize),
+    },
     Min,
+    ArgMax {
+        axis: usize,
+        select_last: bool,
+    },
+    ArgMin {
+        axis: usize,
+        select_last: bool,
+    },
     PReLU {
         scale: usize,
         slopes: Vec<crate::circuit::utils::F32>,
     },
+    Embedding {
+        index_scale: usize,
+    },
     Greater {
         a: Option<ValTensor<F>>,
     },
+    GreaterEqual {
+        a: Option<ValTensor<F>>,
+    },
+    Less {
+        a: Option<ValTensor<F>>,
+    },
+    LessEqual {
+        a: Option<ValTensor<F>>,
+    },
+    Equal {
+        a: Option<ValTensor<F>>,
+    },
+    And {
+        a: Option<ValTensor<F>>,
+    },
+    Or {
+        a: Option<ValTensor<F>>,
+    },
+    Xor {
+        a: Option<ValTensor<F>>,
+    },
+    Not,
+    Iff,
+    Softmax {
+        scales: (usize, usize),
+        bits: usize,
+        mask: Option<ValTensor<F>>,
+    },
+    InstanceNorm2d {
+        scale: usize,
+        epsilon: utils::F32,
+        num_inputs: usize,
+        gamma: ValTensor<F>,
+        beta: ValTensor<F>,
+    },
+    BatchNorm {
+        scale: usize,
+        epsilon: utils::F32,
+        gamma: ValTensor<F>,
+        beta: ValTensor<F>,
+        mean: ValTensor<F>,
+        var: ValTensor<F>,
+    },
+    LayerNorm {
+        scale: usize,
+        epsilon: utils::F32,
+        axes: Vec<usize>,
+        num_inputs: usize,
+        gamma: ValTensor<F>,
+        beta: ValTensor<F>,
+    },
 }
 
 impl<F: FieldExt + TensorType> Op<F> for HybridOp<F> {
@@ -43,49 +120,199 @@ impl<F: FieldExt + TensorType> Op<F> for HybridOp<F> {
             HybridOp::Mean { scale, .. } => {
                 Ok(tensor::ops::nonlinearities::mean(&inputs[0], *scale))
             }
-            HybridOp::Greater { .. } => Ok(inputs[0]
-                .iter()
-                .zip(inputs[1].iter())
-                .map(|(a, b)| if a > b { 1 } else { 0 })
-                .collect_vec()
-                .into_iter()
-                .into()),
+            HybridOp::Greater { a } => {
+                let mut inputs = inputs.to_vec();
+                if let Some(a) = a {
+                    inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
+                }
+                tensor::ops::greater(&inputs[0], &inputs[1])
+            }
+            HybridOp::GreaterEqual { a }
+            | HybridOp::Less { a }
+            | HybridOp::LessEqual { a }
+            | HybridOp::Equal { a }
+            | HybridOp::And { a }
+            | HybridOp::Or { a }
+            | HybridOp::Xor { a } => {
+                let mut inputs = inputs.to_vec();
+                if let Some(a) = a {
+                    inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
+                }
+                let (a, b) = (&inputs[0], &inputs[1]);
+                match self {
+                    HybridOp::GreaterEqual { .. } => tensor::ops::greater_equal(a, b),
+                    HybridOp::Less { .. } => tensor::ops::less(a, b),
+                    HybridOp::LessEqual { .. } => tensor::ops::less_equal(a, b),
+                    HybridOp::Equal { .. } => tensor::ops::equal(a, b),
+                    HybridOp::And { .. } => tensor::ops::and(a, b),
+                    HybridOp::Or { .. } => tensor::ops::or(a, b),
+                    _ => tensor::ops::xor(a, b),
+                }
+            }
+            HybridOp::Not => Ok(tensor::ops::not(&inputs[0])),
+            HybridOp::Iff => tensor::ops::iff(&inputs[0], &inputs[1], &inputs[2]),
             HybridOp::Max => Ok(Tensor::new(
                 Some(&[inputs[0].clone().into_iter().max().unwrap()]),
                 &[1],
             )?),
 
-            HybridOp::EltWiseMax { .. } => Ok(Tensor::new(
-                Some(&[inputs[0].clone().into_iter().max().unwrap()]),
-                &inputs[0].dims(),
-            )?),
+            HybridOp::EltWiseMax { a } => {
+                let mut inputs = inputs.to_vec();
+                if let Some(a) = a {
+                    inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
+                }
+                tensor::ops::eltwise_max(&inputs[0], &inputs[1])
+            }
+            HybridOp::EltWiseMin { a } => {
+                let mut inputs = inputs.to_vec();
+                if let Some(a) = a {
+                    inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
+                }
+                tensor::ops::eltwise_min(&inputs[0], &inputs[1])
+            }
+            HybridOp::Clip { min, max } => {
+                let min = Tensor::new(Some(&min.get_int_evals().unwrap()), min.dims())?;
+                let max = Tensor::new(Some(&max.get_int_evals().unwrap()), max.dims())?;
+                tensor::ops::eltwise_min(&tensor::ops::eltwise_max(&inputs[0], &min)?, &max)
+            }
 
             HybridOp::MaxPool2d {
                 padding,
                 stride,
                 pool_dims,
             } => tensor::ops::max_pool2d(&inputs[0], padding, stride, pool_dims),
+            HybridOp::AvgPool2d {
+                padding,
+                stride,
+                kernel_shape,
+                ceil_mode,
+                count_include_pad,
+                ..
+            } => tensor::ops::avg_pool2d(
+                &inputs[0],
+                *padding,
+                *stride,
+                *kernel_shape,
+                *ceil_mode,
+                *count_include_pad,
+            ),
             HybridOp::Min => Ok(Tensor::new(
                 Some(&[inputs[0].clone().into_iter().min().unwrap()]),
                 &[1],
             )?),
+            HybridOp::ArgMax { axis, select_last } => {
+                tensor::ops::argmax(&inputs[0], *axis, *select_last)
+            }
+            HybridOp::ArgMin { axis, select_last } => {
+                tensor::ops::argmin(&inputs[0], *axis, *select_last)
+            }
+            HybridOp::Embedding { index_scale } => {
+                tensor::ops::embedding(&inputs[0], &inputs[1], *index_scale)
+            }
             HybridOp::PReLU { scale, slopes } => Ok(tensor::ops::nonlinearities::prelu(
                 &inputs[0],
                 *scale,
                 &slopes.iter().map(|e| e.0).collect_vec(),
             )),
+            HybridOp::Softmax { scales, bits, mask } => {
+                let scale_recip = tensor::ops::nonlinearities::softmax_recip_scale(scales.1, *bits);
+                Ok(match mask {
+                    Some(mask) => tensor::ops::nonlinearities::masked_softmax(
+                        &inputs[0],
+                        &Tensor::new(Some(&mask.get_int_evals().unwrap()), mask.dims())?,
+                        scales.0,
+                        scales.1,
+                        scale_recip,
+                    ),
+                    None => tensor::ops::nonlinearities::softmax(
+                        &inputs[0],
+                        scales.0,
+                        scales.1,
+                        scale_recip,
+                    ),
+                })
+            }
+            HybridOp::InstanceNorm2d {
+                scale,
+                epsilon,
+                gamma,
+                beta,
+                ..
+            } => Ok(tensor::ops::nonlinearities::instance_norm(
+                [
+                    inputs[0].clone(),
+                    Tensor::new(Some(&gamma.get_int_evals().unwrap()), gamma.dims())?,
+                    Tensor::new(Some(&beta.get_int_evals().unwrap()), beta.dims())?,
+                ],
+                *scale,
+                epsilon.0,
+            )),
+            HybridOp::BatchNorm {
+                scale,
+                epsilon,
+                gamma,
+                beta,
+                mean,
+                var,
+            } => Ok(tensor::ops::nonlinearities::batch_norm(
+                [
+                    inputs[0].clone(),
+                    Tensor::new(Some(&gamma.get_int_evals().unwrap()), gamma.dims())?,
+                    Tensor::new(Some(&beta.get_int_evals().unwrap()), beta.dims())?,
+                    Tensor::new(Some(&mean.get_int_evals().unwrap()), mean.dims())?,
+                    Tensor::new(Some(&var.get_int_evals().unwrap()), var.dims())?,
+                ],
+                *scale,
+                epsilon.0,
+            )),
+            HybridOp::LayerNorm {
+                scale,
+                epsilon,
+                axes,
+                gamma,
+                beta,
+                ..
+            } => Ok(tensor::ops::nonlinearities::layer_norm(
+                [
+                    inputs[0].clone(),
+                    Tensor::new(Some(&gamma.get_int_evals().unwrap()), gamma.dims())?,
+                    Tensor::new(Some(&beta.get_int_evals().unwrap()), beta.dims())?,
+                ],
+                axes,
+                *scale,
+                epsilon.0,
+            )),
         }
     }
 
     fn as_str(&self) -> &'static str {
         match &self {
             HybridOp::EltWiseMax { .. } => "ELTWISEMAX",
+            HybridOp::EltWiseMin { .. } => "ELTWISEMIN",
+            HybridOp::Clip { .. } => "CLIP",
             HybridOp::Mean { .. } => "MEAN",
             HybridOp::Max => "MAX",
             HybridOp::Greater { .. } => "GREATER",
+            HybridOp::GreaterEqual { .. } => "GREATEREQUAL",
+            HybridOp::Less { .. } => "LESS",
+            HybridOp::LessEqual { .. } => "LESSEQUAL",
+            HybridOp::Equal { .. } => "EQUAL",
+            HybridOp::And { .. } => "AND",
+            HybridOp::Or { .. } => "OR",
+            HybridOp::Xor { .. } => "XOR",
+            HybridOp::Not => "NOT",
+            HybridOp::Iff => "IFF",
             HybridOp::MaxPool2d { .. } => "MAXPOOL2D",
+            HybridOp::AvgPool2d { .. } => "AVGPOOL2D",
             HybridOp::Min => "MIN",
+            HybridOp::ArgMax { .. } => "ARGMAX",
+            HybridOp::ArgMin { .. } => "ARGMIN",
+            HybridOp::Embedding { .. } => "EMBEDDING",
             HybridOp::PReLU { .. } => "PRELU",
+            HybridOp::Softmax { .. } => "SOFTMAX",
+            HybridOp::InstanceNorm2d { .. } => "INSTANCENORM",
+            HybridOp::BatchNorm { .. } => "BATCHNORM",
+            HybridOp::LayerNorm { .. } => "LAYERNORM",
         }
     }
 
@@ -109,14 +336,72 @@ impl<F: FieldExt + TensorType> Op<F> for HybridOp<F> {
                 if let Some(a) = a {
                     values.push(a.clone());
                 }
-                todo!("EltWiseMax")
+                Some(layouts::eltwise_max(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    offset,
+                )?)
             }
-            HybridOp::Greater { a } => {
+            HybridOp::EltWiseMin { a } => {
+                if let Some(a) = a {
+                    values.push(a.clone());
+                }
+                Some(layouts::eltwise_min(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    offset,
+                )?)
+            }
+            HybridOp::Clip { min, max } => {
+                values.extend([min.clone(), max.clone()]);
+                Some(layouts::clip(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    offset,
+                )?)
+            }
+            HybridOp::Greater { a }
+            | HybridOp::GreaterEqual { a }
+            | HybridOp::Less { a }
+            | HybridOp::LessEqual { a }
+            | HybridOp::Equal { a }
+            | HybridOp::And { a }
+            | HybridOp::Or { a }
+            | HybridOp::Xor { a } => {
                 if let Some(a) = a {
                     values.push(a.clone());
                 }
-                todo!()
+                let values: &[ValTensor<F>; 2] = values[..].try_into()?;
+                Some(match self {
+                    HybridOp::Greater { .. } => layouts::greater(config, region, values, offset)?,
+                    HybridOp::GreaterEqual { .. } => {
+                        layouts::greater_equal(config, region, values, offset)?
+                    }
+                    HybridOp::Less { .. } => layouts::less(config, region, values, offset)?,
+                    HybridOp::LessEqual { .. } => {
+                        layouts::less_equal(config, region, values, offset)?
+                    }
+                    HybridOp::Equal { .. } => layouts::equal(config, region, values, offset)?,
+                    HybridOp::And { .. } => layouts::and(config, region, values, offset)?,
+                    HybridOp::Or { .. } => layouts::or(config, region, values, offset)?,
+                    _ => layouts::xor(config, region, values, offset)?,
+                })
             }
+            HybridOp::Not => Some(layouts::not(
+                config,
+                region,
+                values[..].try_into()?,
+                offset,
+            )?),
+            HybridOp::Iff => Some(layouts::iff(
+                config,
+                region,
+                values[..].try_into()?,
+                offset,
+            )?),
             HybridOp::Mean { scale, .. } => Some(layouts::mean(
                 config,
                 region,
@@ -137,6 +422,24 @@ impl<F: FieldExt + TensorType> Op<F> for HybridOp<F> {
                 *pool_dims,
                 offset,
             )?),
+            HybridOp::AvgPool2d {
+                padding,
+                stride,
+                kernel_shape,
+                ceil_mode,
+                count_include_pad,
+                ..
+            } => Some(layouts::avg_pool2d(
+                config,
+                region,
+                values[..].try_into()?,
+                *padding,
+                *stride,
+                *kernel_shape,
+                *ceil_mode,
+                *count_include_pad,
+                offset,
+            )?),
             HybridOp::Max => Some(layouts::max(
                 config,
                 region,
@@ -149,46 +452,329 @@ impl<F: FieldExt + TensorType> Op<F> for HybridOp<F> {
                 values[..].try_into()?,
                 offset,
             )?),
+            HybridOp::ArgMax { axis, select_last } => Some(layouts::argmax(
+                config,
+                region,
+                values[..].try_into()?,
+                *axis,
+                *select_last,
+                offset,
+            )?),
+            HybridOp::ArgMin { axis, select_last } => Some(layouts::argmin(
+                config,
+                region,
+                values[..].try_into()?,
+                *axis,
+                *select_last,
+                offset,
+            )?),
+            HybridOp::Embedding { index_scale } => Some(layouts::embedding(
+                config,
+                region,
+                values[..].try_into()?,
+                *index_scale,
+                offset,
+            )?),
+            HybridOp::Softmax { scales, bits, mask } => Some(layouts::softmax(
+                config,
+                region,
+                values[..].try_into()?,
+                mask.as_ref(),
+                *scales,
+                tensor::ops::nonlinearities::softmax_recip_scale(scales.1, *bits),
+                offset,
+            )?),
+            HybridOp::InstanceNorm2d {
+                scale,
+                epsilon,
+                gamma,
+                beta,
+                ..
+            } => {
+                values.push(gamma.clone());
+                values.push(beta.clone());
+                Some(layouts::instance_norm(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    *scale,
+                    epsilon.0,
+                    offset,
+                )?)
+            }
+            HybridOp::BatchNorm {
+                scale,
+                epsilon,
+                gamma,
+                beta,
+                mean,
+                var,
+            } => {
+                values.extend([gamma.clone(), beta.clone(), mean.clone(), var.clone()]);
+                Some(layouts::batch_norm(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    *scale,
+                    epsilon.0,
+                    offset,
+                )?)
+            }
+            HybridOp::LayerNorm {
+                scale,
+                epsilon,
+                axes,
+                gamma,
+                beta,
+                ..
+            } => {
+                values.extend([gamma.clone(), beta.clone()]);
+                Some(layouts::layer_norm(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    axes,
+                    *scale,
+                    epsilon.0,
+                    offset,
+                )?)
+            }
         })
     }
 
-    fn out_scale(&self, in_scales: Vec<u32>, _: u32) -> u32 {
-        in_scales[0]
+    fn out_scale(&self, in_scales: Vec<u32>, global_scale: u32) -> u32 {
+        match self {
+            // the normalised exponentials are multiplied by a reciprocal, both at the global scale
+            HybridOp::Softmax { .. } => 2 * global_scale,
+            // the normalised input is multiplied by gamma, both at the input scale
+            HybridOp::InstanceNorm2d { .. }
+            | HybridOp::BatchNorm { .. }
+            | HybridOp::LayerNorm { .. } => 2 * in_scales[0],
+            // indices and booleans are plain integers
+            HybridOp::ArgMax { .. }
+            | HybridOp::ArgMin { .. }
+            | HybridOp::Greater { .. }
+            | HybridOp::GreaterEqual { .. }
+            | HybridOp::Less { .. }
+            | HybridOp::LessEqual { .. }
+            | HybridOp::Equal { .. }
+            | HybridOp::And { .. }
+            | HybridOp::Or { .. }
+            | HybridOp::Xor { .. }
+            | HybridOp::Not => 0,
+            // the selected values share a scale once rescaled
+            HybridOp::Iff => in_scales[1],
+            _ => in_scales[0],
+        }
     }
 
     fn has_3d_input(&self) -> bool {
-        matches!(self, HybridOp::MaxPool2d { .. })
+        matches!(
+            self,
+            HybridOp::MaxPool2d { .. }
+                | HybridOp::AvgPool2d { .. }
+                | HybridOp::InstanceNorm2d { .. }
+                | HybridOp::BatchNorm { .. }
+        )
+    }
+
+    fn requires_homogenous_input_scales(&self) -> bool {
+        matches!(
+            self,
+            HybridOp::EltWiseMax { a: None }
+                | HybridOp::EltWiseMin { a: None }
+                | HybridOp::Greater { a: None }
+                | HybridOp::GreaterEqual { a: None }
+                | HybridOp::Less { a: None }
+                | HybridOp::LessEqual { a: None }
+                | HybridOp::Equal { a: None }
+        )
     }
 
     fn rescale(&self, inputs_scale: Vec<u32>, global_scale: u32) -> Box<dyn Op<F>> {
-        let mult = scale_to_multiplier(inputs_scale[0] - global_scale);
         match self {
+            // the mask is boolean, only the selected values need a common scale
+            HybridOp::Iff if inputs_scale[1] != inputs_scale[2] => {
+                let max_scale = inputs_scale[1].max(inputs_scale[2]);
+                Box::new(crate::circuit::Rescaled {
+                    inner: Box::new(self.clone()),
+                    scale: vec![
+                        (0, 1),
+                        (1, scale_to_multiplier(max_scale - inputs_scale[1]) as usize),
+                        (2, scale_to_multiplier(max_scale - inputs_scale[2]) as usize),
+                    ],
+                })
+            }
+            HybridOp::Embedding { .. } => Box::new(HybridOp::Embedding {
+                index_scale: scale_to_multiplier(inputs_scale[1]) as usize,
+            }),
             HybridOp::PReLU { scale: _, slopes } => Box::new(HybridOp::PReLU {
-                scale: mult as usize,
+                scale: scale_to_multiplier(inputs_scale[0] - global_scale) as usize,
                 slopes: slopes.to_vec(),
             }),
             HybridOp::Mean {
                 scale: _,
                 num_inputs,
             } => Box::new(HybridOp::Mean {
-                scale: mult as usize,
+                scale: scale_to_multiplier(inputs_scale[0] - global_scale) as usize,
                 num_inputs: *num_inputs,
             }),
+            HybridOp::Softmax { bits, mask, .. } => Box::new(HybridOp::Softmax {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                bits: *bits,
+                mask: mask.clone(),
+            }),
+            HybridOp::InstanceNorm2d {
+                epsilon,
+                num_inputs,
+                gamma,
+                beta,
+                ..
+            } => Box::new(HybridOp::InstanceNorm2d {
+                scale: scale_to_multiplier(inputs_scale[0]) as usize,
+                epsilon: *epsilon,
+                num_inputs: *num_inputs,
+                gamma: gamma.clone(),
+                beta: beta.clone(),
+            }),
+            HybridOp::BatchNorm {
+                epsilon,
+                gamma,
+                beta,
+                mean,
+                var,
+                ..
+            } => Box::new(HybridOp::BatchNorm {
+                scale: scale_to_multiplier(inputs_scale[0]) as usize,
+                epsilon: *epsilon,
+                gamma: gamma.clone(),
+                beta: beta.clone(),
+                mean: mean.clone(),
+                var: var.clone(),
+            }),
+            HybridOp::LayerNorm {
+                epsilon,
+                axes,
+                num_inputs,
+                gamma,
+                beta,
+                ..
+            } => Box::new(HybridOp::LayerNorm {
+                scale: scale_to_multiplier(inputs_scale[0]) as usize,
+                epsilon: *epsilon,
+                axes: axes.clone(),
+                num_inputs: *num_inputs,
+                gamma: gamma.clone(),
+                beta: beta.clone(),
+            }),
             _ => Box::new(self.clone()),
         }
     }
 
-    fn required_lookup(&self) -> Option<LookupOp> {
+    fn required_lookups(&self) -> Vec<LookupOp> {
         match self {
-            HybridOp::PReLU { scale, .. } => Some(LookupOp::ReLU { scale: *scale }),
+            HybridOp::PReLU { scale, .. } => vec![LookupOp::ReLU { scale: *scale }],
             HybridOp::Max
             | HybridOp::Min
+            | HybridOp::ArgMax { .. }
+            | HybridOp::ArgMin { .. }
             | HybridOp::MaxPool2d { .. }
             | HybridOp::Greater { .. }
-            | HybridOp::EltWiseMax { .. } => Some(LookupOp::ReLU { scale: 1 }),
-            HybridOp::Mean { scale, num_inputs } => Some(LookupOp::Div {
+            | HybridOp::GreaterEqual { .. }
+            | HybridOp::Less { .. }
+            | HybridOp::LessEqual { .. }
+            | HybridOp::Equal { .. }
+            | HybridOp::EltWiseMax { .. }
+            | HybridOp::EltWiseMin { .. }
+            | HybridOp::Clip { .. } => vec![LookupOp::ReLU { scale: 1 }],
+            HybridOp::Embedding { index_scale } => {
+                let mut lookups = vec![LookupOp::ReLU { scale: 1 }];
+                if *index_scale > 1 {
+                    lookups.push(LookupOp::Div {
+                        denom: utils::F32(*index_scale as f32),
+                    });
+                }
+                lookups
+            }
+            HybridOp::AvgPool2d {
+                padding,
+                stride,
+                kernel_shape,
+                ceil_mode,
+                count_include_pad,
+                image_dims,
+            } => {
+                // the geometry is validated when the op is loaded
+                let (_, divisors) = tensor::ops::avg_pool2d_geometry(
+                    *image_dims,
+                    *padding,
+                    *stride,
+                    *kernel_shape,
+                    *ceil_mode,
+                    *count_include_pad,
+                )
+                .expect("avg pool: invalid geometry");
+                // one table per distinct window size
+                divisors
+                    .iter()
+                    .unique()
+                    .map(|d| LookupOp::Div {
+                        denom: utils::F32(*d as f32),
+                    })
+                    .collect()
+            }
+            HybridOp::Mean { scale, num_inputs } => vec![LookupOp::Div {
                 denom: utils::F32((*scale * *num_inputs) as f32),
-            }),
+            }],
+            HybridOp::Softmax { scales, bits, .. } => {
+                let scale_recip = tensor::ops::nonlinearities::softmax_recip_scale(scales.1, *bits);
+                let mut lookups = vec![
+                    // the row maxes
+                    LookupOp::ReLU { scale: 1 },
+                    LookupOp::Exp { scales: *scales },
+                    LookupOp::Recip {
+                        scales: (scales.1, scale_recip),
+                    },
+                ];
+                if scale_recip != scales.1 {
+                    lookups.push(LookupOp::Div {
+                        denom: utils::F32((scale_recip / scales.1) as f32),
+                    });
+                }
+                lookups
+            }
+            HybridOp::InstanceNorm2d {
+                scale, num_inputs, ..
+            }
+            | HybridOp::LayerNorm {
+                scale, num_inputs, ..
+            } => vec![
+                // per channel (or per slice) mean
+                LookupOp::Div {
+                    denom: utils::F32(*num_inputs as f32),
+                },
+                // per channel (or per slice) variance
+                LookupOp::Div {
+                    denom: utils::F32((*scale * *num_inputs) as f32),
+                },
+                LookupOp::Rsqrt {
+                    scales: (*scale, *scale),
+                },
+                LookupOp::Div {
+                    denom: utils::F32(*scale as f32),
+                },
+            ],
+            HybridOp::BatchNorm { scale, .. } => vec![
+                LookupOp::Rsqrt {
+                    scales: (*scale, *scale),
+                },
+                LookupOp::Div {
+                    denom: utils::F32(*scale as f32),
+                },
+            ],
         }
     }
 
@@ -9,12 +9,23 @@ use crate::{
     circuit::{ops::base::BaseOp, utils, BaseConfig, CheckMode, CircuitError},
     fieldutils::i128_to_felt,
     tensor::{
+        get_broadcasted_shape,
         ops::{
-            accumulated, add, affine as non_accum_affine, convolution as non_accum_conv,
-            dot as non_accum_dot, matmul as non_accum_matmul, max_pool2d as non_accum_max_pool2d,
-            mult, nonlinearities::prelu as ref_prelu, pack as non_accum_pack,
-            rescale as ref_rescaled, scale_and_shift as ref_scale_and_shift, sub,
-            sum as non_accum_sum, sumpool as non_accum_sumpool,
+            accumulated, add, affine as non_accum_affine, and as ref_and, argmax as ref_argmax,
+            argmin as ref_argmin, avg_pool2d as ref_avg_pool2d, avg_pool2d_geometry,
+            axes_last_permutation, convolution as non_accum_conv, dot as non_accum_dot,
+            eltwise_max as ref_eltwise_max, eltwise_min as ref_eltwise_min,
+            embedding as ref_embedding, equal as ref_equal, greater as ref_greater,
+            greater_equal as ref_greater_equal, iff as ref_iff, less_equal as ref_less_equal,
+            matmul as non_accum_matmul, max_pool2d as non_accum_max_pool2d, mult,
+            nonlinearities::{
+                batch_norm as ref_batch_norm, instance_norm as ref_instance_norm,
+                layer_norm as ref_layer_norm, masked_softmax as ref_masked_softmax,
+                prelu as ref_prelu, softmax as ref_softmax,
+            },
+            not as ref_not, or as ref_or, pack as non_accum_pack, rescale as ref_rescaled,
+            scale_and_shift as ref_scale_and_shift, sub, sum as non_accum_sum,
+            sumpool as non_accum_sumpool, xor as ref_xor, PadMode,
         },
         Tensor, TensorError, ValType,
     },
@@ -191,47 +202,11 @@ pub fn pairwise<F: FieldExt + TensorType>(
         ))));
     }
 
+    // broadcast both inputs to a common shape
+    let broadcasted_shape = get_broadcasted_shape(values[0].dims(), values[1].dims())?;
     let (mut lhs, mut rhs) = (values[0].clone(), values[1].clone());
-
-    // casts ND
-    if rhs.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-        && lhs.dims().len() > 1
-        && lhs.dims() != rhs.dims()
-    {
-        if lhs.dims()[0] != rhs.dims().iter().product::<usize>() {
-            return Err(Box::new(CircuitError::DimMismatch(format!(
-                "pairwise {} layout",
-                op.as_str()
-            ))));
-        }
-        rhs.reshape(&[lhs.dims()[0]])?;
-        rhs.repeat_rows(lhs.dims()[1..].iter().product::<usize>())?;
-        rhs.reshape(lhs.dims())?;
-    }
-    // make ND commutative
-    else if lhs.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-        && rhs.dims().len() > 1
-        && lhs.dims() != rhs.dims()
-    {
-        if rhs.dims()[0] != lhs.dims().iter().product::<usize>() {
-            return Err(Box::new(CircuitError::DimMismatch(format!(
-                "pairwise {} layout",
-                op.as_str()
-            ))));
-        }
-        lhs.reshape(&[rhs.dims()[0]])?;
-        lhs.tile(rhs.dims()[1..].iter().product::<usize>())?;
-        lhs.reshape(rhs.dims())?;
-    // 1D casting
-    } else if rhs.dims().len() == 1 && rhs.dims()[0] == 1 {
-        rhs.tile(lhs.dims().iter().product::<usize>())?;
-        rhs.reshape(lhs.dims())?;
-    }
-    // make 1D casting commutative
-    else if lhs.dims().len() == 1 && lhs.dims()[0] == 1 {
-        lhs.tile(rhs.dims().iter().product::<usize>())?;
-        lhs.reshape(rhs.dims())?;
-    }
+    lhs.expand(&broadcasted_shape)?;
+    rhs.expand(&broadcasted_shape)?;
 
     let mut inputs = vec![];
 
@@ -272,7 +247,7 @@ pub fn pairwise<F: FieldExt + TensorType>(
 
     *offset += output.len();
 
-    output.reshape(lhs.dims())?;
+    output.reshape(&broadcasted_shape)?;
 
     Ok(output)
 }
@@ -300,20 +275,20 @@ pub fn matmul<F: FieldExt + TensorType>(
         b.reshape(&[b.dims()[0], 1])?;
     }
 
-    // number of stacked matrices
-    let num_stacked_a = Vec::from(&a.dims()[0..a.dims().len() - 2])
-        .iter()
-        .product::<usize>();
-    let num_stacked_b = Vec::from(&b.dims()[0..b.dims().len() - 2])
-        .iter()
-        .product::<usize>();
-    if num_stacked_a != num_stacked_b {
-        return Err(Box::new(CircuitError::DimMismatch(
-            "accum matmul layout".to_string(),
-        )));
-    };
+    // the batch dims are broadcast
+    let batch_dims = get_broadcasted_shape(
+        &a.dims()[0..a.dims().len() - 2],
+        &b.dims()[0..b.dims().len() - 2],
+    )?;
+    for t in [&mut a, &mut b] {
+        let dims = [&batch_dims[..], &t.dims()[t.dims().len() - 2..]].concat();
+        if t.dims() != dims {
+            t.expand(&dims)?;
+        }
+    }
 
-    let num_stacked = num_stacked_a;
+    // number of stacked matrices
+    let num_stacked = batch_dims.iter().product::<usize>();
 
     // [m,n]
     let mut a_stacked = a.clone();
@@ -585,8 +560,10 @@ pub fn sumpool<F: FieldExt + TensorType>(
             config,
             region.as_deref_mut(),
             &[values[0].get_slice(&[i..i + 1])?, kernel.clone().into()],
-            padding,
-            stride,
+            &[padding.0, padding.1],
+            &[stride.0, stride.1],
+            &[1, 1],
+            1,
             offset,
         )?);
     }
@@ -625,7 +602,7 @@ pub fn max_pool2d<F: FieldExt + TensorType>(
     config: &mut BaseConfig<F>,
     mut region: Option<&mut Region<F>>,
     values: &[ValTensor<F>; 1],
-    padding: (usize, usize),
+    padding: [(usize, usize); 2],
     stride: (usize, usize),
     pool_dims: (usize, usize),
     offset: &mut usize,
@@ -641,10 +618,13 @@ pub fn max_pool2d<F: FieldExt + TensorType>(
     let (image_height, image_width) = (image_dims[1], image_dims[2]);
 
     let mut padded_image = image.clone();
-    padded_image.pad(padding)?;
+    padded_image.pad(
+        &[(0, 0), padding[0], padding[1]],
+        &PadMode::Constant(ValType::zero().unwrap()),
+    )?;
 
-    let horz_slides = (image_height + 2 * padding.0 - pool_dims.0) / stride.0 + 1;
-    let vert_slides = (image_width + 2 * padding.1 - pool_dims.1) / stride.1 + 1;
+    let horz_slides = (image_height + padding[0].0 + padding[0].1 - pool_dims.0) / stride.0 + 1;
+    let vert_slides = (image_width + padding[1].0 + padding[1].1 - pool_dims.1) / stride.1 + 1;
 
     let mut output: Tensor<ValType<F>> =
         Tensor::new(None, &[input_channels, horz_slides, vert_slides]).unwrap();
@@ -688,64 +668,75 @@ pub fn max_pool2d<F: FieldExt + TensorType>(
     Ok(res)
 }
 
-/// Convolution accumulated layout
+/// Convolution accumulated layout. Supports any number of spatial dimensions, which is inferred from the kernel,
+/// as well as dilated and grouped (e.g depthwise) convolutions.
 pub fn conv<F: FieldExt + TensorType>(
     config: &mut BaseConfig<F>,
     region: Option<&mut Region<F>>,
     values: &[ValTensor<F>],
-    padding: (usize, usize),
-    stride: (usize, usize),
+    padding: &[(usize, usize)],
+    stride: &[usize],
+    dilation: &[usize],
+    groups: usize,
     offset: &mut usize,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
     let has_bias = values.len() == 3;
     let (image, kernel) = (values[0].clone(), values[1].clone());
 
-    if (image.dims().len() != 3)
-        || (kernel.dims().len() != 4)
-        || (image.dims()[0] != kernel.dims()[1])
+    if (kernel.dims().len() < 3)
+        || (image.dims().len() != kernel.dims().len() - 1)
+        || (padding.len() != kernel.dims().len() - 2)
+        || (stride.len() != kernel.dims().len() - 2)
+        || (dilation.len() != kernel.dims().len() - 2)
     {
         return Err(Box::new(TensorError::DimMismatch("conv".to_string())));
     }
 
-    let image_dims = image.dims();
-    let kernel_dims = kernel.dims();
+    if (groups == 0)
+        || (image.dims()[0] != kernel.dims()[1] * groups)
+        || (kernel.dims()[0] % groups != 0)
+    {
+        return Err(Box::new(TensorError::DimMismatch(
+            "conv groups".to_string(),
+        )));
+    }
 
-    let (output_channels, _input_channels, kernel_height, kernel_width) = (
-        kernel_dims[0],
-        kernel_dims[1],
-        kernel_dims[2],
-        kernel_dims[3],
-    );
+    let output_channels = kernel.dims()[0];
 
-    let (image_height, image_width) = (image_dims[1], image_dims[2]);
-    let padded_height = image_height + 2 * padding.0;
-    let padded_width = image_width + 2 * padding.1;
+    let padded_dims = image.dims()[1..]
+        .iter()
+        .zip(padding)
+        .map(|(d, (before, after))| d + before + after)
+        .collect::<Vec<_>>();
 
-    let vert_slides = (padded_height - kernel_height) / stride.0 + 1;
-    let horz_slides = (padded_width - kernel_width) / stride.1 + 1;
+    let slides = padded_dims
+        .iter()
+        .zip(&kernel.dims()[2..])
+        .zip(stride.iter().zip(dilation))
+        .map(|((d, k), (s, dil))| (d - (k - 1) * dil - 1) / s + 1)
+        .collect::<Vec<_>>();
 
     let mut padded_image = image.clone();
-    padded_image.pad(padding)?;
+    padded_image.pad(
+        &[(0, 0)]
+            .into_iter()
+            .chain(padding.iter().copied())
+            .collect::<Vec<_>>(),
+        &PadMode::Constant(ValType::zero().unwrap()),
+    )?;
     padded_image.flatten();
     padded_image.reshape(&[padded_image.dims()[0], 1])?;
 
     let mut expanded_kernel = kernel.clone();
 
-    expanded_kernel.multi_ch_blocked_toeplitz(
-        vert_slides,
-        padded_height,
-        horz_slides,
-        padded_width,
-        stride.0,
-        stride.1,
-    )?;
+    expanded_kernel.multi_ch_toeplitz(&padded_dims, stride, dilation, groups)?;
 
     let mut res = if has_bias {
         let mut tiled_bias = values[2].clone();
         if (tiled_bias.dims().len() != 1) || (tiled_bias.dims()[0] != kernel.dims()[0]) {
             return Err(Box::new(TensorError::DimMismatch("conv bias".to_string())));
         }
-        tiled_bias.repeat_rows(vert_slides * horz_slides)?;
+        tiled_bias.repeat_rows(slides.iter().product())?;
         tiled_bias.flatten();
         tiled_bias.reshape(&[tiled_bias.dims()[0], 1])?;
 
@@ -759,7 +750,7 @@ pub fn conv<F: FieldExt + TensorType>(
         matmul(config, region, &[expanded_kernel, padded_image], offset)?
     };
 
-    res.reshape(&[output_channels, vert_slides, horz_slides])?;
+    res.reshape(&[&[output_channels], &slides[..]].concat())?;
 
     if matches!(&config.check_mode, CheckMode::SAFE) {
         // during key generation this will be 0 so we use this as a flag to check
@@ -775,6 +766,8 @@ pub fn conv<F: FieldExt + TensorType>(
                     .collect::<Vec<Tensor<_>>>(),
                 padding,
                 stride,
+                dilation,
+                groups,
             )
             .map_err(|e| {
                 error!("{}", e);
@@ -1195,247 +1188,1668 @@ pub fn mean<F: FieldExt + TensorType>(
     nonlinearity(config, region, &[sum_x], &nl, offset)
 }
 
-/// max layout
-pub fn max<F: FieldExt + TensorType>(
+/// softmax layout, applied along the last axis of the input.
+/// The max of each row is subtracted before exponentiating, and the reciprocal of each row sum is taken at
+/// `scale_recip` (see [crate::tensor::ops::nonlinearities::softmax_recip_scale]) before rescaling the output.
+/// If `mask` is set, positions where it is 0 are excluded from the normalisation (see [crate::tensor::ops::nonlinearities::masked_softmax]).
+pub fn softmax<F: FieldExt + TensorType>(
     config: &mut BaseConfig<F>,
     mut region: Option<&mut Region<F>>,
     values: &[ValTensor<F>; 1],
+    mask: Option<&ValTensor<F>>,
+    scales: (usize, usize),
+    scale_recip: usize,
     offset: &mut usize,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
-    // this is safe because we later constrain it
-    let max_int = values[0].get_int_evals()?.into_iter().max();
-    let max_val: ValTensor<F> = match max_int {
-        None => Tensor::new(Some(&[Value::<F>::unknown()]), &[1])?.into(),
-        Some(i) => Tensor::new(Some(&[Value::known(i128_to_felt::<F>(i))]), &[1])?.into(),
-    };
-
-    let assigned_max_val: ValTensor<F> =
-        config.inputs[1].assign(region.as_deref_mut(), *offset, &max_val)?;
-    *offset += 1;
-
-    let unit: ValTensor<F> = if let Some(region) = region.as_deref_mut() {
-        Tensor::from(
-            vec![config.inputs[1].assign_constant(region, *offset, F::from(1))?].into_iter(),
-        )
-        .into()
-    } else {
-        // for dummy run throughs
-        Tensor::from(vec![Value::known(F::from(1))].into_iter()).into()
-    };
-    *offset += 1;
-
-    // max(x - 1)
-    let max_minus_1 = pairwise(
+    let x = &values[0];
+    let row_len = x.dims().last().copied().unwrap_or(1).max(1);
+    let num_rows = x.len() / row_len;
+    let mut flat_x = x.clone();
+    flat_x.flatten();
+
+    // the (constrained) max of each row, subtracted so that the exponentials are at most the output scale
+    let mut maxes: Option<ValTensor<F>> = None;
+    for i in 0..num_rows {
+        let row = flat_x.get_slice(&[i * row_len..(i + 1) * row_len])?;
+        let row_max = max(config, region.as_deref_mut(), &[row], offset)?;
+        maxes = Some(match maxes {
+            Some(m) => m.concat(row_max)?,
+            None => row_max,
+        });
+    }
+    let mut maxes = maxes.ok_or(CircuitError::DimMismatch("softmax layout".to_string()))?;
+    maxes.reshape(&[num_rows])?;
+    maxes.repeat_rows(row_len)?;
+    maxes.reshape(&[x.len()])?;
+    let mut shifted = pairwise(
         config,
         region.as_deref_mut(),
-        &[assigned_max_val.clone(), unit.clone()],
+        &[flat_x, maxes],
         offset,
         BaseOp::Sub,
     )?;
+    shifted.reshape(x.dims())?;
 
-    // x - max(x - 1)
-    let diff = pairwise(
+    let mut exp = masked_exp(
         config,
         region.as_deref_mut(),
-        &[values[0].clone(), max_minus_1],
-        offset,
-        BaseOp::Sub,
-    )?;
-    // relu(x - max(x - 1))
-    let relu = nonlinearity(
-        config,
-        region.as_deref_mut(),
-        &[diff],
-        &LookupOp::ReLU { scale: 1 },
+        &shifted,
+        mask,
+        scales,
         offset,
     )?;
+    exp.flatten();
+
+    // the normalising denominator of each row
+    let mut denoms: Option<ValTensor<F>> = None;
+    for i in 0..num_rows {
+        let row = exp.get_slice(&[i * row_len..(i + 1) * row_len])?;
+        let denom = sum(config, region.as_deref_mut(), &[row], offset)?;
+        denoms = Some(match denoms {
+            Some(d) => d.concat(denom)?,
+            None => denom,
+        });
+    }
+    let mut denoms = denoms.ok_or(CircuitError::DimMismatch("softmax layout".to_string()))?;
+    denoms.reshape(&[num_rows])?;
 
-    let len = relu.dims().iter().product();
-
-    // y_i*(1 - y_i) =0 // assert the values are either 0 or 1
-    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu)?;
-    if let Some(region) = region.as_deref_mut() {
-        for i in 0..len {
-            let (x, y) = config.output.cartesian_coord(*offset + i);
-            config
-                .selectors
-                .get(&(BaseOp::IsBoolean, x))
-                .unwrap()
-                .enable(region, y)?;
+    let recip = LookupOp::Recip {
+        scales: (scales.1, scale_recip),
+    };
+    // each exponential is at most the output scale, so long rows can push a row sum past the reciprocal table
+    if let Some(table) = config.tables.get(&recip) {
+        let bound = 1i128 << (table.bits - 1);
+        if let Some(d) = denoms
+            .get_int_evals()?
+            .into_iter()
+            .find(|d| *d < -bound || *d >= bound)
+        {
+            return Err(Box::new(CircuitError::LookupOutOfRange(
+                d,
+                table.bits,
+                "softmax denominator".to_string(),
+            )));
         }
     }
-    *offset += len;
 
-    // sum(relu(x - max(x - 1)))
-    let sum_relu = sum(config, region.as_deref_mut(), &[relu], offset)?;
-    // 1 - sum(relu(x - max(x - 1)))
-    let one_minus_sum_relu = pairwise(
-        config,
-        region.as_deref_mut(),
-        &[unit, sum_relu],
-        offset,
-        BaseOp::Sub,
-    )?;
-    // relu(1 - sum(relu(x - max(x - 1))))
-    let relu_one_minus_sum_relu = nonlinearity(
+    let mut inv_denoms = nonlinearity(config, region.as_deref_mut(), &[denoms], &recip, offset)?;
+    inv_denoms.repeat_rows(row_len)?;
+    inv_denoms.reshape(&[x.len()])?;
+
+    let mut softmax = pairwise(
         config,
         region.as_deref_mut(),
-        &[one_minus_sum_relu],
-        &LookupOp::ReLU { scale: 1 },
+        &[exp, inv_denoms],
         offset,
+        BaseOp::Mult,
     )?;
-
-    // constraining relu(sum(relu(x - max(x - 1)) - len(x))) = 0
-    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu_one_minus_sum_relu)?;
-
-    if let Some(region) = region {
-        let (x, y) = config.output.cartesian_coord(*offset);
-        config
-            .selectors
-            .get(&(BaseOp::IsZero, x))
-            .unwrap()
-            .enable(region, y)?;
+    // rescale from the reciprocal's scale back to the output scale
+    if scale_recip != scales.1 {
+        let rescale = LookupOp::Div {
+            denom: utils::F32((scale_recip / scales.1) as f32),
+        };
+        softmax = nonlinearity(config, region, &[softmax], &rescale, offset)?;
     }
-    *offset += relu_one_minus_sum_relu.len();
+    softmax.reshape(x.dims())?;
 
     if matches!(&config.check_mode, CheckMode::SAFE) {
         // during key generation this will be 0 so we use this as a flag to check
         // TODO: this isn't very safe and would be better to get the phase directly
-        let is_assigned = !Into::<Tensor<i32>>::into(assigned_max_val.get_inner()?)
+        let is_assigned = !Into::<Tensor<i32>>::into(softmax.get_inner()?)
             .iter()
             .all(|&x| x == 0);
         if is_assigned {
-            let ref_max: Tensor<i32> = Tensor::new(
-                Some(&[values[0].get_int_evals()?.into_iter().max().unwrap() as i32]),
-                &[1],
-            )?;
+            let mut int_input: Tensor<i128> = x.get_int_evals()?.into_iter().into();
+            int_input.reshape(x.dims());
+            let ref_softmax = match mask {
+                Some(mask) => {
+                    let int_mask = Tensor::new(Some(&mask.get_int_evals()?), mask.dims())?;
+                    ref_masked_softmax(&int_input, &int_mask, scales.0, scales.1, scale_recip)
+                }
+                None => ref_softmax(&int_input, scales.0, scales.1, scale_recip),
+            }
+            .map(|e| e as i32);
 
-            assert_eq!(Into::<Tensor<i32>>::into(max_val.get_inner()?), ref_max,)
+            assert_eq!(
+                Into::<Tensor<i32>>::into(softmax.get_inner()?),
+                Into::<Tensor<i32>>::into(ref_softmax),
+            )
         }
     };
-    Ok(assigned_max_val)
+
+    Ok(softmax)
 }
 
-/// min layout
-pub fn min<F: FieldExt + TensorType>(
+/// Elementwise exponentials of `x`, zeroed where `mask` is 0.
+fn masked_exp<F: FieldExt + TensorType>(
     config: &mut BaseConfig<F>,
     mut region: Option<&mut Region<F>>,
-    values: &[ValTensor<F>; 1],
+    x: &ValTensor<F>,
+    mask: Option<&ValTensor<F>>,
+    scales: (usize, usize),
     offset: &mut usize,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
-    // this is safe because we later constrain it
-
-    let min_int = values[0].get_int_evals()?.into_iter().min();
-    let min_val: ValTensor<F> = match min_int {
-        None => Tensor::new(Some(&[Value::<F>::unknown()]), &[1])?.into(),
-        Some(i) => Tensor::new(Some(&[Value::known(i128_to_felt::<F>(i))]), &[1])?.into(),
-    };
-
-    let assigned_min_val: ValTensor<F> =
-        config.inputs[1].assign(region.as_deref_mut(), *offset, &min_val)?;
-    *offset += 1;
-
-    let unit: ValTensor<F> = if let Some(region) = region.as_deref_mut() {
-        Tensor::from(
-            vec![config.inputs[1].assign_constant(region, *offset, F::from(1))?].into_iter(),
-        )
-        .into()
-    } else {
-        // for dummy run throughs
-        Tensor::from(vec![Value::known(F::from(1))].into_iter()).into()
-    };
-    *offset += 1;
-
-    // min(x + 1)
-    let min_plus_1 = pairwise(
+    let exp = nonlinearity(
         config,
         region.as_deref_mut(),
-        &[assigned_min_val.clone(), unit.clone()],
+        &[x.clone()],
+        &LookupOp::Exp { scales },
         offset,
-        BaseOp::Add,
     )?;
+    match mask {
+        Some(mask) => {
+            let masked = pairwise(config, region, &[exp, mask.clone()], offset, BaseOp::Mult)?;
+            if masked.dims() != x.dims() {
+                return Err(Box::new(CircuitError::DimMismatch(
+                    "masked softmax layout".to_string(),
+                )));
+            }
+            Ok(masked)
+        }
+        None => Ok(exp),
+    }
+}
 
-    // min(x + 1)  - x
-    let diff = pairwise(
-        config,
-        region.as_deref_mut(),
-        &[min_plus_1, values[0].clone()],
-        offset,
-        BaseOp::Sub,
-    )?;
+/// instance norm layout, normalising each channel of a `[C, H, W]` input by its own mean and variance
+pub fn instance_norm<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 3],
+    scale: usize,
+    epsilon: f32,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let x = &values[0];
+    if x.dims().len() != 3 || values[1].len() != x.dims()[0] || values[2].len() != x.dims()[0] {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "instance norm layout".to_string(),
+        )));
+    }
 
-    // relu(min(x + 1)  - x)
-    let relu = nonlinearity(
+    let mut diff: Option<ValTensor<F>> = None;
+    let mut var: Option<ValTensor<F>> = None;
+    for i in 0..x.dims()[0] {
+        let row = x.get_slice(&[i..i + 1])?;
+        let row_mean = mean(config, region.as_deref_mut(), &[row.clone()], 1, offset)?;
+        let row_diff = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[row, row_mean],
+            offset,
+            BaseOp::Sub,
+        )?;
+        let row_sq = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[row_diff.clone(), row_diff.clone()],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let row_var = mean(config, region.as_deref_mut(), &[row_sq], scale, offset)?;
+
+        diff = Some(match diff {
+            Some(d) => d.concat(row_diff)?,
+            None => row_diff,
+        });
+        var = Some(match var {
+            Some(v) => v.concat(row_var)?,
+            None => row_var,
+        });
+    }
+    let (mut diff, mut var) = match (diff, var) {
+        (Some(d), Some(v)) => (d, v),
+        _ => {
+            return Err(Box::new(CircuitError::DimMismatch(
+                "instance norm layout".to_string(),
+            )))
+        }
+    };
+    diff.reshape(x.dims())?;
+    var.reshape(&[x.dims()[0]])?;
+
+    let output = normalise(
         config,
-        region.as_deref_mut(),
-        &[diff],
-        &LookupOp::ReLU { scale: 1 },
+        region,
+        &[diff, var, values[1].clone(), values[2].clone()],
+        scale,
+        epsilon,
         offset,
     )?;
 
-    let len = relu.dims().iter().product();
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_inputs = values
+                .iter()
+                .map(|v| -> Result<Tensor<i128>, Box<dyn Error>> {
+                    Ok(Tensor::new(Some(&v.get_int_evals()?), v.dims())?)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let ref_norm =
+                ref_instance_norm(int_inputs.try_into().unwrap(), scale, epsilon).map(|e| e as i32);
 
-    // y_i*(1 - y_i) =0 // assert the values are either 0 or 1
-    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu)?;
-    if let Some(region) = region.as_deref_mut() {
-        for i in 0..len {
-            let (x, y) = config.output.cartesian_coord(*offset + i);
-            config
-                .selectors
-                .get(&(BaseOp::IsBoolean, x))
-                .unwrap()
-                .enable(region, y)?;
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                Into::<Tensor<i32>>::into(ref_norm),
+            )
         }
-    }
+    };
 
-    *offset += len;
+    Ok(output)
+}
 
-    // sum(relu(min(x + 1) - x))
-    let sum_relu = sum(config, region.as_deref_mut(), &[relu], offset)?;
-    // 1 - sum(relu(min(x + 1) - x))
-    let one_minus_sum_relu = pairwise(
+/// batch norm layout, normalising each channel of a `[C, H, W]` input using its running mean and variance
+pub fn batch_norm<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 5],
+    scale: usize,
+    epsilon: f32,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let x = &values[0];
+    if x.dims().len() != 3 || values[1..].iter().any(|v| v.len() != x.dims()[0]) {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "batch norm layout".to_string(),
+        )));
+    }
+
+    // the running mean is broadcast over the spatial dims of each channel
+    let mut mean = values[3].clone();
+    mean.reshape(&[x.dims()[0], 1, 1])?;
+    let diff = pairwise(
         config,
         region.as_deref_mut(),
-        &[unit, sum_relu],
+        &[x.clone(), mean],
         offset,
         BaseOp::Sub,
     )?;
-    // relu(1 - sum(relu(min(x + 1) - x)))
-    let relu_one_minus_sum_relu = nonlinearity(
+
+    let output = normalise(
         config,
-        region.as_deref_mut(),
-        &[one_minus_sum_relu],
-        &LookupOp::ReLU { scale: 1 },
+        region,
+        &[
+            diff,
+            values[4].clone(),
+            values[1].clone(),
+            values[2].clone(),
+        ],
+        scale,
+        epsilon,
         offset,
     )?;
 
-    // constraining product to 0
-    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu_one_minus_sum_relu)?;
-
-    if let Some(region) = region {
-        let (x, y) = config.output.cartesian_coord(*offset);
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_inputs = values
+                .iter()
+                .map(|v| -> Result<Tensor<i128>, Box<dyn Error>> {
+                    Ok(Tensor::new(Some(&v.get_int_evals()?), v.dims())?)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let ref_norm =
+                ref_batch_norm(int_inputs.try_into().unwrap(), scale, epsilon).map(|e| e as i32);
+
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                Into::<Tensor<i32>>::into(ref_norm),
+            )
+        }
+    };
+
+    Ok(output)
+}
+
+/// layer norm layout, normalising each slice of the input spanned by `axes` by its own mean and variance.
+/// `values` holds the input, `gamma` and `beta`, the latter two holding a single value or one value per element of a slice.
+pub fn layer_norm<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 3],
+    axes: &[usize],
+    scale: usize,
+    epsilon: f32,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let x = &values[0];
+    let (perm, inverse) = axes_last_permutation(x.dims().len(), axes)?;
+    let group_len = axes.iter().map(|ax| x.dims()[*ax]).product::<usize>();
+    let num_groups = x.len() / group_len;
+
+    let (mut gamma, mut beta) = (values[1].clone(), values[2].clone());
+    for param in [&mut gamma, &mut beta] {
+        if param.len() != 1 && param.len() != group_len {
+            return Err(Box::new(CircuitError::DimMismatch(
+                "layer norm layout".to_string(),
+            )));
+        }
+        param.reshape(&[param.len()])?;
+    }
+
+    let mut permuted = x.clone();
+    permuted.permute_axes(&perm)?;
+    let permuted_dims = permuted.dims().to_vec();
+    permuted.reshape(&[num_groups, group_len])?;
+
+    // epsilon is rounded up so that the rsqrt never sees a zero variance
+    let eps = (epsilon * scale as f32).ceil() as u64;
+    let eps: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(eps))].into_iter()).into();
+
+    let mut output: Option<ValTensor<F>> = None;
+    for i in 0..num_groups {
+        let row = permuted.get_slice(&[i..i + 1])?;
+        let row_mean = mean(config, region.as_deref_mut(), &[row.clone()], 1, offset)?;
+        let diff = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[row, row_mean],
+            offset,
+            BaseOp::Sub,
+        )?;
+        let sq = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[diff.clone(), diff.clone()],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let var = mean(config, region.as_deref_mut(), &[sq], scale, offset)?;
+        let var_eps = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[var, eps.clone()],
+            offset,
+            BaseOp::Add,
+        )?;
+        let inv_std = nonlinearity(
+            config,
+            region.as_deref_mut(),
+            &[var_eps],
+            &LookupOp::Rsqrt {
+                scales: (scale, scale),
+            },
+            offset,
+        )?;
+        let scaled = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[diff, inv_std],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let normalised = nonlinearity(
+            config,
+            region.as_deref_mut(),
+            &[scaled],
+            &LookupOp::Div {
+                denom: utils::F32(scale as f32),
+            },
+            offset,
+        )?;
+        let weighted = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[normalised, gamma.clone()],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let row_output = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[weighted, beta.clone()],
+            offset,
+            BaseOp::Add,
+        )?;
+
+        output = Some(match output {
+            Some(o) => o.concat(row_output)?,
+            None => row_output,
+        });
+    }
+    let mut output = output.ok_or(CircuitError::DimMismatch("layer norm layout".to_string()))?;
+    output.reshape(&permuted_dims)?;
+    output.permute_axes(&inverse)?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_inputs = values
+                .iter()
+                .map(|v| -> Result<Tensor<i128>, Box<dyn Error>> {
+                    Ok(Tensor::new(Some(&v.get_int_evals()?), v.dims())?)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let ref_norm = ref_layer_norm(int_inputs.try_into().unwrap(), axes, scale, epsilon)
+                .map(|e| e as i32);
+
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                Into::<Tensor<i32>>::into(ref_norm),
+            )
+        }
+    };
+
+    Ok(output)
+}
+
+/// Scales the centred channels of a `[C, H, W]` tensor by `gamma / sqrt(var + epsilon)` and shifts them by `beta`.
+/// `values` holds the centred input, the per channel variance, `gamma` and `beta`.
+fn normalise<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 4],
+    scale: usize,
+    epsilon: f32,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let diff = &values[0];
+    // per channel values are broadcast over the spatial dims of each channel
+    let channel_dims = [diff.dims()[0], 1, 1];
+    let (mut var, mut gamma, mut beta) = (values[1].clone(), values[2].clone(), values[3].clone());
+    for v in [&mut var, &mut gamma, &mut beta] {
+        v.reshape(&channel_dims)?;
+    }
+
+    // epsilon is rounded up so that the rsqrt never sees a zero variance
+    let eps = (epsilon * scale as f32).ceil() as u64;
+    let eps: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(eps))].into_iter()).into();
+    let var_eps = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[var, eps],
+        offset,
+        BaseOp::Add,
+    )?;
+
+    let inv_std = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[var_eps],
+        &LookupOp::Rsqrt {
+            scales: (scale, scale),
+        },
+        offset,
+    )?;
+    let scaled_inv_std = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[inv_std, gamma],
+        offset,
+        BaseOp::Mult,
+    )?;
+    let k = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[scaled_inv_std],
+        &LookupOp::Div {
+            denom: utils::F32(scale as f32),
+        },
+        offset,
+    )?;
+
+    let normalised = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[diff.clone(), k],
+        offset,
+        BaseOp::Mult,
+    )?;
+    pairwise(config, region, &[normalised, beta], offset, BaseOp::Add)
+}
+
+/// 2D average pooling layout over a C x H x W input. The windows are summed with [sumpool] and each sum is
+/// divided by its window's element count, using one `Div` lookup per distinct count.
+#[allow(clippy::too_many_arguments)]
+pub fn avg_pool2d<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    padding: [(usize, usize); 2],
+    stride: (usize, usize),
+    kernel_shape: (usize, usize),
+    ceil_mode: bool,
+    count_include_pad: bool,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let image = &values[0];
+    if image.dims().len() != 3 {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "avg pool layout".to_string(),
+        )));
+    }
+    let (sum_padding, divisors) = avg_pool2d_geometry(
+        (image.dims()[1], image.dims()[2]),
+        padding,
+        stride,
+        kernel_shape,
+        ceil_mode,
+        count_include_pad,
+    )?;
+
+    let mut padded = image.clone();
+    padded.pad(
+        &[(0, 0), sum_padding[0], sum_padding[1]],
+        &PadMode::Constant(ValType::Constant(F::zero())),
+    )?;
+    let sums = sumpool(
+        config,
+        region.as_deref_mut(),
+        &[padded],
+        (0, 0),
+        stride,
+        kernel_shape,
+        offset,
+    )?
+    .get_inner_tensor()?;
+
+    let mut output = sums.clone();
+    for d in divisors.iter().unique() {
+        let positions = (0..sums.len())
+            .filter(|i| divisors[i % divisors.len()] == *d)
+            .collect_vec();
+        let windows: ValTensor<F> = Tensor::new(
+            Some(&positions.iter().map(|i| sums[*i].clone()).collect_vec()),
+            &[positions.len()],
+        )?
+        .into();
+        let averages = nonlinearity(
+            config,
+            region.as_deref_mut(),
+            &[windows],
+            &LookupOp::Div {
+                denom: utils::F32(*d as f32),
+            },
+            offset,
+        )?
+        .get_inner_tensor()?;
+        for (j, i) in positions.iter().enumerate() {
+            output[*i] = averages[j].clone();
+        }
+    }
+    let output: ValTensor<F> = output.into();
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_image = Tensor::new(Some(&image.get_int_evals()?), image.dims())?;
+            let ref_pool = ref_avg_pool2d(
+                &int_image,
+                padding,
+                stride,
+                kernel_shape,
+                ceil_mode,
+                count_include_pad,
+            )?
+            .map(|e| e as i32);
+
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                Into::<Tensor<i32>>::into(ref_pool),
+            )
+        }
+    };
+
+    Ok(output)
+}
+
+/// max layout
+pub fn max<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // this is safe because we later constrain it
+    let max_int = values[0].get_int_evals()?.into_iter().max();
+    let max_val: ValTensor<F> = match max_int {
+        None => Tensor::new(Some(&[Value::<F>::unknown()]), &[1])?.into(),
+        Some(i) => Tensor::new(Some(&[Value::known(i128_to_felt::<F>(i))]), &[1])?.into(),
+    };
+
+    let assigned_max_val: ValTensor<F> =
+        config.inputs[1].assign(region.as_deref_mut(), *offset, &max_val)?;
+    *offset += 1;
+
+    let unit: ValTensor<F> = if let Some(region) = region.as_deref_mut() {
+        Tensor::from(
+            vec![config.inputs[1].assign_constant(region, *offset, F::from(1))?].into_iter(),
+        )
+        .into()
+    } else {
+        // for dummy run throughs
+        Tensor::from(vec![Value::known(F::from(1))].into_iter()).into()
+    };
+    *offset += 1;
+
+    // max(x - 1)
+    let max_minus_1 = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[assigned_max_val.clone(), unit.clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+
+    // x - max(x - 1)
+    let diff = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[values[0].clone(), max_minus_1],
+        offset,
+        BaseOp::Sub,
+    )?;
+    // relu(x - max(x - 1))
+    let relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    let len = relu.dims().iter().product();
+
+    // y_i*(1 - y_i) =0 // assert the values are either 0 or 1
+    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu)?;
+    if let Some(region) = region.as_deref_mut() {
+        for i in 0..len {
+            let (x, y) = config.output.cartesian_coord(*offset + i);
+            config
+                .selectors
+                .get(&(BaseOp::IsBoolean, x))
+                .unwrap()
+                .enable(region, y)?;
+        }
+    }
+    *offset += len;
+
+    // sum(relu(x - max(x - 1)))
+    let sum_relu = sum(config, region.as_deref_mut(), &[relu], offset)?;
+    // 1 - sum(relu(x - max(x - 1)))
+    let one_minus_sum_relu = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[unit, sum_relu],
+        offset,
+        BaseOp::Sub,
+    )?;
+    // relu(1 - sum(relu(x - max(x - 1))))
+    let relu_one_minus_sum_relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[one_minus_sum_relu],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    // constraining relu(sum(relu(x - max(x - 1)) - len(x))) = 0
+    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu_one_minus_sum_relu)?;
+
+    if let Some(region) = region {
+        let (x, y) = config.output.cartesian_coord(*offset);
+        config
+            .selectors
+            .get(&(BaseOp::IsZero, x))
+            .unwrap()
+            .enable(region, y)?;
+    }
+    *offset += relu_one_minus_sum_relu.len();
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(assigned_max_val.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let ref_max: Tensor<i32> = Tensor::new(
+                Some(&[values[0].get_int_evals()?.into_iter().max().unwrap() as i32]),
+                &[1],
+            )?;
+
+            assert_eq!(Into::<Tensor<i32>>::into(max_val.get_inner()?), ref_max,)
+        }
+    };
+    Ok(assigned_max_val)
+}
+
+/// min layout
+pub fn min<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // this is safe because we later constrain it
+
+    let min_int = values[0].get_int_evals()?.into_iter().min();
+    let min_val: ValTensor<F> = match min_int {
+        None => Tensor::new(Some(&[Value::<F>::unknown()]), &[1])?.into(),
+        Some(i) => Tensor::new(Some(&[Value::known(i128_to_felt::<F>(i))]), &[1])?.into(),
+    };
+
+    let assigned_min_val: ValTensor<F> =
+        config.inputs[1].assign(region.as_deref_mut(), *offset, &min_val)?;
+    *offset += 1;
+
+    let unit: ValTensor<F> = if let Some(region) = region.as_deref_mut() {
+        Tensor::from(
+            vec![config.inputs[1].assign_constant(region, *offset, F::from(1))?].into_iter(),
+        )
+        .into()
+    } else {
+        // for dummy run throughs
+        Tensor::from(vec![Value::known(F::from(1))].into_iter()).into()
+    };
+    *offset += 1;
+
+    // min(x + 1)
+    let min_plus_1 = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[assigned_min_val.clone(), unit.clone()],
+        offset,
+        BaseOp::Add,
+    )?;
+
+    // min(x + 1)  - x
+    let diff = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[min_plus_1, values[0].clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+
+    // relu(min(x + 1)  - x)
+    let relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    let len = relu.dims().iter().product();
+
+    // y_i*(1 - y_i) =0 // assert the values are either 0 or 1
+    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu)?;
+    if let Some(region) = region.as_deref_mut() {
+        for i in 0..len {
+            let (x, y) = config.output.cartesian_coord(*offset + i);
+            config
+                .selectors
+                .get(&(BaseOp::IsBoolean, x))
+                .unwrap()
+                .enable(region, y)?;
+        }
+    }
+
+    *offset += len;
+
+    // sum(relu(min(x + 1) - x))
+    let sum_relu = sum(config, region.as_deref_mut(), &[relu], offset)?;
+    // 1 - sum(relu(min(x + 1) - x))
+    let one_minus_sum_relu = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[unit, sum_relu],
+        offset,
+        BaseOp::Sub,
+    )?;
+    // relu(1 - sum(relu(min(x + 1) - x)))
+    let relu_one_minus_sum_relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[one_minus_sum_relu],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    // constraining product to 0
+    config.inputs[1].assign(region.as_deref_mut(), *offset, &relu_one_minus_sum_relu)?;
+
+    if let Some(region) = region {
+        let (x, y) = config.output.cartesian_coord(*offset);
         config
             .selectors
             .get(&(BaseOp::IsZero, x))
             .unwrap()
             .enable(region, y)?;
     }
-    *offset += relu_one_minus_sum_relu.len();
+    *offset += relu_one_minus_sum_relu.len();
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(assigned_min_val.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let ref_min: Tensor<i32> = Tensor::new(
+                Some(&[values[0].get_int_evals()?.into_iter().min().unwrap() as i32]),
+                &[1],
+            )?;
+
+            assert_eq!(Into::<Tensor<i32>>::into(min_val.get_inner()?), ref_min,)
+        }
+    };
+    Ok(assigned_min_val)
+}
+
+/// argmax layout, returns the index of the largest element along `axis`
+pub fn argmax<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+    select_last: bool,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_extremum(config, region, values, axis, select_last, true, offset)
+}
+
+/// argmin layout, returns the index of the smallest element along `axis`
+pub fn argmin<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+    select_last: bool,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_extremum(config, region, values, axis, select_last, false, offset)
+}
+
+/// Constrains every element of `values` to be zero.
+fn enforce_zero<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &ValTensor<F>,
+    offset: &mut usize,
+) -> Result<(), Box<dyn Error>> {
+    config.inputs[1].assign(region.as_deref_mut(), *offset, values)?;
+    if let Some(region) = region {
+        for i in 0..values.len() {
+            let (x, y) = config.output.cartesian_coord(*offset + i);
+            config
+                .selectors
+                .get(&(BaseOp::IsZero, x))
+                .unwrap()
+                .enable(region, y)?;
+        }
+    }
+    *offset += values.len();
+    Ok(())
+}
+
+/// Encodes the single element `index` as a one-hot vector over `positions`, i.e `e_j = relu(1 - |p_j - i|)`,
+/// which is all zeros if `index` isn't one of the (integer) positions. Also returns the one-sided distances
+/// `[relu(p_j - i), relu(i - p_j)]`.
+fn one_hot<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    index: &ValTensor<F>,
+    positions: &ValTensor<F>,
+    offset: &mut usize,
+) -> Result<(ValTensor<F>, [ValTensor<F>; 2]), Box<dyn Error>> {
+    let unit: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(1))].into_iter()).into();
+
+    // j - i and i - j
+    let after = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[positions.clone(), index.clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+    let before = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[index.clone(), positions.clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+    let after = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[after],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+    let before = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[before],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    // relu(1 - |j - i|)
+    let distance = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[after.clone(), before.clone()],
+        offset,
+        BaseOp::Add,
+    )?;
+    let one_minus_distance = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[unit, distance],
+        offset,
+        BaseOp::Sub,
+    )?;
+    let one_hot = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[one_minus_distance],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+    Ok((one_hot, [after, before]))
+}
+
+/// Shared layout of [argmax] and [argmin]. For each row `x` along `axis` a witnessed index `i` is constrained by:
+/// - the one-hot `e_j = relu(1 - |j - i|)`, which is all zeros if `i` is out of range
+/// - the indicator `b_j` of `x_j` being the extremum, e.g. `relu(x_j - max(x) + 1)` which is boolean as [max] bounds `x`
+/// - `sum(e_j * b_j) = 1`, so `x_i` is the extremum
+/// - `sum(relu(i - j) * b_j) = 0` (or `relu(j - i)` if `select_last`), so no earlier (later) index ties with `x_i`
+fn arg_extremum<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+    select_last: bool,
+    is_max: bool,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let x = &values[0];
+    let (perm, inverse) = axes_last_permutation(x.dims().len(), &[axis])?;
+    let len = x.dims()[axis];
+    if len == 0 {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "arg extremum layout".to_string(),
+        )));
+    }
+    let num_rows = x.len() / len;
+
+    let mut permuted = x.clone();
+    permuted.permute_axes(&perm)?;
+    let mut out_dims = permuted.dims().to_vec();
+    *out_dims.last_mut().unwrap() = 1;
+    permuted.reshape(&[num_rows, len])?;
+
+    let unit: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(1))].into_iter()).into();
+    let positions: ValTensor<F> =
+        Tensor::from((0..len).map(|j| ValType::Constant(F::from(j as u64)))).into();
+
+    let mut output: Option<ValTensor<F>> = None;
+    for i in 0..num_rows {
+        let mut row = permuted.get_slice(&[i..i + 1])?;
+        row.reshape(&[len])?;
+
+        // this is safe because we later constrain it
+        let int_row = row.get_int_evals()?;
+        // the values are unknown during key generation (and when measuring the region), in which
+        // case the index is too
+        let index = if int_row.len() == len {
+            let int_row = Tensor::new(Some(&int_row), &[len])?;
+            let index = if is_max {
+                ref_argmax(&int_row, 0, select_last)?
+            } else {
+                ref_argmin(&int_row, 0, select_last)?
+            };
+            Value::known(i128_to_felt::<F>(index[0]))
+        } else {
+            Value::<F>::unknown()
+        };
+        let index: ValTensor<F> = Tensor::new(Some(&[index]), &[1])?.into();
+        let assigned_index = config.inputs[1].assign(region.as_deref_mut(), *offset, &index)?;
+        *offset += 1;
+
+        let (one_hot, [after, before]) = one_hot(
+            config,
+            region.as_deref_mut(),
+            &assigned_index,
+            &positions,
+            offset,
+        )?;
+
+        let is_extremum = if is_max {
+            let extremum = max(config, region.as_deref_mut(), &[row.clone()], offset)?;
+            // x - max(x) + 1
+            let diff = pairwise(
+                config,
+                region.as_deref_mut(),
+                &[row, extremum],
+                offset,
+                BaseOp::Sub,
+            )?;
+            pairwise(
+                config,
+                region.as_deref_mut(),
+                &[diff, unit.clone()],
+                offset,
+                BaseOp::Add,
+            )?
+        } else {
+            let extremum = min(config, region.as_deref_mut(), &[row.clone()], offset)?;
+            // min(x) + 1 - x
+            let min_plus_1 = pairwise(
+                config,
+                region.as_deref_mut(),
+                &[extremum, unit.clone()],
+                offset,
+                BaseOp::Add,
+            )?;
+            pairwise(
+                config,
+                region.as_deref_mut(),
+                &[min_plus_1, row],
+                offset,
+                BaseOp::Sub,
+            )?
+        };
+        let is_extremum = nonlinearity(
+            config,
+            region.as_deref_mut(),
+            &[is_extremum],
+            &LookupOp::ReLU { scale: 1 },
+            offset,
+        )?;
+
+        // sum(e_j * b_j) - 1 = 0
+        let selected = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[one_hot, is_extremum.clone()],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let selected = sum(config, region.as_deref_mut(), &[selected], offset)?;
+        let selected_minus_1 = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[selected, unit.clone()],
+            offset,
+            BaseOp::Sub,
+        )?;
+        enforce_zero(config, region.as_deref_mut(), &selected_minus_1, offset)?;
+
+        // no other extremum lies on the losing side of the index
+        let losing = if select_last { after } else { before };
+        let ties = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[losing, is_extremum],
+            offset,
+            BaseOp::Mult,
+        )?;
+        let ties = sum(config, region.as_deref_mut(), &[ties], offset)?;
+        enforce_zero(config, region.as_deref_mut(), &ties, offset)?;
+
+        output = Some(match output {
+            Some(o) => o.concat(assigned_index)?,
+            None => assigned_index,
+        });
+    }
+    let mut output = output.ok_or(CircuitError::DimMismatch("arg extremum layout".to_string()))?;
+    output.reshape(&out_dims)?;
+    output.permute_axes(&inverse)?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation the inputs will be 0 so we use this as a flag to check
+        // (the output can legitimately be all 0s)
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(x.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_input = Tensor::new(Some(&x.get_int_evals()?), x.dims())?;
+            let ref_output = if is_max {
+                ref_argmax(&int_input, axis, select_last)?
+            } else {
+                ref_argmin(&int_input, axis, select_last)?
+            };
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                ref_output.map(|e| e as i32),
+            )
+        }
+    };
+    Ok(output)
+}
+
+/// Embedding layout, i.e `out[i] = table[indices[i]]` for a table of `n` rows and indices at fixed point multiplier
+/// `index_scale`. The indices are first divided back to integers, then each is encoded as a one-hot over the row
+/// positions `0..n`, constrained to sum to 1 such that out of range indices are rejected, and the output is the
+/// product of the one-hots with the table. The table is committed to in an advice column when private, else it is fixed.
+/// As the (fixed point) indices and the one-hot distances `|j - i|` are looked up, `n * index_scale` can't exceed
+/// half the range of the lookup tables, which errors otherwise (or if the ReLU table isn't configured).
+pub fn embedding<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    index_scale: usize,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let (mut table, mut indices) = (values[0].clone(), values[1].clone());
+    if table.dims().is_empty() || table.dims()[0] == 0 {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "embedding layout".to_string(),
+        )));
+    }
+    let num_rows = table.dims()[0];
+    let relu = LookupOp::ReLU { scale: 1 };
+    let bits = config
+        .tables
+        .get(&relu)
+        .ok_or(CircuitError::MissingLookup(format!("{:?}", relu)))?
+        .bits;
+    let max_rows = (1 << (bits - 1)) / index_scale.max(1);
+    if num_rows > max_rows {
+        return Err(Box::new(CircuitError::EmbeddingTooLarge(
+            num_rows, max_rows, bits,
+        )));
+    }
+    let row_dims = table.dims()[1..].to_vec();
+    let out_dims = [indices.dims(), &row_dims[..]].concat();
+    table.reshape(&[num_rows, row_dims.iter().product::<usize>()])?;
+    indices.reshape(&[indices.len()])?;
+
+    if index_scale > 1 {
+        indices = nonlinearity(
+            config,
+            region.as_deref_mut(),
+            &[indices],
+            &LookupOp::Div {
+                denom: utils::F32(index_scale as f32),
+            },
+            offset,
+        )?;
+    }
+
+    let unit: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(1))].into_iter()).into();
+    let positions: ValTensor<F> =
+        Tensor::from((0..num_rows).map(|j| ValType::Constant(F::from(j as u64)))).into();
+
+    let mut one_hots: Option<ValTensor<F>> = None;
+    for i in 0..indices.len() {
+        let index = indices.get_slice(&[i..i + 1])?;
+        let (one_hot, _) = one_hot(config, region.as_deref_mut(), &index, &positions, offset)?;
+
+        // sum(e_j) - 1 = 0
+        let total = sum(config, region.as_deref_mut(), &[one_hot.clone()], offset)?;
+        let total_minus_1 = pairwise(
+            config,
+            region.as_deref_mut(),
+            &[total, unit.clone()],
+            offset,
+            BaseOp::Sub,
+        )?;
+        enforce_zero(config, region.as_deref_mut(), &total_minus_1, offset)?;
+
+        one_hots = Some(match one_hots {
+            Some(o) => o.concat(one_hot)?,
+            None => one_hot,
+        });
+    }
+    let mut one_hots = one_hots.ok_or(CircuitError::DimMismatch("embedding layout".to_string()))?;
+    one_hots.reshape(&[indices.len(), num_rows])?;
+
+    let mut output = matmul(config, region, &[one_hots, table], offset)?;
+    output.reshape(&out_dims)?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation the inputs will be 0 so we use this as a flag to check
+        // (the indices can legitimately be all 0s)
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(values[0].get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let int_table = Tensor::new(Some(&values[0].get_int_evals()?), values[0].dims())?;
+            let int_indices = Tensor::new(Some(&values[1].get_int_evals()?), values[1].dims())?;
+            let ref_output = ref_embedding(&int_table, &int_indices, index_scale)?;
+            assert_eq!(
+                Into::<Tensor<i32>>::into(output.get_inner()?),
+                ref_output.map(|e| e as i32),
+            )
+        }
+    };
+    Ok(output)
+}
+
+/// elementwise greater than layout, the inputs are broadcast to a common shape
+pub fn greater<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a - b
+    let diff = pairwise(config, region.as_deref_mut(), values, offset, BaseOp::Sub)?;
+    // relu(a - b)
+    let relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff.clone()],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    let unit: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(1))].into_iter()).into();
+    // a - b - 1
+    let diff_minus_1 = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[diff, unit],
+        offset,
+        BaseOp::Sub,
+    )?;
+    // relu(a - b - 1)
+    let relu_minus_1 = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff_minus_1],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+
+    // for integers, relu(a - b) - relu(a - b - 1) is 1 if a > b and 0 otherwise
+    let output = pairwise(config, region, &[relu, relu_minus_1], offset, BaseOp::Sub)?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let a = Tensor::new(Some(&values[0].get_int_evals()?), values[0].dims())?;
+            let b = Tensor::new(Some(&values[1].get_int_evals()?), values[1].dims())?;
+            let ref_greater = ref_greater(&a, &b)?.map(|e| e as i32);
+
+            assert_eq!(Into::<Tensor<i32>>::into(output.get_inner()?), ref_greater)
+        }
+    };
+
+    Ok(output)
+}
+
+/// elementwise less than layout, the inputs are broadcast to a common shape
+pub fn less<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a < b is b > a
+    greater(
+        config,
+        region,
+        &[values[1].clone(), values[0].clone()],
+        offset,
+    )
+}
+
+/// elementwise greater than or equal layout, the inputs are broadcast to a common shape
+pub fn greater_equal<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a >= b is 1 - (a < b)
+    let lt = less(config, region.as_deref_mut(), values, offset)?;
+    let output = negate_boolean(config, region, &lt, offset)?;
+
+    check_against_reference(config, values, &output, |t| ref_greater_equal(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise less than or equal layout, the inputs are broadcast to a common shape
+pub fn less_equal<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a <= b is 1 - (a > b)
+    let gt = greater(config, region.as_deref_mut(), values, offset)?;
+    let output = negate_boolean(config, region, &gt, offset)?;
+
+    check_against_reference(config, values, &output, |t| ref_less_equal(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise equality layout, the inputs are broadcast to a common shape
+pub fn equal<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // at most one of a > b and a < b holds, so 1 - (a > b) - (a < b) is boolean
+    let gt = greater(config, region.as_deref_mut(), values, offset)?;
+    let lt = less(config, region.as_deref_mut(), values, offset)?;
+    let neq = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[gt, lt],
+        offset,
+        BaseOp::Add,
+    )?;
+    let output = negate_boolean(config, region, &neq, offset)?;
+
+    check_against_reference(config, values, &output, |t| ref_equal(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise logical and layout, the inputs are constrained to be boolean and broadcast to a common shape
+pub fn and<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let a = enforce_boolean(config, region.as_deref_mut(), &values[0], offset)?;
+    let b = enforce_boolean(config, region.as_deref_mut(), &values[1], offset)?;
+    // a * b
+    let output = pairwise(config, region, &[a, b], offset, BaseOp::Mult)?;
+
+    check_against_reference(config, values, &output, |t| ref_and(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise logical or layout, the inputs are constrained to be boolean and broadcast to a common shape
+pub fn or<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let a = enforce_boolean(config, region.as_deref_mut(), &values[0], offset)?;
+    let b = enforce_boolean(config, region.as_deref_mut(), &values[1], offset)?;
+    // a + b - a * b
+    let prod = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[a.clone(), b.clone()],
+        offset,
+        BaseOp::Mult,
+    )?;
+    let total = pairwise(config, region.as_deref_mut(), &[a, b], offset, BaseOp::Add)?;
+    let output = pairwise(config, region, &[total, prod], offset, BaseOp::Sub)?;
+
+    check_against_reference(config, values, &output, |t| ref_or(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise logical xor layout, the inputs are constrained to be boolean and broadcast to a common shape
+pub fn xor<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let a = enforce_boolean(config, region.as_deref_mut(), &values[0], offset)?;
+    let b = enforce_boolean(config, region.as_deref_mut(), &values[1], offset)?;
+    // a + b - 2 * a * b
+    let prod = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[a.clone(), b.clone()],
+        offset,
+        BaseOp::Mult,
+    )?;
+    let total = pairwise(config, region.as_deref_mut(), &[a, b], offset, BaseOp::Add)?;
+    let total = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[total, prod.clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+    let output = pairwise(config, region, &[total, prod], offset, BaseOp::Sub)?;
+
+    check_against_reference(config, values, &output, |t| ref_xor(&t[0], &t[1]))?;
+    Ok(output)
+}
+
+/// elementwise logical not layout, the input is constrained to be boolean
+pub fn not<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 1],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let a = enforce_boolean(config, region.as_deref_mut(), &values[0], offset)?;
+    let output = negate_boolean(config, region, &a, offset)?;
+
+    check_against_reference(config, values, &output, |t| Ok(ref_not(&t[0])))?;
+    Ok(output)
+}
+
+/// elementwise select layout, returns `a` where the boolean `mask` is 1 and `b` where it is 0.
+/// The inputs are ordered `[mask, a, b]` and broadcast to a common shape.
+pub fn iff<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 3],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mask = enforce_boolean(config, region.as_deref_mut(), &values[0], offset)?;
+    // b + mask * (a - b)
+    let diff = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[values[1].clone(), values[2].clone()],
+        offset,
+        BaseOp::Sub,
+    )?;
+    let masked = pairwise(
+        config,
+        region.as_deref_mut(),
+        &[mask, diff],
+        offset,
+        BaseOp::Mult,
+    )?;
+    let output = pairwise(
+        config,
+        region,
+        &[values[2].clone(), masked],
+        offset,
+        BaseOp::Add,
+    )?;
+
+    check_against_reference(config, values, &output, |t| ref_iff(&t[0], &t[1], &t[2]))?;
+    Ok(output)
+}
+
+/// Constrains every element of `values` to be 0 or 1, returning the constrained copy.
+fn enforce_boolean<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &ValTensor<F>,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let assigned = config.inputs[1].assign(region.as_deref_mut(), *offset, values)?;
+    if let Some(region) = region {
+        for i in 0..values.len() {
+            let (x, y) = config.output.cartesian_coord(*offset + i);
+            config
+                .selectors
+                .get(&(BaseOp::IsBoolean, x))
+                .unwrap()
+                .enable(region, y)?;
+        }
+    }
+    *offset += values.len();
+    Ok(assigned)
+}
+
+/// Returns `1 - values`, which is boolean for boolean `values`.
+fn negate_boolean<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    region: Option<&mut Region<F>>,
+    values: &ValTensor<F>,
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let unit: ValTensor<F> = Tensor::from(vec![ValType::Constant(F::from(1))].into_iter()).into();
+    pairwise(config, region, &[unit, values.clone()], offset, BaseOp::Sub)
+}
+
+/// In SAFE mode, asserts that `output` matches `reference` evaluated on the integer values of `values`.
+fn check_against_reference<F: FieldExt + TensorType>(
+    config: &BaseConfig<F>,
+    values: &[ValTensor<F>],
+    output: &ValTensor<F>,
+    reference: impl FnOnce(&[Tensor<i128>]) -> Result<Tensor<i128>, TensorError>,
+) -> Result<(), Box<dyn Error>> {
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation the inputs will be 0 so we use this as a flag to check
+        // (boolean outputs can legitimately be all 0s)
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = values.iter().any(|v| match v.get_inner() {
+            Ok(inner) => !Into::<Tensor<i32>>::into(inner).iter().all(|&x| x == 0),
+            Err(_) => false,
+        });
+        if is_assigned {
+            let int_inputs = values
+                .iter()
+                .map(|v| -> Result<Tensor<i128>, Box<dyn Error>> {
+                    Ok(Tensor::new(Some(&v.get_int_evals()?), v.dims())?)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let expected = reference(&int_inputs)?.map(|e| e as i32);
+
+            assert_eq!(Into::<Tensor<i32>>::into(output.get_inner()?), expected)
+        }
+    };
+    Ok(())
+}
+
+/// elementwise max layout, the inputs are broadcast to a common shape
+pub fn eltwise_max<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a - b
+    let diff = pairwise(config, region.as_deref_mut(), values, offset, BaseOp::Sub)?;
+    // relu(a - b)
+    let relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+    // relu(a - b) + b
+    let output = pairwise(
+        config,
+        region,
+        &[relu, values[1].clone()],
+        offset,
+        BaseOp::Add,
+    )?;
 
     if matches!(&config.check_mode, CheckMode::SAFE) {
         // during key generation this will be 0 so we use this as a flag to check
         // TODO: this isn't very safe and would be better to get the phase directly
-        let is_assigned = !Into::<Tensor<i32>>::into(assigned_min_val.get_inner()?)
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
             .iter()
             .all(|&x| x == 0);
         if is_assigned {
-            let ref_min: Tensor<i32> = Tensor::new(
-                Some(&[values[0].get_int_evals()?.into_iter().min().unwrap() as i32]),
-                &[1],
-            )?;
+            let a = Tensor::new(Some(&values[0].get_int_evals()?), values[0].dims())?;
+            let b = Tensor::new(Some(&values[1].get_int_evals()?), values[1].dims())?;
+            let ref_max = ref_eltwise_max(&a, &b)?.map(|e| e as i32);
 
-            assert_eq!(Into::<Tensor<i32>>::into(min_val.get_inner()?), ref_min,)
+            assert_eq!(Into::<Tensor<i32>>::into(output.get_inner()?), ref_max)
         }
     };
-    Ok(assigned_min_val)
+
+    Ok(output)
+}
+
+/// elementwise min layout, the inputs are broadcast to a common shape
+pub fn eltwise_min<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 2],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a - b
+    let diff = pairwise(config, region.as_deref_mut(), values, offset, BaseOp::Sub)?;
+    // relu(a - b)
+    let relu = nonlinearity(
+        config,
+        region.as_deref_mut(),
+        &[diff],
+        &LookupOp::ReLU { scale: 1 },
+        offset,
+    )?;
+    // a - relu(a - b)
+    let output = pairwise(
+        config,
+        region,
+        &[values[0].clone(), relu],
+        offset,
+        BaseOp::Sub,
+    )?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be 0 so we use this as a flag to check
+        // TODO: this isn't very safe and would be better to get the phase directly
+        let is_assigned = !Into::<Tensor<i32>>::into(output.get_inner()?)
+            .iter()
+            .all(|&x| x == 0);
+        if is_assigned {
+            let a = Tensor::new(Some(&values[0].get_int_evals()?), values[0].dims())?;
+            let b = Tensor::new(Some(&values[1].get_int_evals()?), values[1].dims())?;
+            let ref_min = ref_eltwise_min(&a, &b)?.map(|e| e as i32);
+
+            assert_eq!(Into::<Tensor<i32>>::into(output.get_inner()?), ref_min)
+        }
+    };
+
+    Ok(output)
+}
+
+/// clip layout, clamps the input to `[min, max]` by comparisons rather than a single lookup.
+/// The inputs are ordered `[x, min, max]` and broadcast to a common shape.
+pub fn clip<F: FieldExt + TensorType>(
+    config: &mut BaseConfig<F>,
+    mut region: Option<&mut Region<F>>,
+    values: &[ValTensor<F>; 3],
+    offset: &mut usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // min(max(x, min), max)
+    let lower = eltwise_max(
+        config,
+        region.as_deref_mut(),
+        &[values[0].clone(), values[1].clone()],
+        offset,
+    )?;
+    eltwise_min(config, region, &[lower, values[2].clone()], offset)
 }
@@ -17,14 +17,63 @@ use super::Op;
 /// An enum representing the operations that can be used to express more complex operations via accumulation
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 pub enum LookupOp {
-    Div { denom: utils::F32 },
-    ReLU { scale: usize },
-    Sqrt { scales: (usize, usize) },
-    Rsqrt { scales: (usize, usize) },
-    LeakyReLU { scale: usize, slope: utils::F32 },
-    Sigmoid { scales: (usize, usize) },
-    Tanh { scales: (usize, usize) },
-    Erf { scales: (usize, usize) },
+    Div {
+        denom: utils::F32,
+    },
+    ReLU {
+        scale: usize,
+    },
+    Sqrt {
+        scales: (usize, usize),
+    },
+    Rsqrt {
+        scales: (usize, usize),
+    },
+    LeakyReLU {
+        scale: usize,
+        slope: utils::F32,
+    },
+    Sigmoid {
+        scales: (usize, usize),
+    },
+    Tanh {
+        scales: (usize, usize),
+    },
+    Erf {
+        scales: (usize, usize),
+    },
+    Exp {
+        scales: (usize, usize),
+    },
+    Recip {
+        scales: (usize, usize),
+    },
+    Ln {
+        scales: (usize, usize),
+    },
+    Pow {
+        scales: (usize, usize),
+        a: utils::F32,
+    },
+    Gelu {
+        scales: (usize, usize),
+    },
+    Silu {
+        scales: (usize, usize),
+    },
+    HardSigmoid {
+        scales: (usize, usize),
+        alpha: utils::F32,
+        beta: utils::F32,
+    },
+    HardSwish {
+        scales: (usize, usize),
+    },
+    Clip {
+        scales: (usize, usize),
+        min: utils::F32,
+        max: utils::F32,
+    },
 }
 
 impl LookupOp {
@@ -68,6 +117,37 @@ impl<F: FieldExt + TensorType> Op<F> for LookupOp {
             LookupOp::Erf { scales } => Ok(tensor::ops::nonlinearities::erffunc(
                 &x[0], scales.0, scales.1,
             )),
+            LookupOp::Exp { scales } => {
+                Ok(tensor::ops::nonlinearities::exp(&x[0], scales.0, scales.1))
+            }
+            LookupOp::Recip { scales } => Ok(tensor::ops::nonlinearities::recip(
+                &x[0], scales.0, scales.1,
+            )),
+            LookupOp::Ln { scales } => {
+                Ok(tensor::ops::nonlinearities::ln(&x[0], scales.0, scales.1))
+            }
+            LookupOp::Pow { scales, a } => Ok(tensor::ops::nonlinearities::pow(
+                &x[0], scales.0, scales.1, a.0,
+            )),
+            LookupOp::Gelu { scales } => {
+                Ok(tensor::ops::nonlinearities::gelu(&x[0], scales.0, scales.1))
+            }
+            LookupOp::Silu { scales } => {
+                Ok(tensor::ops::nonlinearities::silu(&x[0], scales.0, scales.1))
+            }
+            LookupOp::HardSigmoid {
+                scales,
+                alpha,
+                beta,
+            } => Ok(tensor::ops::nonlinearities::hard_sigmoid(
+                &x[0], scales.0, scales.1, alpha.0, beta.0,
+            )),
+            LookupOp::HardSwish { scales } => Ok(tensor::ops::nonlinearities::hard_swish(
+                &x[0], scales.0, scales.1,
+            )),
+            LookupOp::Clip { scales, min, max } => Ok(tensor::ops::nonlinearities::clip(
+                &x[0], scales.0, scales.1, min.0, max.0,
+            )),
         }
     }
 
@@ -82,6 +162,15 @@ impl<F: FieldExt + TensorType> Op<F> for LookupOp {
             LookupOp::Tanh { .. } => "TANH",
             LookupOp::Erf { .. } => "ERF",
             LookupOp::Rsqrt { .. } => "RSQRT",
+            LookupOp::Exp { .. } => "EXP",
+            LookupOp::Recip { .. } => "RECIP",
+            LookupOp::Ln { .. } => "LN",
+            LookupOp::Pow { .. } => "POW",
+            LookupOp::Gelu { .. } => "GELU",
+            LookupOp::Silu { .. } => "SILU",
+            LookupOp::HardSigmoid { .. } => "HARD_SIGMOID",
+            LookupOp::HardSwish { .. } => "HARD_SWISH",
+            LookupOp::Clip { .. } => "CLIP",
         }
     }
 
@@ -145,11 +234,70 @@ impl<F: FieldExt + TensorType> Op<F> for LookupOp {
                     scale_to_multiplier(global_scale) as usize,
                 ),
             }),
+            LookupOp::Exp { .. } => Box::new(LookupOp::Exp {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::Recip { .. } => Box::new(LookupOp::Recip {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::Ln { .. } => Box::new(LookupOp::Ln {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::Pow { a, .. } => Box::new(LookupOp::Pow {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                a: *a,
+            }),
+            LookupOp::Gelu { .. } => Box::new(LookupOp::Gelu {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::Silu { .. } => Box::new(LookupOp::Silu {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::HardSigmoid { alpha, beta, .. } => Box::new(LookupOp::HardSigmoid {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                alpha: *alpha,
+                beta: *beta,
+            }),
+            LookupOp::HardSwish { .. } => Box::new(LookupOp::HardSwish {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
+            LookupOp::Clip { min, max, .. } => Box::new(LookupOp::Clip {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                min: *min,
+                max: *max,
+            }),
         }
     }
 
-    fn required_lookup(&self) -> Option<LookupOp> {
-        Some(self.clone())
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        vec![self.clone()]
     }
 
     fn clone_dyn(&self) -> Box<dyn Op<F>> {
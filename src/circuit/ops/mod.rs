@@ -40,19 +40,54 @@ pub trait Op<F: FieldExt + TensorType>: std::fmt::Debug + Send + Sync {
         global_scale
     }
 
+    /// Returns the number of outputs of the op, which are addressed by their output slot.
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    /// Computes every output of the op, by default the single output of [Op::f].
+    fn f_outputs(&self, x: &[Tensor<i128>]) -> Result<Vec<Tensor<i128>>, TensorError> {
+        Ok(vec![self.f(x)?])
+    }
+
+    /// Lays out every output of the op, by default the single output of [Op::layout].
+    fn layout_outputs(
+        &self,
+        config: &mut crate::circuit::BaseConfig<F>,
+        region: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        offset: &mut usize,
+    ) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+        Ok(self
+            .layout(config, region, values, offset)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns the fixed point scale of every output of the op, by default that of [Op::out_scale].
+    fn out_scales(&self, in_scales: Vec<u32>, global_scale: u32) -> Vec<u32> {
+        vec![self.out_scale(in_scales, global_scale)]
+    }
+
     ///
     fn has_3d_input(&self) -> bool {
         false
     }
 
+    /// Returns the rank of the single sample the op is applied to (e.g 3 for a C x H x W image), if it isn't
+    /// applied to each sample of a batch. By default that of the ops with a [Op::has_3d_input].
+    fn sample_rank(&self) -> Option<usize> {
+        self.has_3d_input().then_some(3)
+    }
+
     ///
     fn requires_homogenous_input_scales(&self) -> bool {
         false
     }
 
-    ///
-    fn required_lookup(&self) -> Option<LookupOp> {
-        None
+    /// Returns the lookup tables the op needs to have configured in order to be laid out.
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        vec![]
     }
 
     ///
@@ -63,6 +98,11 @@ pub trait Op<F: FieldExt + TensorType>: std::fmt::Debug + Send + Sync {
         false
     }
 
+    /// Returns true if the op takes no inputs and always evaluates to the same (known) tensor.
+    fn is_constant(&self) -> bool {
+        false
+    }
+
     ///
     fn clone_dyn(&self) -> Box<dyn Op<F>>;
 }
@@ -108,6 +148,123 @@ impl<F: FieldExt + TensorType> Op<F> for Input {
     }
 }
 
+/// A constant tensor, for instance the indices consumed by a gather.
+#[derive(Clone, Debug)]
+pub struct Constant<F: FieldExt + TensorType> {
+    ///
+    pub values: ValTensor<F>,
+}
+
+impl<F: FieldExt + TensorType> Op<F> for Constant<F> {
+    fn f(&self, _: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+        Tensor::new(
+            Some(&self.values.get_int_evals().unwrap()),
+            self.values.dims(),
+        )
+    }
+
+    fn as_str(&self) -> &'static str {
+        "Constant"
+    }
+    fn layout(
+        &self,
+        _: &mut crate::circuit::BaseConfig<F>,
+        _: Option<&mut Region<F>>,
+        _: &[ValTensor<F>],
+        _: &mut usize,
+    ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+        Ok(Some(self.values.clone()))
+    }
+
+    fn out_scale(&self, _: Vec<u32>, _: u32) -> u32 {
+        self.values.scale()
+    }
+
+    fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
+        Box::new(self.clone())
+    }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Op<F>> {
+        Box::new(self.clone()) // Forward to the derive(Clone) impl
+    }
+}
+
+/// Forwards each of its inputs to an output slot of its own, e.g to stand in for an onnx node with several
+/// outputs which is loaded as several nodes (such as an unrolled scan).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Tuple {
+    /// The number of inputs, and hence of outputs.
+    pub len: usize,
+}
+
+impl<F: FieldExt + TensorType> Op<F> for Tuple {
+    fn f(&self, x: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+        x.first()
+            .cloned()
+            .ok_or_else(|| TensorError::DimMismatch("tuple".to_string()))
+    }
+
+    fn as_str(&self) -> &'static str {
+        "Tuple"
+    }
+
+    fn layout(
+        &self,
+        _: &mut crate::circuit::BaseConfig<F>,
+        _: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        _: &mut usize,
+    ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+        Ok(values.first().cloned())
+    }
+
+    fn out_scale(&self, in_scales: Vec<u32>, global_scale: u32) -> u32 {
+        in_scales.first().copied().unwrap_or(global_scale)
+    }
+
+    fn num_outputs(&self) -> usize {
+        self.len
+    }
+
+    fn f_outputs(&self, x: &[Tensor<i128>]) -> Result<Vec<Tensor<i128>>, TensorError> {
+        if x.len() != self.len {
+            return Err(TensorError::DimMismatch("tuple".to_string()));
+        }
+        Ok(x.to_vec())
+    }
+
+    fn layout_outputs(
+        &self,
+        _: &mut crate::circuit::BaseConfig<F>,
+        _: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        _: &mut usize,
+    ) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+        if values.len() != self.len {
+            return Err(Box::new(crate::circuit::CircuitError::DimMismatch(
+                "tuple layout".to_string(),
+            )));
+        }
+        Ok(values.to_vec())
+    }
+
+    fn out_scales(&self, in_scales: Vec<u32>, _: u32) -> Vec<u32> {
+        in_scales
+    }
+
+    fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
+        Box::new(self.clone())
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Op<F>> {
+        Box::new(self.clone()) // Forward to the derive(Clone) impl
+    }
+}
+
 ///
 #[derive(Clone, Debug)]
 pub struct Rescaled<F: FieldExt + TensorType> {
@@ -117,8 +274,8 @@ pub struct Rescaled<F: FieldExt + TensorType> {
     pub scale: Vec<(usize, usize)>,
 }
 
-impl<F: FieldExt + TensorType> Op<F> for Rescaled<F> {
-    fn f(&self, x: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+impl<F: FieldExt + TensorType> Rescaled<F> {
+    fn rescale_inputs(&self, x: &[Tensor<i128>]) -> Result<Vec<Tensor<i128>>, TensorError> {
         if self.scale.len() != x.len() {
             return Err(TensorError::DimMismatch("rescaled inputs".to_string()));
         }
@@ -128,7 +285,45 @@ impl<F: FieldExt + TensorType> Op<F> for Rescaled<F> {
         for (i, ri) in inputs.iter_mut().enumerate() {
             rescaled_inputs.push(tensor::ops::rescale(ri, self.scale[i].1)?);
         }
-        Ok(Op::<F>::f(&*self.inner, &rescaled_inputs)?)
+        Ok(rescaled_inputs)
+    }
+
+    fn rescale_in_scales(&self, in_scales: Vec<u32>) -> Vec<u32> {
+        in_scales
+            .into_iter()
+            .zip(self.scale.iter())
+            .map(|(a, b)| a + crate::graph::mult_to_scale(b.1 as f32))
+            .collect()
+    }
+
+    fn layout_rescaled(
+        &self,
+        config: &mut crate::circuit::BaseConfig<F>,
+        region: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        offset: &mut usize,
+    ) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+        if self.scale.len() != values.len() {
+            return Err(Box::new(TensorError::DimMismatch(
+                "rescaled inputs".to_string(),
+            )));
+        }
+
+        layouts::rescale(config, region, values, &self.scale, offset)
+    }
+}
+
+impl<F: FieldExt + TensorType> Op<F> for Rescaled<F> {
+    fn f(&self, x: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+        Op::<F>::f(&*self.inner, &self.rescale_inputs(x)?)
+    }
+
+    fn f_outputs(&self, x: &[Tensor<i128>]) -> Result<Vec<Tensor<i128>>, TensorError> {
+        self.inner.f_outputs(&self.rescale_inputs(x)?)
+    }
+
+    fn num_outputs(&self) -> usize {
+        self.inner.num_outputs()
     }
 
     fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
@@ -140,12 +335,15 @@ impl<F: FieldExt + TensorType> Op<F> for Rescaled<F> {
     }
 
     fn out_scale(&self, in_scales: Vec<u32>, _g: u32) -> u32 {
-        let in_scales = in_scales
-            .into_iter()
-            .zip(self.scale.iter())
-            .map(|(a, b)| a + crate::graph::mult_to_scale(b.1 as f32))
-            .collect();
-        Op::<F>::out_scale(&*self.inner, in_scales, _g)
+        Op::<F>::out_scale(&*self.inner, self.rescale_in_scales(in_scales), _g)
+    }
+
+    fn out_scales(&self, in_scales: Vec<u32>, _g: u32) -> Vec<u32> {
+        self.inner.out_scales(self.rescale_in_scales(in_scales), _g)
+    }
+
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        self.inner.required_lookups()
     }
 
     fn layout(
@@ -155,20 +353,19 @@ impl<F: FieldExt + TensorType> Op<F> for Rescaled<F> {
         values: &[ValTensor<F>],
         offset: &mut usize,
     ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
-        if self.scale.len() != values.len() {
-            return Err(Box::new(TensorError::DimMismatch(
-                "rescaled inputs".to_string(),
-            )));
-        }
+        let res = self.layout_rescaled(config, region.as_deref_mut(), values, offset)?;
+        self.inner.layout(config, region, &res, offset)
+    }
 
-        let res = &layouts::rescale(
-            config,
-            region.as_deref_mut(),
-            values[..].try_into()?,
-            &self.scale,
-            offset,
-        )?[..];
-        self.inner.layout(config, region, res, offset)
+    fn layout_outputs(
+        &self,
+        config: &mut crate::circuit::BaseConfig<F>,
+        mut region: Option<&mut Region<F>>,
+        values: &[ValTensor<F>],
+        offset: &mut usize,
+    ) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+        let res = self.layout_rescaled(config, region.as_deref_mut(), values, offset)?;
+        self.inner.layout_outputs(config, region, &res, offset)
     }
 
     fn clone_dyn(&self) -> Box<dyn Op<F>> {
@@ -1,6 +1,7 @@
 use crate::{
     circuit::layouts,
-    tensor::{self, Tensor, TensorError},
+    fieldutils::i128_to_felt,
+    tensor::{self, ops::PadMode, Tensor, TensorError, ValType},
 };
 
 use super::{base::BaseOp, *};
@@ -12,13 +13,18 @@ pub enum PolyOp<F: FieldExt + TensorType> {
     Dot,
     Matmul {
         a: Option<ValTensor<F>>,
+        a_trans: bool,
+        b_trans: bool,
+        c_trans: bool,
     },
     Affine,
     Conv {
         kernel: ValTensor<F>,
         bias: Option<ValTensor<F>>,
-        padding: (usize, usize),
-        stride: (usize, usize),
+        padding: Vec<(usize, usize)>,
+        stride: Vec<usize>,
+        dilation: Vec<usize>,
+        groups: usize,
     },
     SumPool {
         padding: (usize, usize),
@@ -28,14 +34,36 @@ pub enum PolyOp<F: FieldExt + TensorType> {
     Add {
         a: Option<ValTensor<F>>,
     },
-    Sub,
+    Sub {
+        a: Option<ValTensor<F>>,
+    },
     Mult {
         a: Option<ValTensor<F>>,
     },
     Identity,
     Reshape(Vec<usize>),
     Flatten(Vec<usize>),
-    Pad(usize, usize),
+    Pad {
+        padding: Vec<(usize, usize)>,
+        mode: PadMode<i128>,
+    },
+    Transpose {
+        perm: Vec<usize>,
+    },
+    /// `constants` has an entry per concatenated tensor, `None` entries are taken (in order) from the op's inputs.
+    Concat {
+        axis: usize,
+        constants: Vec<Option<ValTensor<F>>>,
+    },
+    Slice {
+        axis: usize,
+        start: usize,
+        end: usize,
+    },
+    Gather {
+        axis: usize,
+        indices: Tensor<usize>,
+    },
     Sum,
     Pow(u32),
     Pack(u32, u32),
@@ -49,10 +77,14 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
             PolyOp::Identity => "IDENTITY",
             PolyOp::Reshape(_) => "RESHAPE",
             PolyOp::Flatten(_) => "FLATTEN",
-            PolyOp::Pad(_, _) => "PAD",
+            PolyOp::Pad { .. } => "PAD",
+            PolyOp::Transpose { .. } => "TRANSPOSE",
+            PolyOp::Concat { .. } => "CONCAT",
+            PolyOp::Slice { .. } => "SLICE",
+            PolyOp::Gather { .. } => "GATHER",
             PolyOp::Add { .. } => "ADD",
             PolyOp::Mult { .. } => "MULT",
-            PolyOp::Sub => "SUB",
+            PolyOp::Sub { .. } => "SUB",
             PolyOp::Sum => "SUM",
             PolyOp::Dot => "DOT",
             PolyOp::Pow(_) => "POW",
@@ -81,19 +113,42 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                 t.reshape(new_dims);
                 Ok(t)
             }
-            PolyOp::Pad(dim1, dim2) => {
+            PolyOp::Pad { padding, mode } => {
                 if 1 != inputs.len() {
                     return Err(TensorError::DimMismatch("pad inputs".to_string()));
                 }
-                tensor::ops::pad(&inputs[0], (*dim1, *dim2))
+                tensor::ops::pad(&inputs[0], padding, mode)
+            }
+            PolyOp::Transpose { perm } => tensor::ops::permute_axes(&inputs[0], perm),
+            PolyOp::Concat { axis, constants } => {
+                let mut inputs = inputs.into_iter();
+                let mut to_concat = vec![];
+                for c in constants {
+                    to_concat.push(match c {
+                        Some(c) => Tensor::new(Some(&c.get_int_evals().unwrap()), c.dims())?,
+                        None => inputs
+                            .next()
+                            .ok_or_else(|| TensorError::DimMismatch("concat inputs".to_string()))?,
+                    });
+                }
+                tensor::ops::concat(&to_concat, *axis)
+            }
+            PolyOp::Slice { axis, start, end } => {
+                tensor::ops::slice(&inputs[0], *axis, *start, *end)
             }
+            PolyOp::Gather { axis, indices } => tensor::ops::gather(&inputs[0], indices, *axis),
             PolyOp::Add { a } => {
                 if let Some(a) = a {
                     inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
                 }
                 tensor::ops::add(&inputs)
             }
-            PolyOp::Sub => tensor::ops::sub(&inputs),
+            PolyOp::Sub { a } => {
+                if let Some(a) = a {
+                    inputs.insert(0, Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
+                }
+                tensor::ops::sub(&inputs)
+            }
             PolyOp::Mult { a } => {
                 if let Some(a) = a {
                     inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
@@ -101,14 +156,29 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                 tensor::ops::mult(&inputs)
             }
             PolyOp::Affine => tensor::ops::affine(&inputs),
-            PolyOp::Matmul { a } => {
+            PolyOp::Matmul {
+                a,
+                a_trans,
+                b_trans,
+                c_trans,
+            } => {
                 if let Some(a) = a {
                     let b = inputs;
                     inputs = vec![Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?];
                     inputs.extend(b);
                 }
+                for (input, trans) in inputs.iter_mut().zip([a_trans, b_trans]) {
+                    if *trans && input.dims().len() > 1 {
+                        *input = tensor::ops::permute_axes(input, &matrix_transpose(input.dims()))?;
+                    }
+                }
 
-                tensor::ops::matmul(&inputs)
+                let output = tensor::ops::matmul(&inputs)?;
+                if *c_trans && output.dims().len() > 1 {
+                    tensor::ops::permute_axes(&output, &matrix_transpose(output.dims()))
+                } else {
+                    Ok(output)
+                }
             }
             PolyOp::Dot => tensor::ops::dot(&inputs.iter().collect()),
             PolyOp::Conv {
@@ -116,12 +186,14 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                 bias,
                 padding,
                 stride,
+                dilation,
+                groups,
             } => {
                 inputs.push(Tensor::new(Some(&a.get_int_evals().unwrap()), a.dims())?);
                 if let Some(b) = bias {
                     inputs.push(Tensor::new(Some(&b.get_int_evals().unwrap()), b.dims())?);
                 }
-                tensor::ops::convolution(&inputs, *padding, *stride)
+                tensor::ops::convolution(&inputs, padding, stride, dilation, *groups)
             }
             PolyOp::SumPool {
                 padding,
@@ -164,14 +236,28 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
         Ok(Some(match self {
             PolyOp::Dot => layouts::dot(config, region, values[..].try_into()?, offset)?,
             PolyOp::Sum => layouts::sum(config, region, values[..].try_into()?, offset)?,
-            PolyOp::Matmul { a } => {
+            PolyOp::Matmul {
+                a,
+                a_trans,
+                b_trans,
+                c_trans,
+            } => {
                 if let Some(a) = a {
                     let b = values;
                     values = vec![a.clone()];
                     values.extend(b);
                 }
+                for (value, trans) in values.iter_mut().zip([a_trans, b_trans]) {
+                    if *trans && value.dims().len() > 1 {
+                        value.permute_axes(&matrix_transpose(value.dims()))?;
+                    }
+                }
 
-                layouts::matmul(config, region, values[..].try_into()?, offset)?
+                let mut output = layouts::matmul(config, region, values[..].try_into()?, offset)?;
+                if *c_trans && output.dims().len() > 1 {
+                    output.permute_axes(&matrix_transpose(output.dims()))?;
+                }
+                output
             }
             PolyOp::Affine => layouts::affine(config, region, values[..].try_into()?, offset)?,
             PolyOp::Conv {
@@ -179,6 +265,8 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                 bias,
                 padding,
                 stride,
+                dilation,
+                groups,
             } => {
                 values.push(kernel.clone());
                 if let Some(bias) = bias {
@@ -188,8 +276,10 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                     config,
                     region,
                     values[..].try_into()?,
-                    *padding,
-                    *stride,
+                    padding,
+                    stride,
+                    dilation,
+                    *groups,
                     offset,
                 )?
             }
@@ -213,7 +303,10 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
 
                 layouts::pairwise(config, region, values[..].try_into()?, offset, BaseOp::Add)?
             }
-            PolyOp::Sub => {
+            PolyOp::Sub { a } => {
+                if let Some(a) = a {
+                    values.insert(0, a.clone());
+                }
                 layouts::pairwise(config, region, values[..].try_into()?, offset, BaseOp::Sub)?
             }
             PolyOp::Mult { a } => {
@@ -224,12 +317,46 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
             }
             PolyOp::Identity => layouts::identity(config, region, values[..].try_into()?, offset)?,
             PolyOp::Reshape(d) | PolyOp::Flatten(d) => layouts::reshape(values[..].try_into()?, d)?,
-            PolyOp::Pad(p1, p2) => {
+            PolyOp::Pad { padding, mode } => {
                 if values.len() != 1 {
                     return Err(Box::new(TensorError::DimError));
                 }
+                // constant padding values are fixed in the circuit
+                let mode = match mode {
+                    PadMode::Constant(c) => PadMode::Constant(ValType::Constant(i128_to_felt(*c))),
+                    PadMode::Reflect => PadMode::Reflect,
+                    PadMode::Edge => PadMode::Edge,
+                };
                 let mut input = values[0].clone();
-                input.pad((*p1, *p2))?;
+                input.pad(padding, &mode)?;
+                input
+            }
+            PolyOp::Transpose { perm } => {
+                let mut input = values[0].clone();
+                input.permute_axes(perm)?;
+                input
+            }
+            PolyOp::Concat { axis, constants } => {
+                let mut inputs = values.into_iter();
+                let mut to_concat = vec![];
+                for c in constants {
+                    to_concat.push(match c {
+                        Some(c) => c.clone(),
+                        None => inputs
+                            .next()
+                            .ok_or_else(|| TensorError::DimMismatch("concat inputs".to_string()))?,
+                    });
+                }
+                ValTensor::concat_axis(&to_concat, *axis)?
+            }
+            PolyOp::Slice { axis, start, end } => {
+                let mut input = values[0].clone();
+                input.slice(*axis, *start, *end)?;
+                input
+            }
+            PolyOp::Gather { axis, indices } => {
+                let mut input = values[0].clone();
+                input.gather(indices, *axis)?;
                 input
             }
             PolyOp::Pow(exp) => layouts::pow(config, region, values[..].try_into()?, *exp, offset)?,
@@ -252,7 +379,7 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
         match self {
             PolyOp::Dot => in_scales[0] + in_scales[1],
             PolyOp::Sum => in_scales[0],
-            PolyOp::Matmul { a } => {
+            PolyOp::Matmul { a, .. } => {
                 let mut scale = in_scales[0];
                 if let Some(a) = a {
                     scale += a.scale();
@@ -281,7 +408,7 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
                 assert_eq!(scale_a, scale_b);
                 scale_a
             }
-            PolyOp::Sub => in_scales[0],
+            PolyOp::Sub { .. } => in_scales[0],
             PolyOp::Mult { a } => {
                 let mut scale = in_scales[0];
                 if let Some(a) = a {
@@ -293,7 +420,11 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
             }
             PolyOp::Identity => in_scales[0],
             PolyOp::Reshape(_) | PolyOp::Flatten(_) => in_scales[0],
-            PolyOp::Pad(_, _) => in_scales[0],
+            PolyOp::Pad { .. } => in_scales[0],
+            PolyOp::Transpose { .. }
+            | PolyOp::Concat { .. }
+            | PolyOp::Slice { .. }
+            | PolyOp::Gather { .. } => in_scales[0],
             PolyOp::Pow(pow) => in_scales[0] * (*pow),
             PolyOp::Pack(_, _) => in_scales[0],
             PolyOp::RangeCheck(_) => in_scales[0],
@@ -302,13 +433,21 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
     }
 
     fn has_3d_input(&self) -> bool {
-        matches!(
-            self,
-            PolyOp::Conv { .. }
-                | PolyOp::SumPool { .. }
-                | PolyOp::GlobalSumPool
-                | PolyOp::Pad { .. }
-        )
+        match self {
+            // only 2D convolutions expect a C x H x W input
+            PolyOp::Conv { kernel, .. } => kernel.dims().len() == 4,
+            PolyOp::SumPool { .. } | PolyOp::GlobalSumPool => true,
+            _ => false,
+        }
+    }
+
+    fn sample_rank(&self) -> Option<usize> {
+        match self {
+            // convolutions of any spatial rank take a single C x spatial dims sample
+            PolyOp::Conv { kernel, .. } => Some(kernel.dims().len() - 1),
+            PolyOp::SumPool { .. } | PolyOp::GlobalSumPool => Some(3),
+            _ => None,
+        }
     }
 
     fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
@@ -316,10 +455,21 @@ impl<F: FieldExt + TensorType> Op<F> for PolyOp<F> {
     }
 
     fn requires_homogenous_input_scales(&self) -> bool {
-        matches!(self, PolyOp::Add { .. } | PolyOp::Sub)
+        matches!(
+            self,
+            PolyOp::Add { .. } | PolyOp::Sub { .. } | PolyOp::Concat { .. }
+        )
     }
 
     fn clone_dyn(&self) -> Box<dyn Op<F>> {
         Box::new(self.clone()) // Forward to the derive(Clone) impl
     }
 }
+
+/// Returns the permutation which transposes the matrices (i.e the last two axes) of a tensor with dims `dims`.
+fn matrix_transpose(dims: &[usize]) -> Vec<usize> {
+    let rank = dims.len();
+    let mut perm = (0..rank).collect::<Vec<_>>();
+    perm.swap(rank - 2, rank - 1);
+    perm
+}
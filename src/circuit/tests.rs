@@ -9,8 +9,105 @@ use halo2_proofs::{
 use halo2curves::pasta::pallas;
 use halo2curves::pasta::Fp as F;
 use rand::rngs::OsRng;
+use std::cell::RefCell;
 use std::marker::PhantomData;
 
+thread_local! {
+    /// The logrows, column length, lookup bits and lookups [OpCircuit]s are configured with on this thread. As
+    /// halo2 configures circuits without an instance of them, these are set by [assert_op_satisfied] beforehand.
+    static OP_CIRCUIT_SETUP: RefCell<(usize, usize, usize, Vec<LookupOp>)> = RefCell::new((0, 0, 0, vec![]));
+}
+
+/// Configures two input columns and an output column of `len` cells over `2^k` rows, with `lookups` over tables
+/// of `bits` bits.
+fn configure_op<F: FieldExt + TensorType>(
+    cs: &mut ConstraintSystem<F>,
+    k: usize,
+    len: usize,
+    bits: usize,
+    lookups: &[LookupOp],
+) -> BaseConfig<F> {
+    let a = VarTensor::new_advice(cs, k, len);
+    let b = VarTensor::new_advice(cs, k, len);
+    let output = VarTensor::new_advice(cs, k, len);
+
+    let mut config = BaseConfig::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE, 0);
+    for nl in lookups {
+        config.configure_lookup(cs, &b, &output, bits, nl).unwrap();
+    }
+    config
+}
+
+/// A circuit laying out a single op on its inputs.
+#[derive(Clone)]
+struct OpCircuit<F: FieldExt + TensorType> {
+    op: Box<dyn Op<F>>,
+    inputs: Vec<ValTensor<F>>,
+}
+
+impl<F: FieldExt + TensorType> Circuit<F> for OpCircuit<F> {
+    type Config = BaseConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        OP_CIRCUIT_SETUP.with(|setup| {
+            let (k, len, bits, lookups) = &*setup.borrow();
+            configure_op(cs, *k, *len, *bits, lookups)
+        })
+    }
+
+    fn synthesize(
+        &self,
+        mut config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.layout_tables(&mut layouter).unwrap();
+        layouter
+            .assign_region(
+                || "",
+                |mut region| {
+                    config
+                        .layout(Some(&mut region), &self.inputs, &mut 0, self.op.clone())
+                        .map_err(|_| Error::Synthesis)
+                },
+            )
+            .unwrap();
+        Ok(())
+    }
+}
+
+/// Lays out `op` on `inputs` in a circuit of `2^k` rows with columns of `len` cells, and the lookups the op
+/// requires over tables of `bits` bits, and asserts the circuit is satisfied.
+fn assert_op_satisfied<F: FieldExt + TensorType>(
+    op: Box<dyn Op<F>>,
+    inputs: &[ValTensor<F>],
+    k: usize,
+    len: usize,
+    bits: usize,
+) {
+    let lookups = op.required_lookups();
+    OP_CIRCUIT_SETUP.with(|setup| *setup.borrow_mut() = (k, len, bits, lookups));
+    let circuit = OpCircuit {
+        op,
+        inputs: inputs.to_vec(),
+    };
+    let prover = MockProver::run(k as u32, &circuit, vec![]).unwrap();
+    prover.assert_satisfied();
+}
+
+/// Asserts `op` maps `inputs` to `expected` outside of a circuit.
+fn assert_op_eq<F: FieldExt + TensorType>(
+    op: &dyn Op<F>,
+    inputs: &[Tensor<i128>],
+    expected: Tensor<i128>,
+) {
+    assert_eq!(op.f(inputs).unwrap(), expected);
+}
+
 #[cfg(test)]
 mod matmul {
     use super::*;
@@ -53,7 +150,12 @@ mod matmul {
                                 Some(&mut region),
                                 &self.inputs.clone(),
                                 &mut 0,
-                                Box::new(PolyOp::Matmul { a: None }),
+                                Box::new(PolyOp::Matmul {
+                                    a: None,
+                                    a_trans: false,
+                                    b_trans: false,
+                                    c_trans: false,
+                                }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -84,6 +186,95 @@ mod matmul {
     }
 }
 
+#[cfg(test)]
+mod batched_matmul {
+    use super::*;
+
+    const K: usize = 9;
+    const LEN: usize = 3;
+
+    // a stack of matrices multiplied by the transpose of another, as in q @ k^T
+    fn op<F: FieldExt + TensorType>() -> PolyOp<F> {
+        PolyOp::Matmul {
+            a: None,
+            a_trans: false,
+            b_trans: true,
+            c_trans: false,
+        }
+    }
+
+    #[derive(Clone)]
+    struct MatmulCircuit<F: FieldExt + TensorType> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for MatmulCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN * LEN);
+            let b = VarTensor::new_advice(cs, K, LEN * LEN);
+            let output = VarTensor::new_advice(cs, K, LEN * LEN);
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE, 0)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &self.inputs.clone(),
+                                &mut 0,
+                                Box::new(op::<F>()),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batchedmatmulcircuit() {
+        let mut a = Tensor::from((0..2 * 2 * LEN).map(|i| Value::known(F::from((i + 1) as u64))));
+        a.reshape(&[2, 2, LEN]);
+
+        let circuit = MatmulCircuit::<F> {
+            inputs: [ValTensor::from(a.clone()), ValTensor::from(a)],
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn batchedmatmulcircuit_matches_reference() {
+        let a = Tensor::<i128>::new(Some(&(1..=12).collect::<Vec<_>>()), &[2, 2, LEN]).unwrap();
+
+        let res = Op::<F>::f(&op::<F>(), &[a.clone(), a]).unwrap();
+
+        let expected =
+            Tensor::<i128>::new(Some(&[14, 32, 32, 77, 194, 266, 266, 365]), &[2, 2, 2]).unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
 #[cfg(test)]
 mod matmul_col_overflow {
     use super::*;
@@ -126,7 +317,12 @@ mod matmul_col_overflow {
                                 Some(&mut region),
                                 &self.inputs.clone(),
                                 &mut 0,
-                                Box::new(PolyOp::Matmul { a: None }),
+                                Box::new(PolyOp::Matmul {
+                                    a: None,
+                                    a_trans: false,
+                                    b_trans: false,
+                                    c_trans: false,
+                                }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -685,6 +881,7 @@ mod conv {
     #[derive(Clone)]
     struct ConvCircuit<F: FieldExt + TensorType> {
         inputs: Vec<ValTensor<F>>,
+        dilation: usize,
         _marker: PhantomData<F>,
     }
 
@@ -720,8 +917,10 @@ mod conv {
                                 Box::new(PolyOp::Conv {
                                     kernel: self.inputs[1].clone(),
                                     bias: None,
-                                    padding: (1, 1),
-                                    stride: (2, 2),
+                                    padding: vec![(1, 1); self.inputs[1].dims().len() - 2],
+                                    stride: vec![2; self.inputs[1].dims().len() - 2],
+                                    dilation: vec![self.dilation; self.inputs[1].dims().len() - 2],
+                                    groups: self.inputs[0].dims()[0] / self.inputs[1].dims()[1],
                                 }),
                             )
                             .map_err(|_| Error::Synthesis)
@@ -763,6 +962,7 @@ mod conv {
                 ValTensor::from(bias),
             ]
             .to_vec(),
+            dilation: 1,
             _marker: PhantomData,
         };
 
@@ -792,6 +992,124 @@ mod conv {
 
         let circuit = ConvCircuit::<F> {
             inputs: [ValTensor::from(image), ValTensor::from(kernels)].to_vec(),
+            dilation: 1,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn conv1dcircuit() {
+        // parameters
+        let kernel_len = 3;
+        let image_len = 9;
+        let in_channels = 2;
+        let out_channels = 3;
+
+        let mut image =
+            Tensor::from((0..in_channels * image_len).map(|i| Value::known(F::from(i as u64))));
+        image.reshape(&[in_channels, image_len]);
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_len })
+                .map(|i| Value::known(F::from(i as u64))),
+        );
+        kernels.reshape(&[out_channels, in_channels, kernel_len]);
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [ValTensor::from(image), ValTensor::from(kernels)].to_vec(),
+            dilation: 1,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn conv3dcircuit() {
+        // parameters
+        let kernel_dims = [2, 2, 2];
+        let image_dims = [3, 4, 3];
+        let in_channels = 2;
+        let out_channels = 2;
+
+        let mut image = Tensor::from(
+            (0..in_channels * image_dims.iter().product::<usize>())
+                .map(|i| Value::known(F::from(i as u64))),
+        );
+        image.reshape(&[&[in_channels], &image_dims[..]].concat());
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_dims.iter().product::<usize>() })
+                .map(|i| Value::known(F::from(i as u64))),
+        );
+        kernels.reshape(&[&[out_channels, in_channels], &kernel_dims[..]].concat());
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [ValTensor::from(image), ValTensor::from(kernels)].to_vec(),
+            dilation: 1,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn dilatedconvcircuit() {
+        // parameters
+        let kernel_height = 2;
+        let kernel_width = 2;
+        let image_height = 6;
+        let image_width = 5;
+        let in_channels = 2;
+        let out_channels = 2;
+
+        let mut image = Tensor::from(
+            (0..in_channels * image_height * image_width).map(|i| Value::known(F::from(i as u64))),
+        );
+        image.reshape(&[in_channels, image_height, image_width]);
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * in_channels * kernel_height * kernel_width })
+                .map(|i| Value::known(F::from(i as u64))),
+        );
+        kernels.reshape(&[out_channels, in_channels, kernel_height, kernel_width]);
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [ValTensor::from(image), ValTensor::from(kernels)].to_vec(),
+            dilation: 2,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn depthwiseconvcircuit() {
+        // parameters
+        let kernel_height = 3;
+        let kernel_width = 3;
+        let image_height = 5;
+        let image_width = 5;
+        let in_channels = 3;
+        let out_channels = 3;
+
+        let mut image = Tensor::from(
+            (0..in_channels * image_height * image_width).map(|i| Value::known(F::from(i as u64))),
+        );
+        image.reshape(&[in_channels, image_height, image_width]);
+        // a single input channel per group
+        let mut kernels = Tensor::from(
+            (0..{ out_channels * kernel_height * kernel_width })
+                .map(|i| Value::known(F::from(i as u64))),
+        );
+        kernels.reshape(&[out_channels, 1, kernel_height, kernel_width]);
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [ValTensor::from(image), ValTensor::from(kernels)].to_vec(),
+            dilation: 1,
             _marker: PhantomData,
         };
 
@@ -880,6 +1198,211 @@ mod sumpool {
     }
 }
 
+#[cfg(test)]
+mod avg_pool {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+
+    const K: usize = 10;
+    const LEN: usize = 64;
+    const BITS: usize = 8;
+
+    // the last row and column of windows only partially overlap the input
+    fn op<F: FieldExt + TensorType>() -> HybridOp<F> {
+        HybridOp::AvgPool2d {
+            padding: [(0, 0), (0, 0)],
+            stride: (2, 2),
+            kernel_shape: (2, 2),
+            ceil_mode: true,
+            count_include_pad: false,
+            image_dims: (3, 3),
+        }
+    }
+
+    #[test]
+    fn avgpoolcircuit() {
+        let mut input = Tensor::from(
+            [4, 2, 6, 0, 4, 2, 3, 1, 8]
+                .iter()
+                .map(|i| Value::known(F::from(*i))),
+        );
+        input.reshape(&[1, 3, 3]);
+        assert_op_satisfied::<F>(Box::new(op::<F>()), &[input.into()], K, LEN, BITS);
+    }
+
+    #[test]
+    fn avgpool_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[4, 2, 6, 0, 4, 2, 3, 1, 8]), &[1, 3, 3]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[3, 4, 2, 8]), &[1, 2, 2]).unwrap();
+        assert_op_eq::<F>(&op::<F>(), &[input], expected);
+    }
+}
+
+#[cfg(test)]
+mod arg_extremum {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+
+    const K: usize = 10;
+    const LEN: usize = 128;
+    const BITS: usize = 8;
+
+    fn input() -> Tensor<i128> {
+        Tensor::<i128>::new(Some(&[2, 7, 7, 5, 0, 3]), &[2, 3]).unwrap()
+    }
+
+    fn val_input() -> ValTensor<F> {
+        let mut input = Tensor::from([2, 7, 7, 5, 0, 3].iter().map(|i| Value::known(F::from(*i))));
+        input.reshape(&[2, 3]);
+        ValTensor::from(input)
+    }
+
+    #[test]
+    fn argmaxcircuit() {
+        for select_last in [false, true] {
+            let op = HybridOp::<F>::ArgMax {
+                axis: 1,
+                select_last,
+            };
+            assert_op_satisfied::<F>(Box::new(op), &[val_input()], K, LEN, BITS);
+        }
+    }
+
+    #[test]
+    fn argmincircuit() {
+        let op = HybridOp::<F>::ArgMin {
+            axis: 0,
+            select_last: false,
+        };
+        assert_op_satisfied::<F>(Box::new(op), &[val_input()], K, LEN, BITS);
+    }
+
+    #[test]
+    fn arg_extremum_with_unknown_values() {
+        // values are unknown during key generation
+        let input: Tensor<Value<F>> = Tensor::new(None, &[2, 3]).unwrap();
+        let mut config = BaseConfig::<F>::dummy(K);
+        let res = config
+            .layout(
+                None,
+                &[input.into()],
+                &mut 0,
+                Box::new(HybridOp::<F>::ArgMax {
+                    axis: 1,
+                    select_last: false,
+                }),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.dims(), &[2, 1]);
+    }
+
+    #[test]
+    fn arg_extremum_matches_reference() {
+        let cases = [
+            (
+                HybridOp::<F>::ArgMax {
+                    axis: 1,
+                    select_last: false,
+                },
+                vec![1, 0],
+                vec![2, 1],
+            ),
+            (
+                HybridOp::<F>::ArgMax {
+                    axis: 1,
+                    select_last: true,
+                },
+                vec![2, 0],
+                vec![2, 1],
+            ),
+            (
+                HybridOp::<F>::ArgMin {
+                    axis: 0,
+                    select_last: false,
+                },
+                vec![0, 1, 0],
+                vec![1, 3],
+            ),
+        ];
+        for (op, expected, dims) in cases {
+            let expected = Tensor::<i128>::new(Some(&expected), &dims).unwrap();
+            assert_op_eq::<F>(&op, &[input()], expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod embedding {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::tensor::ValType;
+
+    const K: usize = 10;
+    const LEN: usize = 128;
+    const BITS: usize = 8;
+    const TABLE: [u64; 6] = [1, 2, 3, 4, 5, 6];
+
+    #[test]
+    fn embeddingcircuit() {
+        // the indices are at a fixed point multiplier of 2
+        let mut indices = Tensor::from([4, 0, 4].iter().map(|i| Value::known(F::from(*i))));
+        indices.reshape(&[3]);
+
+        // a public table is fixed, a private one is witnessed in an advice column
+        let public = Tensor::from(TABLE.iter().map(|i| ValType::Constant(F::from(*i))));
+        let private = Tensor::from(TABLE.iter().map(|i| Value::known(F::from(*i))));
+        for mut table in [ValTensor::from(public), ValTensor::from(private)] {
+            table.reshape(&[3, 2]).unwrap();
+            let op = HybridOp::<F>::Embedding { index_scale: 2 };
+            assert_op_satisfied::<F>(Box::new(op), &[table, indices.clone().into()], K, LEN, BITS);
+        }
+    }
+
+    #[test]
+    fn embedding_matches_reference() {
+        let table = Tensor::<i128>::new(Some(&[1, 2, 3, 4, 5, 6]), &[3, 2]).unwrap();
+        let indices = Tensor::<i128>::new(Some(&[2, 0, 1, 2]), &[2, 2]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[5, 6, 1, 2, 3, 4, 5, 6]), &[2, 2, 2]).unwrap();
+        let op = HybridOp::<F>::Embedding { index_scale: 1 };
+        assert_op_eq::<F>(&op, &[table.clone(), indices], expected);
+
+        // fixed point indices are rounded to the nearest row
+        let op = HybridOp::<F>::Embedding { index_scale: 2 };
+        let indices = Tensor::<i128>::new(Some(&[3, 1]), &[2]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[5, 6, 3, 4]), &[2, 2]).unwrap();
+        assert_op_eq::<F>(&op, &[table.clone(), indices], expected);
+
+        // out of range indices are rejected
+        let indices = Tensor::<i128>::new(Some(&[6]), &[1]).unwrap();
+        assert!(Op::<F>::f(&op, &[table, indices]).is_err());
+    }
+
+    #[test]
+    fn embedding_rejects_oversized_tables() {
+        let op = HybridOp::<F>::Embedding { index_scale: 2 };
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = configure_op(&mut cs, K, LEN, BITS, &Op::<F>::required_lookups(&op));
+
+        // the 8 bit tables can index 128 rows, i.e 64 rows at a fixed point multiplier of 2
+        let mut table: ValTensor<F> =
+            Tensor::from((0..65 * 2).map(|i| ValType::Constant(F::from(i as u64)))).into();
+        table.reshape(&[65, 2]).unwrap();
+        let indices: ValTensor<F> =
+            Tensor::from([0].iter().map(|i| Value::known(F::from(*i)))).into();
+
+        let inputs = [table, indices];
+        let res = config.layout(None, &inputs, &mut 0, Box::new(op.clone()));
+        assert!(res.is_err());
+
+        // the size can't be checked without the tables
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = configure_op(&mut cs, K, LEN, BITS, &[]);
+        let res = config.layout(None, &inputs, &mut 0, Box::new(op));
+        assert!(res.is_err());
+    }
+}
+
 #[cfg(test)]
 mod add_w_shape_casting {
     use super::*;
@@ -1133,7 +1656,7 @@ mod sub {
                                 Some(&mut region),
                                 &self.inputs.clone(),
                                 &mut 0,
-                                Box::new(PolyOp::Sub),
+                                Box::new(PolyOp::Sub { a: None }),
                             )
                             .map_err(|_| Error::Synthesis)
                     },
@@ -1490,7 +2013,12 @@ mod matmul_relu {
             layouter.assign_region(
                 || "",
                 |mut region| {
-                    let op = PolyOp::Matmul { a: None };
+                    let op = PolyOp::Matmul {
+                        a: None,
+                        a_trans: false,
+                        b_trans: false,
+                        c_trans: false,
+                    };
                     let mut offset = 0;
                     let output = config
                         .base_config
@@ -1712,3 +2240,971 @@ mod relu {
         prover.assert_satisfied();
     }
 }
+
+#[cfg(test)]
+mod softmax {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::circuit::lookup::LookupOp;
+    use crate::tensor::ValType;
+
+    const K: usize = 10;
+    const LEN: usize = 3;
+    const BITS: usize = 8;
+    const SCALES: (usize, usize) = (1, 8);
+    // the longest row of 0s whose sum (of exponentials at the output scale) fits in the 8 bit reciprocal table
+    const LONG_LEN: usize = 15;
+
+    #[derive(Clone)]
+    struct SoftmaxCircuit<F: FieldExt + TensorType> {
+        pub input: ValTensor<F>,
+        pub mask: Option<ValTensor<F>>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for SoftmaxCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config =
+                BaseConfig::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE, 0);
+
+            let op = HybridOp::<F>::Softmax {
+                scales: SCALES,
+                bits: BITS,
+                mask: None,
+            };
+            for nl in Op::<F>::required_lookups(&op) {
+                config.configure_lookup(cs, &b, &output, BITS, &nl).unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &[self.input.clone()],
+                                &mut 0,
+                                Box::new(HybridOp::Softmax {
+                                    scales: SCALES,
+                                    bits: BITS,
+                                    mask: self.mask.clone(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn softmaxcircuit() {
+        let mut input = Tensor::from([0, 1, 2, 1, 1, 0].iter().map(|i| Value::known(F::from(*i))));
+        input.reshape(&[2, LEN]);
+
+        let circuit = SoftmaxCircuit::<F> {
+            input: ValTensor::from(input),
+            mask: None,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn softmaxcircuit_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[0, 1, 2, 1, 1, 0]), &[2, LEN]).unwrap();
+
+        let op = HybridOp::<F>::Softmax {
+            scales: SCALES,
+            bits: BITS,
+            mask: None,
+        };
+        let res = Op::<F>::f(&op, &[input]).unwrap();
+
+        let expected = Tensor::<i128>::new(Some(&[5, 15, 40, 24, 24, 9]), &[2, LEN]).unwrap();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn softmax_matches_float_on_large_logits() {
+        let logits = [5.0, 7.5, 10.0, 6.0, 12.0, 5.0, 9.0, 11.0];
+        let input = Tensor::<i128>::new(
+            Some(
+                &logits
+                    .iter()
+                    .map(|l| (l * 128.0) as i128)
+                    .collect::<Vec<_>>(),
+            ),
+            &[2, 4],
+        )
+        .unwrap();
+
+        // with wider tables the reciprocal is taken at a higher scale than the output
+        for bits in [16, 20] {
+            let op = HybridOp::<F>::Softmax {
+                scales: (128, 128),
+                bits,
+                mask: None,
+            };
+            let res = Op::<F>::f(&op, &[input.clone()]).unwrap();
+
+            for (row, out) in logits.chunks(4).zip(res.chunks(4)) {
+                let denom: f32 = row.iter().map(|l: &f32| l.exp()).sum();
+                for (l, o) in row.iter().zip(out) {
+                    let expected = l.exp() / denom;
+                    assert!((*o as f32 / (128.0 * 128.0) - expected).abs() < 0.01);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn softmaxcircuit_long_row() {
+        let mut input = Tensor::from((0..LONG_LEN).map(|_| Value::known(F::from(0))));
+        input.reshape(&[1, LONG_LEN]);
+
+        let circuit = SoftmaxCircuit::<F> {
+            input: ValTensor::from(input),
+            mask: None,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    fn mask<F: FieldExt + TensorType>() -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            [1, 1, 0].iter().map(|i| ValType::Constant(F::from(*i))),
+        ))
+    }
+
+    #[test]
+    fn maskedsoftmaxcircuit() {
+        let mut input = Tensor::from([0, 1, 2, 1, 1, 0].iter().map(|i| Value::known(F::from(*i))));
+        input.reshape(&[2, LEN]);
+
+        let circuit = SoftmaxCircuit::<F> {
+            input: ValTensor::from(input),
+            mask: Some(mask()),
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn maskedsoftmaxcircuit_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[0, 1, 2, 1, 1, 0]), &[2, LEN]).unwrap();
+
+        let op = HybridOp::<F>::Softmax {
+            scales: SCALES,
+            bits: BITS,
+            mask: Some(mask()),
+        };
+        let res = Op::<F>::f(&op, &[input]).unwrap();
+
+        // the masked position is excluded from the normalisation
+        let expected = Tensor::<i128>::new(Some(&[16, 48, 0, 32, 32, 0]), &[2, LEN]).unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[cfg(test)]
+mod instance_norm {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 10;
+    const LEN: usize = 4;
+    const BITS: usize = 8;
+    const SCALE: usize = 4;
+
+    fn params<F: FieldExt + TensorType>(v: &[i128]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            v.iter().map(|e| Value::known(i128_to_felt::<F>(*e))),
+        ))
+    }
+
+    fn op<F: FieldExt + TensorType>() -> HybridOp<F> {
+        HybridOp::InstanceNorm2d {
+            scale: SCALE,
+            epsilon: utils::F32(1e-5),
+            num_inputs: LEN,
+            gamma: params(&[4, 8, 4]),
+            beta: params(&[0, 16, -16]),
+        }
+    }
+
+    #[derive(Clone)]
+    struct NormCircuit<F: FieldExt + TensorType> {
+        pub input: ValTensor<F>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for NormCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config =
+                BaseConfig::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE, 0);
+
+            for nl in Op::<F>::required_lookups(&op::<F>()) {
+                config.configure_lookup(cs, &b, &output, BITS, &nl).unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &[self.input.clone()],
+                                &mut 0,
+                                Box::new(op::<F>()),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn instancenormcircuit() {
+        let mut input = Tensor::from(
+            [0, 1, 2, 1, 1, 0, 4, 2, 8, 1, 1, 2]
+                .iter()
+                .map(|i| Value::known(F::from(*i))),
+        );
+        input.reshape(&[3, 2, 2]);
+
+        let circuit = NormCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn instancenorm_matches_reference() {
+        let input =
+            Tensor::<i128>::new(Some(&[0, 1, 2, 1, 1, 0, 4, 2, 8, 1, 1, 2]), &[3, 2, 2]).unwrap();
+
+        let res = Op::<F>::f(&op::<F>(), &[input]).unwrap();
+
+        let expected = Tensor::<i128>::new(
+            Some(&[-8, 0, 8, 0, 4, -8, 40, 16, 9, -26, -26, -21]),
+            &[3, 2, 2],
+        )
+        .unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[cfg(test)]
+mod batch_norm {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 10;
+    const LEN: usize = 4;
+    const BITS: usize = 8;
+    const SCALE: usize = 4;
+
+    fn params<F: FieldExt + TensorType>(v: &[i128]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            v.iter().map(|e| Value::known(i128_to_felt::<F>(*e))),
+        ))
+    }
+
+    fn op<F: FieldExt + TensorType>() -> HybridOp<F> {
+        HybridOp::BatchNorm {
+            scale: SCALE,
+            epsilon: utils::F32(1e-5),
+            gamma: params(&[4, 4]),
+            beta: params(&[0, 16]),
+            mean: params(&[6, 4]),
+            var: params(&[4, 4]),
+        }
+    }
+
+    #[derive(Clone)]
+    struct NormCircuit<F: FieldExt + TensorType> {
+        pub input: ValTensor<F>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for NormCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config =
+                BaseConfig::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE, 0);
+
+            for nl in Op::<F>::required_lookups(&op::<F>()) {
+                config.configure_lookup(cs, &b, &output, BITS, &nl).unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &[self.input.clone()],
+                                &mut 0,
+                                Box::new(op::<F>()),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batchnormcircuit() {
+        let mut input = Tensor::from([4, 8, 2, 6].iter().map(|i| Value::known(F::from(*i))));
+        input.reshape(&[2, 1, 2]);
+
+        let circuit = NormCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod layer_norm {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 10;
+    const LEN: usize = 6;
+    const BITS: usize = 8;
+    const SCALE: usize = 4;
+
+    fn params<F: FieldExt + TensorType>(v: &[i128]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            v.iter().map(|e| Value::known(i128_to_felt::<F>(*e))),
+        ))
+    }
+
+    // normalises over the leading axis so the slices are strided
+    fn op<F: FieldExt + TensorType>() -> HybridOp<F> {
+        HybridOp::LayerNorm {
+            scale: SCALE,
+            epsilon: utils::F32(1e-5),
+            axes: vec![0],
+            num_inputs: 2,
+            gamma: params(&[4, 8]),
+            beta: params(&[0, 16]),
+        }
+    }
+
+    #[test]
+    fn layernormcircuit() {
+        let mut input = Tensor::from([0, 2, 4, 2, 2, 8].iter().map(|i| Value::known(F::from(*i))));
+        input.reshape(&[2, 3]);
+        assert_op_satisfied::<F>(Box::new(op::<F>()), &[input.into()], K, LEN, BITS);
+    }
+
+    #[test]
+    fn layernorm_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[0, 2, 4, 2, 2, 8]), &[2, 3]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[-8, 0, -12, 32, 16, 40]), &[2, 3]).unwrap();
+        assert_op_eq::<F>(&op::<F>(), &[input], expected);
+    }
+}
+
+#[cfg(test)]
+mod pad {
+    use super::*;
+    use crate::tensor::ops::PadMode;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct PadCircuit<F: FieldExt + TensorType> {
+        input: ValTensor<F>,
+        mode: PadMode<i128>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for PadCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 4 * LEN);
+            let b = VarTensor::new_advice(cs, K, 4 * LEN);
+            let output = VarTensor::new_advice(cs, K, 4 * LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE, 0)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        let mut offset = 0;
+                        let padded = config
+                            .layout(
+                                Some(&mut region),
+                                &[self.input.clone()],
+                                &mut offset,
+                                Box::new(PolyOp::Pad {
+                                    padding: vec![(0, 0), (2, 1)],
+                                    mode: self.mode.clone(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)?
+                            .unwrap();
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &[padded],
+                                &mut offset,
+                                Box::new(PolyOp::Sum),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn padcircuit() {
+        for mode in [PadMode::Constant(3), PadMode::Reflect, PadMode::Edge] {
+            let mut input = Tensor::from((0..2 * LEN).map(|i| Value::known(F::from(i as u64))));
+            input.reshape(&[2, LEN]);
+
+            let circuit = PadCircuit::<F> {
+                input: ValTensor::from(input),
+                mode,
+            };
+
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn padcircuit_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+
+        let op = PolyOp::<F>::Pad {
+            padding: vec![(0, 0), (2, 1)],
+            mode: PadMode::Reflect,
+        };
+        let res = Op::<F>::f(&op, &[input]).unwrap();
+
+        let expected =
+            Tensor::<i128>::new(Some(&[3, 2, 1, 2, 3, 2, 6, 5, 4, 5, 6, 5]), &[2, 6]).unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[cfg(test)]
+mod movement {
+    use super::*;
+    use crate::tensor::ValType;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MovementCircuit<F: FieldExt + TensorType> {
+        inputs: Vec<ValTensor<F>>,
+        op: PolyOp<F>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for MovementCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 4 * LEN);
+            let b = VarTensor::new_advice(cs, K, 4 * LEN);
+            let output = VarTensor::new_advice(cs, K, 4 * LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE, 0)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        let mut offset = 0;
+                        let moved = config
+                            .layout(
+                                Some(&mut region),
+                                &self.inputs,
+                                &mut offset,
+                                Box::new(self.op.clone()),
+                            )
+                            .map_err(|_| Error::Synthesis)?
+                            .unwrap();
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &[moved],
+                                &mut offset,
+                                Box::new(PolyOp::Sum),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn input<F: FieldExt + TensorType>(start: usize) -> ValTensor<F> {
+        let mut input =
+            Tensor::from((start..start + 2 * LEN).map(|i| Value::known(F::from(i as u64))));
+        input.reshape(&[2, LEN]);
+        ValTensor::from(input)
+    }
+
+    #[test]
+    fn movementcircuit() {
+        let constant: Tensor<ValType<F>> =
+            Tensor::from((0..2).map(|i| ValType::Constant(F::from(i as u64))));
+        let mut constant = ValTensor::from(constant);
+        constant.reshape(&[2, 1]).unwrap();
+
+        let ops = vec![
+            (PolyOp::Transpose { perm: vec![1, 0] }, 1),
+            (
+                PolyOp::Concat {
+                    axis: 1,
+                    constants: vec![None, Some(constant), None],
+                },
+                2,
+            ),
+            (
+                PolyOp::Slice {
+                    axis: 1,
+                    start: 1,
+                    end: 3,
+                },
+                1,
+            ),
+            (
+                PolyOp::Gather {
+                    axis: 1,
+                    indices: Tensor::new(Some(&[3, 0, 3]), &[3]).unwrap(),
+                },
+                1,
+            ),
+        ];
+
+        for (op, num_inputs) in ops {
+            let circuit = MovementCircuit::<F> {
+                inputs: (0..num_inputs).map(|i| input(i * 2 * LEN)).collect(),
+                op,
+            };
+
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn movementcircuit_matches_reference() {
+        let a = Tensor::<i128>::new(Some(&[1, 2, 3, 4, 5, 6]), &[2, 3]).unwrap();
+        let b = Tensor::<i128>::new(Some(&[7, 8]), &[2, 1]).unwrap();
+
+        let op = PolyOp::<F>::Concat {
+            axis: 1,
+            constants: vec![None, None],
+        };
+        let res = Op::<F>::f(&op, &[a.clone(), b]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[1, 2, 3, 7, 4, 5, 6, 8]), &[2, 4]).unwrap();
+        assert_eq!(res, expected);
+
+        let op = PolyOp::<F>::Gather {
+            axis: 0,
+            indices: Tensor::new(Some(&[1]), &[]).unwrap(),
+        };
+        let res = Op::<F>::f(&op, &[a]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[4, 5, 6]), &[3]).unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[cfg(test)]
+mod broadcast {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+
+    const K: usize = 8;
+    const LEN: usize = 4;
+    const BITS: usize = 6;
+
+    #[derive(Clone)]
+    struct BroadcastCircuit<F: FieldExt + TensorType> {
+        inputs: [ValTensor<F>; 2],
+        op: Box<dyn Op<F>>,
+    }
+
+    impl<F: FieldExt + TensorType> Circuit<F> for BroadcastCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config =
+                BaseConfig::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE, 0);
+            config
+                .configure_lookup(cs, &b, &output, BITS, &LookupOp::ReLU { scale: 1 })
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |mut region| {
+                        config
+                            .layout(
+                                Some(&mut region),
+                                &self.inputs.clone(),
+                                &mut 0,
+                                self.op.clone(),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn broadcastcircuit() {
+        // a [C, H, W] input and a [C, 1, 1] per channel operand
+        let mut a = Tensor::from((0..2 * LEN).map(|i| Value::known(F::from(i as u64))));
+        a.reshape(&[2, 2, 2]);
+        let mut b = Tensor::from([3, 5].iter().map(|i| Value::known(F::from(*i))));
+        b.reshape(&[2, 1, 1]);
+
+        let ops: Vec<Box<dyn Op<F>>> = vec![
+            Box::new(PolyOp::Add { a: None }),
+            Box::new(PolyOp::Sub { a: None }),
+            Box::new(PolyOp::Mult { a: None }),
+            Box::new(HybridOp::Greater { a: None }),
+            Box::new(HybridOp::EltWiseMax { a: None }),
+            Box::new(HybridOp::EltWiseMin { a: None }),
+        ];
+
+        for op in ops {
+            let circuit = BroadcastCircuit::<F> {
+                inputs: [ValTensor::from(a.clone()), ValTensor::from(b.clone())],
+                op,
+            };
+
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn broadcastcircuit_matches_reference() {
+        let a = Tensor::<i128>::new(Some(&[0, 1, 2, 3, 4, 5, 6, 7]), &[2, 2, 2]).unwrap();
+        let b = Tensor::<i128>::new(Some(&[3, 5]), &[2, 1, 1]).unwrap();
+
+        let op = PolyOp::<F>::Add { a: None };
+        let res = Op::<F>::f(&op, &[a.clone(), b.clone()]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[3, 4, 5, 6, 9, 10, 11, 12]), &[2, 2, 2]).unwrap();
+        assert_eq!(res, expected);
+
+        let op = HybridOp::<F>::Greater { a: None };
+        let res = Op::<F>::f(&op, &[a.clone(), b.clone()]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[0, 0, 0, 0, 0, 0, 1, 1]), &[2, 2, 2]).unwrap();
+        assert_eq!(res, expected);
+
+        let op = HybridOp::<F>::EltWiseMax { a: None };
+        let res = Op::<F>::f(&op, &[a.clone(), b.clone()]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[3, 3, 3, 3, 5, 5, 6, 7]), &[2, 2, 2]).unwrap();
+        assert_eq!(res, expected);
+
+        let op = HybridOp::<F>::EltWiseMin { a: None };
+        let res = Op::<F>::f(&op, &[a, b]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[0, 1, 2, 3, 4, 5, 5, 5]), &[2, 2, 2]).unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[cfg(test)]
+mod logic {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+
+    const K: usize = 8;
+    const LEN: usize = 64;
+    const BITS: usize = 6;
+
+    fn val_tensor(values: &[u64], dims: &[usize]) -> ValTensor<F> {
+        let mut t = Tensor::from(values.iter().map(|i| Value::known(F::from(*i))));
+        t.reshape(dims);
+        ValTensor::from(t)
+    }
+
+    fn tensor(values: &[i128], dims: &[usize]) -> Tensor<i128> {
+        Tensor::<i128>::new(Some(values), dims).unwrap()
+    }
+
+    fn comparison_ops() -> Vec<HybridOp<F>> {
+        vec![
+            HybridOp::GreaterEqual { a: None },
+            HybridOp::Less { a: None },
+            HybridOp::LessEqual { a: None },
+            HybridOp::Equal { a: None },
+        ]
+    }
+
+    fn boolean_ops() -> Vec<HybridOp<F>> {
+        vec![
+            HybridOp::And { a: None },
+            HybridOp::Or { a: None },
+            HybridOp::Xor { a: None },
+        ]
+    }
+
+    #[test]
+    fn comparisoncircuit() {
+        let a = val_tensor(&[0, 3, 5, 2], &[2, 2]);
+        let b = val_tensor(&[3], &[1]);
+        for op in comparison_ops() {
+            assert_op_satisfied::<F>(Box::new(op), &[a.clone(), b.clone()], K, LEN, BITS);
+        }
+    }
+
+    #[test]
+    fn booleancircuit() {
+        let p = val_tensor(&[1, 0, 1, 0], &[2, 2]);
+        let q = val_tensor(&[1, 1, 0, 0], &[2, 2]);
+        for op in boolean_ops() {
+            assert_op_satisfied::<F>(Box::new(op), &[p.clone(), q.clone()], K, LEN, BITS);
+        }
+        assert_op_satisfied::<F>(Box::new(HybridOp::Not), &[p], K, LEN, BITS);
+    }
+
+    #[test]
+    fn iffcircuit() {
+        let mask = val_tensor(&[1, 0], &[2, 1]);
+        let a = val_tensor(&[2, 1, 2, 1, 1, 1], &[2, 3]);
+        let b = val_tensor(&[7], &[1]);
+        assert_op_satisfied::<F>(Box::new(HybridOp::Iff), &[mask, a, b], K, LEN, BITS);
+    }
+
+    #[test]
+    fn logic_matches_reference() {
+        let a = tensor(&[0, 3, 5, 2], &[2, 2]);
+        let b = tensor(&[3], &[1]);
+        let expected = [[0, 1, 1, 0], [1, 0, 0, 1], [1, 1, 0, 1], [0, 1, 0, 0]];
+        for (op, expected) in comparison_ops().into_iter().zip(expected) {
+            assert_op_eq::<F>(&op, &[a.clone(), b.clone()], tensor(&expected, &[2, 2]));
+        }
+
+        let p = tensor(&[1, 0, 1, 0], &[2, 2]);
+        let q = tensor(&[1, 1, 0, 0], &[2, 2]);
+        let expected = [[1, 0, 0, 0], [1, 1, 1, 0], [0, 1, 1, 0]];
+        for (op, expected) in boolean_ops().into_iter().zip(expected) {
+            assert_op_eq::<F>(&op, &[p.clone(), q.clone()], tensor(&expected, &[2, 2]));
+        }
+        assert_op_eq::<F>(&HybridOp::Not, &[p], tensor(&[0, 1, 0, 1], &[2, 2]));
+
+        let mask = tensor(&[1, 0], &[2, 1]);
+        let a = tensor(&[2, 1, 2, 1, 1, 1], &[2, 3]);
+        let b = tensor(&[7], &[1]);
+        assert_op_eq::<F>(
+            &HybridOp::Iff,
+            &[mask, a, b],
+            tensor(&[2, 1, 2, 7, 7, 7], &[2, 3]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod clip {
+    use super::*;
+    use crate::circuit::hybrid::HybridOp;
+    use crate::circuit::utils::F32;
+    use crate::tensor::ValType;
+
+    const K: usize = 8;
+    const LEN: usize = 64;
+    const BITS: usize = 6;
+    // relu6 on inputs at scale 2^2
+    const LOOKUP: LookupOp = LookupOp::Clip {
+        scales: (4, 4),
+        min: F32(0.0),
+        max: F32(6.0),
+    };
+
+    fn comparison_op<F: FieldExt + TensorType>() -> HybridOp<F> {
+        HybridOp::Clip {
+            min: Tensor::from([ValType::Constant(F::from(0))].into_iter()).into(),
+            max: Tensor::from([ValType::Constant(F::from(24))].into_iter()).into(),
+        }
+    }
+
+    #[test]
+    fn clipcircuit() {
+        let input = Tensor::from([0, 5, 12, 25, 30].iter().map(|i| Value::known(F::from(*i))));
+        let ops: Vec<Box<dyn Op<F>>> = vec![Box::new(LOOKUP), Box::new(comparison_op::<F>())];
+        for op in ops {
+            assert_op_satisfied::<F>(op, &[input.clone().into()], K, LEN, BITS);
+        }
+    }
+
+    #[test]
+    fn clip_matches_reference() {
+        let input = Tensor::<i128>::new(Some(&[-8, 0, 5, 12, 25, 30]), &[6]).unwrap();
+        let expected = Tensor::<i128>::new(Some(&[0, 0, 5, 12, 24, 24]), &[6]).unwrap();
+        assert_op_eq::<F>(&LOOKUP, &[input.clone()], expected.clone());
+        assert_op_eq::<F>(&comparison_op::<F>(), &[input], expected);
+    }
+}
+
+#[cfg(test)]
+mod elementwise_lookups {
+    use super::*;
+    use crate::circuit::utils::F32;
+
+    const K: usize = 8;
+    const LEN: usize = 4;
+    const BITS: usize = 6;
+    const OPS: [LookupOp; 9] = [
+        LookupOp::Exp { scales: (4, 4) },
+        LookupOp::Ln { scales: (4, 4) },
+        LookupOp::Recip { scales: (4, 4) },
+        LookupOp::Pow {
+            scales: (4, 4),
+            a: F32(0.5),
+        },
+        LookupOp::Gelu { scales: (4, 4) },
+        LookupOp::Silu { scales: (4, 4) },
+        LookupOp::HardSigmoid {
+            scales: (4, 4),
+            alpha: F32(1.0 / 6.0),
+            beta: F32(0.5),
+        },
+        LookupOp::HardSwish { scales: (4, 4) },
+        LookupOp::Clip {
+            scales: (4, 4),
+            min: F32(0.0),
+            max: F32(0.5),
+        },
+    ];
+
+    #[test]
+    fn lookupcircuit() {
+        let input = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        for nl in OPS {
+            assert_op_satisfied::<F>(Box::new(nl), &[input.clone().into()], K, LEN, BITS);
+        }
+    }
+}
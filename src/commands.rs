@@ -74,6 +74,23 @@ pub struct RunArgs {
     /// run sanity checks during calculations (safe or unsafe)
     #[arg(long, default_value = "safe")]
     pub check_mode: CheckMode,
+    /// The batch size dynamic (symbolic) batch dims of the model inputs are concretised to.
+    /// Each sample's outputs are exposed as separate instances. Models with ops on a single sample
+    /// (e.g convolutions of any rank, pooling, norms) only support a batch size of 1.
+    #[arg(long, default_value = "1")]
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// The path to a quantization profile (see the `calibrate` command) specifying the scale the params of each node are quantized at
+    #[arg(long)]
+    pub quantization_profile: Option<PathBuf>,
+    /// The path to dump the reference and assigned values of every node to when laying out the circuit for the mock prover (for debugging)
+    #[arg(long)]
+    pub dump_tensors: Option<PathBuf>,
+}
+
+/// The batch size of [RunArgs] deserialized without one, e.g from configs predating it.
+fn default_batch_size() -> usize {
+    1
 }
 
 const EZKLCONF: &str = "EZKLCONF";
@@ -177,6 +194,51 @@ pub enum Commands {
         output: String,
     },
 
+    /// Compares the quantized forward pass to a float forward pass of the original model, and prints the error of each output and node
+    #[command(arg_required_else_help = true)]
+    Accuracy {
+        /// The path to the .json data file
+        #[arg(short = 'D', long)]
+        data: String,
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: String,
+        /// Path to save the report to as a .json file (optional)
+        #[arg(short = 'O', long)]
+        output: Option<String>,
+    },
+
+    /// Calibrates the scale the params (e.g weights) of each node are quantized at on sample data, and saves the resulting quantization profile to a .json file
+    #[command(arg_required_else_help = true)]
+    Calibrate {
+        /// The path to the .json data file
+        #[arg(short = 'D', long)]
+        data: String,
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: String,
+        /// Path to the new .json quantization profile
+        #[arg(short = 'O', long)]
+        output: String,
+    },
+
+    /// Searches for the smallest bits, scale and logrows for which the quantized model stays accurate on sample data, and saves them as run args to a .json file (that can be loaded using the RUNARGS env variable)
+    #[command(name = "calibrate-settings", arg_required_else_help = true)]
+    CalibrateSettings {
+        /// The path to the .json data file
+        #[arg(short = 'D', long)]
+        data: String,
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: String,
+        /// Path to the new .json run args file
+        #[arg(short = 'O', long)]
+        output: String,
+        /// The largest absolute error allowed between the quantized and float outputs of the model
+        #[arg(long, default_value = "0.01")]
+        max_error: f32,
+    },
+
     /// Generates a dummy SRS
     #[command(name = "gen-srs", arg_required_else_help = true)]
     GenSrs {
@@ -438,3 +500,16 @@ pub fn data_path(data: String) -> PathBuf {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn run_args_default_batch_size() {
+        let json = r#"{"tolerance":0,"scale":7,"bits":16,"logrows":17,"public_inputs":false,"public_outputs":true,"public_params":false,"pack_base":1,"check_mode":"SAFE"}"#;
+        let run_args: RunArgs = serde_json::from_str(json).unwrap();
+        assert_eq!(run_args.batch_size, 1);
+    }
+}
@@ -30,7 +30,7 @@ use halo2_proofs::{dev::MockProver, poly::commitment::ParamsProver};
 use halo2curves::bn256::{Bn256, Fr, G1Affine};
 #[cfg(not(target_arch = "wasm32"))]
 use log::warn;
-use log::{info, trace};
+use log::{error, info, trace};
 #[cfg(feature = "render")]
 use plotters::prelude::*;
 use snark_verifier::loader::native::NativeLoader;
@@ -44,6 +44,7 @@ use std::io::Write;
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
+use std::sync::PoisonError;
 use std::time::Instant;
 use tabled::Table;
 use thiserror::Error;
@@ -87,6 +88,22 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             model,
             output,
         } => forward(data, model, output, cli.args),
+        Commands::Accuracy {
+            data,
+            model,
+            output,
+        } => accuracy(data, model, output, cli.args),
+        Commands::Calibrate {
+            data,
+            model,
+            output,
+        } => calibrate(data, model, output, cli.args),
+        Commands::CalibrateSettings {
+            data,
+            model,
+            output,
+            max_error,
+        } => calibrate_settings(data, model, output, max_error, cli.args),
         Commands::Mock { data, model: _ } => mock(data, cli.args.logrows),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CreateEVMVerifier {
@@ -346,8 +363,8 @@ fn forward(
 
     // quantize the supplied data using the provided scale.
     let mut model_inputs = vec![];
-    for v in data.input_data.iter() {
-        let t = vector_to_quantized(v, &Vec::from([v.len()]), 0.0, args.scale)?;
+    for (v, shape) in data.input_data.iter().zip(data.input_shapes.iter()) {
+        let t = vector_to_quantized(v, shape, 0.0, args.scale)?;
         model_inputs.push(t);
     }
 
@@ -361,6 +378,65 @@ fn forward(
     Ok(())
 }
 
+fn accuracy(
+    data: String,
+    model: String,
+    output: Option<String>,
+    args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let data = prepare_data(data)?;
+
+    let report = Model::<Fr>::accuracy(model, &data, args)?;
+    info!("output errors:\n{}", Table::new(report.outputs.iter()));
+    info!("node errors:\n{}", Table::new(report.nodes.iter()));
+
+    if let Some(output) = output {
+        serde_json::to_writer(&File::create(output)?, &report)?;
+    }
+    Ok(())
+}
+
+fn calibrate(
+    data: String,
+    model: String,
+    output: String,
+    args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let data = prepare_data(data)?;
+
+    // quantize the supplied data using the provided scale.
+    let mut model_inputs = vec![];
+    for (v, shape) in data.input_data.iter().zip(data.input_shapes.iter()) {
+        let t = vector_to_quantized(v, shape, 0.0, args.scale)?;
+        model_inputs.push(t);
+    }
+
+    let profile = Model::<Fr>::calibrate(model, &model_inputs, args)?;
+    info!("calibrated param scales: {:?}", profile.scales);
+
+    profile.save(output)?;
+    Ok(())
+}
+
+fn calibrate_settings(
+    data: String,
+    model: String,
+    output: String,
+    max_error: f32,
+    args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let data = prepare_data(data)?;
+
+    let args = Model::<Fr>::calibrate_settings(model, &data, args, max_error)?;
+    info!(
+        "calibrated settings: bits {}, scale {}, logrows {}",
+        args.bits, args.scale, args.logrows
+    );
+
+    serde_json::to_writer(&File::create(output)?, &args)?;
+    Ok(())
+}
+
 fn mock(data: String, logrows: u32) -> Result<(), Box<dyn Error>> {
     let data = prepare_data(data)?;
     let model = Model::from_arg()?;
@@ -371,10 +447,21 @@ fn mock(data: String, logrows: u32) -> Result<(), Box<dyn Error>> {
 
     let prover =
         MockProver::run(logrows, &circuit, public_inputs).map_err(Box::<dyn Error>::from)?;
-    prover.assert_satisfied();
-    prover
-        .verify()
-        .map_err(|e| Box::<dyn Error>::from(ExecutionError::VerifyError(e)))?;
+    if let Err(failures) = prover.verify() {
+        // the model is laid out in a single region, so map failures back to the nodes they occurred in
+        let ranges = circuit
+            .layout_ranges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        for failure in failures.iter() {
+            error!("{}", failure);
+            for description in ranges.describe(failure) {
+                error!("{}", description);
+            }
+        }
+        return Err(Box::new(ExecutionError::VerifyError(failures)));
+    }
     Ok(())
 }
 
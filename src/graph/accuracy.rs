@@ -0,0 +1,91 @@
+use crate::tensor::Tensor;
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+/// Summary statistics of the error of quantized values against their float counterparts.
+/// Relative errors are only computed over the values whose float counterpart is non-zero.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct ErrorStats {
+    /// The largest absolute error.
+    pub max_abs: f32,
+    /// The mean absolute error.
+    pub mean_abs: f32,
+    /// The largest relative error.
+    pub max_rel: f32,
+    /// The mean relative error.
+    pub mean_rel: f32,
+}
+
+impl ErrorStats {
+    /// Computes the error of the (dequantized) `quantized` values against the `float` values.
+    /// Tensors are compared elementwise, in their flattened order.
+    pub fn new(quantized: &Tensor<f32>, float: &Tensor<f32>) -> Self {
+        let mut stats = ErrorStats::default();
+        let (mut num_abs, mut num_rel) = (0, 0);
+        for (q, f) in quantized.iter().zip(float.iter()) {
+            let abs = (q - f).abs();
+            stats.max_abs = stats.max_abs.max(abs);
+            stats.mean_abs += abs;
+            num_abs += 1;
+            if *f != 0.0 {
+                let rel = abs / f.abs();
+                stats.max_rel = stats.max_rel.max(rel);
+                stats.mean_rel += rel;
+                num_rel += 1;
+            }
+        }
+        if num_abs > 0 {
+            stats.mean_abs /= num_abs as f32;
+        }
+        if num_rel > 0 {
+            stats.mean_rel /= num_rel as f32;
+        }
+        stats
+    }
+}
+
+/// The error of one output of a node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tabled)]
+pub struct NodeAccuracy {
+    /// The node's index.
+    pub idx: usize,
+    /// The output slot of the node.
+    pub slot: usize,
+    /// The name of the node's op.
+    pub op: String,
+    /// The error of the output.
+    #[tabled(inline)]
+    pub error: ErrorStats,
+}
+
+/// How far the quantized forward pass of a model is from the float model it was loaded from,
+/// as computed by [crate::graph::Model::accuracy].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccuracyReport {
+    /// The error of each output of the model (one per sample of a batch).
+    pub outputs: Vec<ErrorStats>,
+    /// The error of the outputs of each node that has a counterpart in the float model.
+    pub nodes: Vec<NodeAccuracy>,
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn error_stats() {
+        let quantized = Tensor::<f32>::new(Some(&[1.0, 2.5, 0.5, -1.0]), &[4]).unwrap();
+        let float = Tensor::<f32>::new(Some(&[1.0, 2.0, 0.0, -2.0]), &[4]).unwrap();
+        let stats = ErrorStats::new(&quantized, &float);
+        assert_eq!(
+            stats,
+            ErrorStats {
+                max_abs: 1.0,
+                mean_abs: 0.5,
+                max_rel: 0.5,
+                mean_rel: 0.25,
+            }
+        );
+    }
+}
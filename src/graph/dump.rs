@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+/// The (fixed point) values of one output of a node.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputDump {
+    /// The values computed by the node's op (see [crate::circuit::Op::f]).
+    pub reference: Vec<i128>,
+    /// The values assigned in the circuit, if they are known (e.g public inputs aren't).
+    pub assigned: Option<Vec<i128>>,
+}
+
+impl OutputDump {
+    /// Whether the assigned values differ from the reference values.
+    pub fn diverges(&self) -> bool {
+        match &self.assigned {
+            Some(assigned) => assigned != &self.reference,
+            None => false,
+        }
+    }
+}
+
+/// The values of each of the outputs of a node.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeDump {
+    /// The name of the node's op.
+    pub op: String,
+    /// The values of each output of the node.
+    pub outputs: Vec<OutputDump>,
+}
+
+/// The reference and assigned values of every node of a model, keyed by node index, as dumped
+/// when laying out the circuit for the mock prover (see [crate::commands::RunArgs]).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TensorDump {
+    /// The values of each node.
+    pub nodes: BTreeMap<usize, NodeDump>,
+}
+
+impl TensorDump {
+    /// Loads a dump from a .json file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Saves the dump to a .json file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer(&File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Returns the index of the first node (in layout order) with an output whose assigned values
+    /// differ from its reference values, along with that node's values.
+    pub fn first_divergence(&self) -> Option<(usize, &NodeDump)> {
+        self.nodes
+            .iter()
+            .find(|(_, node)| node.outputs.iter().any(|o| o.diverges()))
+            .map(|(idx, node)| (*idx, node))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn first_divergence() {
+        let output = |reference: Vec<i128>, assigned: Option<Vec<i128>>| OutputDump {
+            reference,
+            assigned,
+        };
+        let mut dump = TensorDump::default();
+        dump.nodes.insert(
+            0,
+            NodeDump {
+                op: "Input".to_string(),
+                outputs: vec![output(vec![1, -2], None)],
+            },
+        );
+        dump.nodes.insert(
+            1,
+            NodeDump {
+                op: "Relu".to_string(),
+                outputs: vec![output(vec![1, 0], Some(vec![1, 0]))],
+            },
+        );
+        assert!(dump.first_divergence().is_none());
+
+        dump.nodes.insert(
+            3,
+            NodeDump {
+                op: "Add".to_string(),
+                outputs: vec![output(vec![2, 0], Some(vec![2, 1]))],
+            },
+        );
+        dump.nodes.insert(
+            2,
+            NodeDump {
+                op: "Sigmoid".to_string(),
+                outputs: vec![output(vec![64], Some(vec![65]))],
+            },
+        );
+        assert_eq!(dump.first_divergence().map(|(idx, _)| idx), Some(2));
+    }
+}
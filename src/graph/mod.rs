@@ -1,3 +1,7 @@
+/// Comparisons of quantized forward passes to those of the original float models.
+pub mod accuracy;
+/// Dumps of the values of every node, for debugging circuits.
+pub mod dump;
 /// Helper functions
 pub mod utilities;
 pub use utilities::*;
@@ -5,6 +9,12 @@ pub use utilities::*;
 pub mod model;
 /// Inner elements of a computational graph that represent a single operation / constraints.
 pub mod node;
+/// Per-node quantization profiles.
+pub mod quantization;
+/// Offset ranges ops are laid out at, for mapping mock prover failures back to nodes.
+pub mod ranges;
+/// Registry of constructors for ops the graph loader does not support natively (e.g proprietary ops).
+pub mod registry;
 /// Representations of a computational graph's variables.
 pub mod vars;
 
@@ -14,7 +24,9 @@ use crate::pfsys::ModelInput;
 use crate::tensor::ops::pack;
 use crate::tensor::TensorType;
 use crate::tensor::{Tensor, ValTensor};
+pub use accuracy::*;
 use anyhow::Result;
+pub use dump::*;
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -23,10 +35,14 @@ use halo2_proofs::{
 use log::{info, trace};
 pub use model::*;
 pub use node::*;
+pub use quantization::*;
+pub use ranges::*;
+pub use registry::*;
 // use std::fs::File;
 // use std::io::{BufReader, BufWriter, Read, Write};
 use std::marker::PhantomData;
 // use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
 use thiserror::Error;
 pub use vars::*;
 
@@ -39,12 +55,18 @@ pub enum GraphError {
     /// Shape mismatch in circuit construction
     #[error("invalid dimensions used for node {0} ({1})")]
     InvalidDims(usize, String),
+    /// An op on a single sample (e.g a C x H x W image) was given a batch of several
+    #[error("node {0} ({1}) takes a single sample, but its input of dims {2:?} holds several (only a batch size of 1 is supported for such ops)")]
+    UnsupportedBatch(usize, String, Vec<usize>),
     /// Wrong method was called to configure an op
     #[error("wrong method was called to configure node {0} ({1})")]
     WrongMethod(usize, String),
     /// A requested node is missing in the graph
     #[error("a requested node is missing in the graph: {0}")]
     MissingNode(usize),
+    /// A requested output of a node is missing
+    #[error("node {0} has no output {1}")]
+    MissingOutput(usize, usize),
     /// The wrong method was called on an operation
     #[error("an unsupported method was called on node {0} ({1})")]
     OpMismatch(usize, String),
@@ -84,6 +106,8 @@ pub struct ModelCircuit<F: FieldExt + TensorType> {
     pub inputs: Vec<Tensor<i128>>,
     ///
     pub model: Model<F>,
+    /// The offsets each op was laid out at by the last synthesis of the circuit (see [Model::layout]).
+    pub layout_ranges: Arc<Mutex<LayoutRanges>>,
     /// Represents the Field we are using.
     pub _marker: PhantomData<F>,
 }
@@ -104,6 +128,7 @@ impl<F: FieldExt + TensorType> ModelCircuit<F> {
         Ok(ModelCircuit::<F> {
             inputs,
             model,
+            layout_ranges: Arc::new(Mutex::new(LayoutRanges::default())),
             _marker: PhantomData,
         })
     }
@@ -220,10 +245,14 @@ impl<F: FieldExt + TensorType> Circuit<F> for ModelCircuit<F> {
             .map(|i| ValTensor::from(<Tensor<i128> as Into<Tensor<Value<F>>>>::into(i.clone())))
             .collect::<Vec<ValTensor<F>>>();
         trace!("Setting output in synthesize");
-        config
+        let ranges = config
             .model
             .layout(config.clone(), &mut layouter, &inputs, &config.vars)
             .unwrap();
+        *self
+            .layout_ranges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = ranges;
 
         Ok(())
     }
@@ -7,16 +7,20 @@ use crate::circuit::Op;
 
 use crate::commands::RunArgs;
 use crate::commands::{Cli, Commands};
-use crate::graph::scale_to_multiplier;
+use crate::graph::{
+    rm_batch_dim, sample_dims, scale_to_multiplier, unroll_scan, vector_to_quantized,
+    AccuracyReport, ErrorStats, LayoutRange, LayoutRanges, NodeAccuracy, NodeDump, OutputDump,
+    QuantizationProfile, TensorDump,
+};
+use crate::pfsys::ModelInput;
 use crate::tensor::TensorType;
 use crate::tensor::{Tensor, ValTensor};
 use serde::Deserialize;
 use serde::Serialize;
 use tract_onnx::prelude::DatumExt;
-use tract_onnx::prelude::Graph;
 use tract_onnx::prelude::InferenceModelExt;
-use tract_onnx::prelude::TypedFact;
-use tract_onnx::prelude::TypedOp;
+use tract_onnx::prelude::OutletId;
+use tract_onnx::prelude::{tvec, InferenceModel, TVec, TypedModel};
 use tract_onnx::tract_hir::internal::Factoid;
 use tract_onnx::tract_hir::internal::GenericFactoid;
 //use clap::Parser;
@@ -28,7 +32,7 @@ use halo2_proofs::{
 };
 use itertools::Itertools;
 use log::error;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use std::collections::BTreeMap;
 use std::error::Error;
 use std::path::Path;
@@ -50,6 +54,13 @@ pub enum Mode {
     Verify,
 }
 
+/// The smallest number of bits [Model::calibrate_settings] searches over.
+const MIN_CALIBRATION_BITS: usize = 8;
+/// The largest number of bits [Model::calibrate_settings] searches over.
+const MAX_CALIBRATION_BITS: usize = 24;
+/// Rows reserved for blinding factors when computing the smallest logrows that fits a circuit.
+const CALIBRATION_RESERVED_ROWS: usize = 6;
+
 /// A circuit configuration for the entirety of a model loaded from an Onnx file.
 #[derive(Clone, Debug)]
 pub struct ModelConfig<F: FieldExt + TensorType> {
@@ -65,8 +76,8 @@ pub struct ModelConfig<F: FieldExt + TensorType> {
 pub struct Model<F: FieldExt + TensorType> {
     /// input indices
     pub inputs: Vec<usize>,
-    /// output indices
-    pub outputs: Vec<usize>,
+    /// output [Outlet]s
+    pub outputs: Vec<Outlet>,
     /// Graph of nodes we are loading from Onnx.
     pub nodes: NodeGraph<F>, // Wrapped nodes with additional methods and data (e.g. inferred shape, quantization)
     /// The [RunArgs] being used
@@ -75,6 +86,9 @@ pub struct Model<F: FieldExt + TensorType> {
     pub mode: Mode,
     /// Defines which inputs to the model are public and private (params, inputs, outputs) using [VarVisibility].
     pub visibility: VarVisibility,
+    /// The number of samples held along the leading axis of the model's inputs and outputs, if its inputs have a
+    /// dynamic batch dim, as recorded when the inputs are concretised to [RunArgs::batch_size].
+    pub batch: Option<usize>,
 }
 
 impl<F: FieldExt + TensorType> Model<F> {
@@ -90,21 +104,32 @@ impl<F: FieldExt + TensorType> Model<F> {
         mode: Mode,
         visibility: VarVisibility,
     ) -> Result<Self, Box<dyn Error>> {
-        let (model, nodes) = Self::load_onnx_model(path, run_args.scale, run_args.public_params)?;
+        let profile = Self::load_profile(&run_args)?;
+        let (inputs, outputs, nodes, batch) = Self::load_onnx_model(
+            path,
+            run_args.scale,
+            run_args.public_params,
+            run_args.bits,
+            run_args.batch_size,
+            &profile,
+        )?;
 
         let om = Model {
-            inputs: model.inputs.iter().map(|o| o.node).collect(),
-            outputs: model.outputs.iter().map(|o| o.node).collect(),
+            inputs,
+            outputs,
             run_args,
             nodes,
             mode,
             visibility,
+            batch,
         };
 
         Ok(om)
     }
 
     /// Runs a forward pass on sample data !
+    /// If the model has a dynamic batch dim, it is concretised to the batch size of `run_args` and each
+    /// sample's outputs are returned separately.
     /// # Arguments
     /// * `path` - A path to an Onnx file.
     /// * `run_args` - [RunArgs]
@@ -113,99 +138,619 @@ impl<F: FieldExt + TensorType> Model<F> {
         model_inputs: &[Tensor<i128>],
         run_args: RunArgs,
     ) -> Result<Vec<Tensor<f32>>, Box<dyn Error>> {
-        let (model, nodes) =
-            Self::load_onnx_model(model_path, run_args.scale, run_args.public_params)?;
+        let (_, outputs, nodes, batch) = Self::load_onnx_model(
+            model_path,
+            run_args.scale,
+            run_args.public_params,
+            run_args.bits,
+            run_args.batch_size,
+            &Self::load_profile(&run_args)?,
+        )?;
+
+        let results = Self::forward_nodes(&nodes, model_inputs)?;
+
+        let output_nodes = outputs.iter();
+        info!(
+            "model outputs are node outlets: {:?}",
+            output_nodes.clone().collect_vec()
+        );
+        let mut outputs = vec![];
+        for (idx, slot) in output_nodes {
+            let n = nodes.get(idx).unwrap();
+            let scale = scale_to_multiplier(n.out_scales[*slot]);
+            let output = results.get(idx).unwrap()[*slot]
+                .clone()
+                .map(|x| (x as f32) / scale);
+            // each sample of a batch is a separate output
+            outputs.extend(split_samples(output, batch)?);
+        }
+
+        Ok(outputs)
+    }
+
+    /// Runs a forward pass of the original (float) model using tract, i.e without quantization.
+    /// Outputs are returned in the same layout as those of [Model::forward].
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `input_data` - The (flattened) inputs to the model.
+    /// * `batch_size` - The batch size dynamic batch dims are concretised to.
+    pub fn float_forward(
+        model_path: impl AsRef<Path>,
+        input_data: &[Vec<f32>],
+        batch_size: usize,
+    ) -> Result<Vec<Tensor<f32>>, Box<dyn Error>> {
+        let (model, batch) = Self::load_concrete_model(model_path, batch_size)?;
+        let model = model.into_typed()?;
+        let inputs = float_inputs(&model, input_data)?;
+
+        let plan = model.into_optimized()?.into_runnable()?;
+        let mut outputs = vec![];
+        for output in plan.run(inputs)? {
+            let dims = rm_batch_dim(output.shape().to_vec());
+            let output = Tensor::new(Some(output.as_slice::<f32>()?), &dims)?;
+            outputs.extend(split_samples(output, batch)?);
+        }
+        Ok(outputs)
+    }
+
+    /// Searches for the smallest `bits` (and then the largest `scale` for those bits) for which the
+    /// quantized forward pass of the model on sample data doesn't overflow the lookup tables and
+    /// stays within `max_error` of the float model. The smallest `logrows` that fits the resulting
+    /// circuit is then computed using [Model::dummy_layout].
+    /// Returns `run_args` updated with the resulting settings.
+    /// A quantization profile set in `run_args` is kept: the search quantizes node params with it, so
+    /// profiled nodes keep their param scales whatever the global scale. As a profile is calibrated at
+    /// a given global scale (see [Model::calibrate]), a warning is logged if the calibrated scale differs.
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `data` - The sample data to calibrate on.
+    /// * `run_args` - The [RunArgs] to start from.
+    /// * `max_error` - The largest absolute error allowed on the outputs of the model.
+    pub fn calibrate_settings(
+        model_path: impl AsRef<Path>,
+        data: &ModelInput,
+        mut run_args: RunArgs,
+        max_error: f32,
+    ) -> Result<RunArgs, Box<dyn Error>> {
+        let float_outputs =
+            Self::float_forward(model_path.as_ref(), &data.input_data, run_args.batch_size)?;
+
+        let profile = Self::load_profile(&run_args)?;
+
+        let mut settings = None;
+        'search: for bits in MIN_CALIBRATION_BITS..=MAX_CALIBRATION_BITS {
+            // larger scales are more accurate, so the first scale that fits is the best one for these bits
+            for scale in (1..bits as u32 - 1).rev() {
+                let model_inputs = data
+                    .input_data
+                    .iter()
+                    .zip(data.input_shapes.iter())
+                    .map(|(v, shape)| vector_to_quantized(v, shape, 0.0, scale))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (_, outputs, nodes, batch) = Self::load_onnx_model(
+                    model_path.as_ref(),
+                    scale,
+                    run_args.public_params,
+                    bits,
+                    run_args.batch_size,
+                    &profile,
+                )?;
+                let results = Self::forward_nodes(&nodes, &model_inputs)?;
+
+                // the inputs of lookups need to fit in the lookup tables
+                let fits = nodes
+                    .values()
+                    .filter(|n| !n.opkind.required_lookups().is_empty())
+                    .flat_map(|n| n.inputs.iter())
+                    .all(|(idx, slot)| headroom(&results[idx][*slot], bits) >= 0);
+                if !fits {
+                    continue;
+                }
+
+                let mut error: f32 = 0.0;
+                let mut quantized_outputs = vec![];
+                for (idx, slot) in outputs.iter() {
+                    let multiplier = scale_to_multiplier(nodes[idx].out_scales[*slot]);
+                    let output = results[idx][*slot].map(|x| (x as f32) / multiplier);
+                    quantized_outputs.extend(split_samples(output, batch)?);
+                }
+                for (q, f) in quantized_outputs.iter().zip(float_outputs.iter()) {
+                    for (a, b) in q.iter().zip(f.iter()) {
+                        error = error.max((a - b).abs());
+                    }
+                }
+                debug!("bits {}, scale {}: max error {}", bits, scale, error);
+                if error <= max_error {
+                    settings = Some((bits, scale));
+                    break 'search;
+                }
+                break;
+            }
+        }
+
+        let (bits, scale) = match settings {
+            Some(settings) => settings,
+            None => {
+                return Err(Box::new(GraphError::MisformedParams(format!(
+                    "no bits and scale up to {} bits reach a max error of {}",
+                    MAX_CALIBRATION_BITS, max_error
+                ))))
+            }
+        };
+        info!("calibrated bits {} and scale {}", bits, scale);
+        if let Some(path) = &run_args.quantization_profile {
+            if scale != run_args.scale {
+                warn!(
+                    "calibrated scale {} differs from scale {}, the quantization profile {} may need recalibrating",
+                    scale,
+                    run_args.scale,
+                    path.display()
+                );
+            }
+        }
+        run_args.bits = bits;
+        run_args.scale = scale;
 
-        let mut results: BTreeMap<&usize, Tensor<i128>> = BTreeMap::new();
+        // the lookup tables need 2^bits rows, and the smallest logrows can only grow the layout
+        let mut logrows = bits as u32 + 1;
+        loop {
+            run_args.logrows = logrows;
+            let visibility = VarVisibility::from_args(run_args.clone())?;
+            let model = Model::<F>::new(
+                model_path.as_ref(),
+                run_args.clone(),
+                Mode::Mock,
+                visibility,
+            )?;
+            let num_rows = model.dummy_layout(&model.input_shapes())? + CALIBRATION_RESERVED_ROWS;
+            let needed = (num_rows as f64).log2().ceil() as u32;
+            if needed <= logrows {
+                break;
+            }
+            logrows = needed;
+        }
+        info!("calibrated logrows {}", logrows);
+
+        Ok(run_args)
+    }
+
+    /// Compares the quantized forward pass of the model on sample data to the forward pass of the
+    /// original (float) model using tract, for each output of the model and each of its nodes.
+    /// Nodes without a counterpart in the float model (e.g those unrolled from a scan), or whose
+    /// float counterpart holds a different number of values (e.g fused patterns), are skipped.
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `data` - The sample data to compare on.
+    /// * `run_args` - [RunArgs]
+    pub fn accuracy(
+        model_path: impl AsRef<Path>,
+        data: &ModelInput,
+        run_args: RunArgs,
+    ) -> Result<AccuracyReport, Box<dyn Error>> {
+        let (mut model, batch) = Self::load_typed_model(model_path, run_args.batch_size)?;
+        let (_, outputs, nodes, ids) = Self::load_nodes(
+            &model,
+            run_args.scale,
+            run_args.public_params,
+            run_args.bits,
+            &Self::load_profile(&run_args)?,
+        )?;
+        Self::check_batch(&nodes, &outputs, batch)?;
+
+        let model_inputs = data
+            .input_data
+            .iter()
+            .zip(data.input_shapes.iter())
+            .map(|(v, shape)| vector_to_quantized(v, shape, 0.0, run_args.scale))
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = Self::forward_nodes(&nodes, &model_inputs)?;
+        let dequantized = |idx: &usize, slot: usize| {
+            let multiplier = scale_to_multiplier(nodes[idx].out_scales[slot]);
+            results[idx][slot].map(|x| (x as f32) / multiplier)
+        };
+
+        // expose the outputs of every node of the float model, not only those of the model
+        let model_outlets = model.outputs.clone();
+        let outlets = model
+            .nodes
+            .iter()
+            .flat_map(|n| (0..n.outputs.len()).map(move |slot| OutletId::new(n.id, slot)))
+            .collect_vec();
+        model.set_output_outlets(&outlets)?;
+        let inputs = float_inputs(&model, &data.input_data)?;
+        let mut float_results = BTreeMap::new();
+        for (outlet, output) in outlets.iter().zip(model.into_runnable()?.run(inputs)?) {
+            // only the outputs of ops over numbers can be compared
+            if let Ok(output) = output.cast_to::<f32>() {
+                let dims = rm_batch_dim(output.shape().to_vec());
+                let output = Tensor::new(Some(output.as_slice::<f32>()?), &dims)?;
+                float_results.insert((outlet.node, outlet.slot), output);
+            }
+        }
+
+        let mut report = AccuracyReport::default();
+        for ((idx, slot), outlet) in outputs.iter().zip(model_outlets.iter()) {
+            let float = match float_results.get(&(outlet.node, outlet.slot)) {
+                Some(float) => float.clone(),
+                None => return Err(Box::new(GraphError::MissingOutput(*idx, *slot))),
+            };
+            let quantized = split_samples(dequantized(idx, *slot), batch)?;
+            for (q, f) in quantized.iter().zip(split_samples(float, batch)?.iter()) {
+                report.outputs.push(ErrorStats::new(q, f));
+            }
+        }
+        for (id, idx) in ids.iter() {
+            let node = match nodes.get(idx) {
+                Some(node) => node,
+                None => continue,
+            };
+            for slot in 0..node.out_dims.len() {
+                let quantized = dequantized(idx, slot);
+                match float_results.get(&(*id, slot)) {
+                    Some(float) if float.len() == quantized.len() => {
+                        report.nodes.push(NodeAccuracy {
+                            idx: *idx,
+                            slot,
+                            op: node.opkind.as_str().to_string(),
+                            error: ErrorStats::new(&quantized, float),
+                        });
+                    }
+                    _ => debug!("node {} output {} has no float counterpart", idx, slot),
+                }
+            }
+        }
+        report.nodes.sort_by_key(|n| (n.idx, n.slot));
+
+        Ok(report)
+    }
+
+    /// Runs a forward pass over loaded nodes, returning the (fixed point) outputs of every node.
+    /// # Arguments
+    /// * `nodes` - The [NodeGraph] to run.
+    /// * `model_inputs` - The quantized inputs to the model.
+    pub fn forward_nodes(
+        nodes: &NodeGraph<F>,
+        model_inputs: &[Tensor<i128>],
+    ) -> Result<BTreeMap<usize, Vec<Tensor<i128>>>, Box<dyn Error>> {
+        let mut results: BTreeMap<usize, Vec<Tensor<i128>>> = BTreeMap::new();
         for (i, n) in nodes.iter() {
             let mut inputs = vec![];
             if n.opkind.is_input() {
                 let mut t = model_inputs[*i].clone();
-                t.reshape(&n.out_dims);
+                if t.len() != n.out_dims[0].iter().product::<usize>() {
+                    return Err(Box::new(GraphError::InvalidDims(
+                        *i,
+                        format!(
+                            "input of {} values for dims {:?} (is the batch size right?)",
+                            t.len(),
+                            n.out_dims[0]
+                        ),
+                    )));
+                }
+                t.reshape(&n.out_dims[0]);
                 inputs.push(t);
             } else {
-                for i in n.inputs.iter() {
-                    match results.get(&i) {
+                for (idx, slot) in n.inputs.iter() {
+                    match results.get(idx).and_then(|r| r.get(*slot)) {
                         Some(value) => inputs.push(value.clone()),
-                        None => return Err(Box::new(GraphError::MissingNode(*i))),
+                        None => return Err(Box::new(GraphError::MissingOutput(*idx, *slot))),
                     }
                 }
             };
-            results.insert(i, Op::<F>::f(&*n.opkind, &inputs)?);
+            results.insert(*i, Op::<F>::f_outputs(&*n.opkind, &inputs)?);
         }
+        Ok(results)
+    }
 
-        let output_nodes = model.outputs.iter();
-        info!(
-            "model outputs are nodes: {:?}",
-            output_nodes.clone().map(|o| o.node).collect_vec()
-        );
-        let outputs = output_nodes
-            .map(|o| {
-                let n = nodes.get(&o.node).unwrap();
-                let scale = scale_to_multiplier(n.out_scale);
-                results
-                    .get(&o.node)
-                    .unwrap()
-                    .clone()
-                    .map(|x| (x as f32) / scale)
-            })
-            .collect_vec();
+    /// Calibrates the scales the params (e.g weights) of each node are quantized at on sample data,
+    /// such that the fixed point outputs of every node fit in the `bits`-wide lookup tables.
+    /// A node's param scale is shifted from the global scale by the headroom left in the fixed point
+    /// outputs of the node and of the nodes it feeds into. As shifts compound across consecutive
+    /// nodes, positive shifts are then lowered until a forward pass using the profile fits.
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `model_inputs` - The quantized sample inputs to the model.
+    /// * `run_args` - [RunArgs]
+    pub fn calibrate(
+        model_path: impl AsRef<Path>,
+        model_inputs: &[Tensor<i128>],
+        run_args: RunArgs,
+    ) -> Result<QuantizationProfile, Box<dyn Error>> {
+        let run = |profile: &QuantizationProfile| {
+            let (inputs, _, nodes, _) = Self::load_onnx_model(
+                model_path.as_ref(),
+                run_args.scale,
+                run_args.public_params,
+                run_args.bits,
+                run_args.batch_size,
+                profile,
+            )?;
+            let headrooms = Self::forward_nodes(&nodes, model_inputs)?
+                .into_iter()
+                .map(|(idx, res)| {
+                    let h = res.iter().map(|t| headroom(t, run_args.bits)).min();
+                    (idx, h.unwrap_or(0))
+                })
+                .collect::<BTreeMap<usize, i64>>();
+            Ok::<_, Box<dyn Error>>((inputs, nodes, headrooms))
+        };
 
-        Ok(outputs)
+        let (inputs, nodes, headrooms) = run(&QuantizationProfile::default())?;
+        let mut shifts = BTreeMap::new();
+        for (idx, node) in nodes.iter().filter(|(idx, _)| !inputs.contains(*idx)) {
+            let shift = nodes
+                .values()
+                .filter(|n| n.inputs.iter().any(|(i, _)| i == idx))
+                .chain([node])
+                .map(|n| headrooms[&n.idx])
+                .min()
+                .unwrap_or(0);
+            shifts.insert(*idx, shift);
+        }
+
+        loop {
+            let profile = QuantizationProfile {
+                scales: shifts
+                    .iter()
+                    .filter(|(_, shift)| **shift != 0)
+                    .map(|(idx, shift)| (*idx, (run_args.scale as i64 + shift).max(0) as u32))
+                    .collect(),
+            };
+            let (_, _, headrooms) = run(&profile)?;
+            let overflow = headrooms.values().copied().min().unwrap_or(0);
+            if overflow >= 0 {
+                return Ok(profile);
+            }
+            if shifts.values().all(|shift| *shift <= 0) {
+                warn!(
+                    "node outputs overflow the lookup tables by {} bits",
+                    -overflow
+                );
+                return Ok(profile);
+            }
+            for shift in shifts.values_mut().filter(|shift| **shift > 0) {
+                *shift = (*shift + overflow).max(0);
+            }
+        }
     }
 
-    /// Loads an Onnx model from a specified path.
+    /// Loads the [QuantizationProfile] specified in `run_args`, if any.
+    fn load_profile(run_args: &RunArgs) -> Result<QuantizationProfile, Box<dyn Error>> {
+        match &run_args.quantization_profile {
+            Some(path) => QuantizationProfile::load(path),
+            None => Ok(QuantizationProfile::default()),
+        }
+    }
+
+    /// Parses an Onnx model, concretising the dims of its inputs.
+    /// Additionally returns the batch size if a dynamic batch dim was concretised, which is then
+    /// the leading axis of the model's inputs and outputs.
     /// # Arguments
     /// * `path` - A path to an Onnx file.
-    /// * `scale` - The scale to use for quantization.
-    fn load_onnx_model(
+    /// * `batch_size` - The batch size dynamic batch dims are concretised to.
+    fn load_concrete_model(
         path: impl AsRef<Path>,
-        scale: u32,
-        public_params: bool,
-    ) -> Result<(Graph<TypedFact, Box<dyn TypedOp>>, BTreeMap<usize, Node<F>>), Box<dyn Error>>
-    {
+        batch_size: usize,
+    ) -> Result<(InferenceModel, Option<usize>), Box<dyn Error>> {
         let mut model = tract_onnx::onnx()
             .model_for_path(path)
             .map_err(|_| GraphError::ModelLoad)?;
-        // .into_optimized()?;
 
+        let mut batch = None;
         for (i, id) in model.clone().inputs.iter().enumerate() {
             let input = model.node(id.node);
 
-            // add batch dim
+            // concretise unknown or symbolic (i.e dynamic batch) dims to the batch size
             let mut dims = vec![];
-            let extracted_dims: Vec<usize> = input.outputs[0]
-                .fact
-                .shape
-                .dims()
-                .filter_map(|x| x.concretize())
-                .map(|x| x.to_i64().unwrap() as usize)
-                .collect();
+            for (axis, x) in input.outputs[0].fact.shape.dims().enumerate() {
+                dims.push(match x.concretize().map(|d| d.to_i64()) {
+                    Some(Ok(d)) => d as usize,
+                    _ if axis == 0 => {
+                        batch = Some(batch_size);
+                        batch_size
+                    }
+                    _ => {
+                        return Err(Box::new(GraphError::InvalidDims(
+                            id.node,
+                            format!(
+                                "dynamic dim {} of input {} (only the batch dim can be)",
+                                axis, i
+                            ),
+                        )))
+                    }
+                });
+            }
 
-            // if we have unknown / unspecified dims, add a batch dim of 1
+            // if we have unspecified dims, add a batch dim
             if let GenericFactoid::Only(elem) = input.outputs[0].fact.shape.rank() {
-                if (elem as usize) > extracted_dims.len() {
-                    dims.push(1);
+                if (elem as usize) > dims.len() {
+                    dims.insert(0, batch_size);
+                    batch = Some(batch_size);
                 }
             };
-            dims.extend(extracted_dims);
 
             model = model.with_input_fact(i, f32::fact(dims).into())?;
         }
+        Ok((model, batch))
+    }
+
+    /// Checks that every output of the model holds the batch (if any) along its leading axis,
+    /// such that it can be split into one output per sample (see [sample_dims]).
+    fn check_batch(
+        nodes: &NodeGraph<F>,
+        outputs: &[Outlet],
+        batch: Option<usize>,
+    ) -> Result<(), GraphError> {
+        let batch_size = match batch {
+            Some(b) if b > 1 => b,
+            _ => return Ok(()),
+        };
+        for (idx, slot) in outputs {
+            let dims = &nodes[idx].out_dims[*slot];
+            if dims.len() < 2 || dims[0] != batch_size {
+                return Err(GraphError::InvalidDims(
+                    *idx,
+                    format!(
+                        "output of dims {:?} doesn't hold the batch of {} samples along its leading axis",
+                        dims, batch_size
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads an Onnx model from a specified path.
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `scale` - The scale to use for quantization.
+    /// * `bits` - The number of bits used in lookup tables.
+    /// * `batch_size` - The batch size dynamic batch dims are concretised to.
+    /// * `profile` - The [QuantizationProfile] node params are quantized with.
+    /// Returns the indices of the model's input nodes and its output [Outlet]s along with the loaded nodes,
+    /// and the batch size if the model has a dynamic batch dim (see [Model::load_concrete_model]).
+    fn load_onnx_model(
+        path: impl AsRef<Path>,
+        scale: u32,
+        public_params: bool,
+        bits: usize,
+        batch_size: usize,
+        profile: &QuantizationProfile,
+    ) -> Result<(Vec<usize>, Vec<Outlet>, NodeGraph<F>, Option<usize>), Box<dyn Error>> {
+        let (model, batch) = Self::load_typed_model(path, batch_size)?;
+        let (inputs, outputs, nodes, _) =
+            Self::load_nodes(&model, scale, public_params, bits, profile)?;
+        Self::check_batch(&nodes, &outputs, batch)?;
+        Ok((inputs, outputs, nodes, batch))
+    }
+
+    /// Parses an Onnx model into the (decluttered) tract graph ezkl nodes are loaded from,
+    /// along with its batch size (see [Model::load_concrete_model]).
+    /// # Arguments
+    /// * `path` - A path to an Onnx file.
+    /// * `batch_size` - The batch size dynamic batch dims are concretised to.
+    fn load_typed_model(
+        path: impl AsRef<Path>,
+        batch_size: usize,
+    ) -> Result<(TypedModel, Option<usize>), Box<dyn Error>> {
+        let (model, batch) = Self::load_concrete_model(path, batch_size)?;
         // Note: do not optimize the model, as the layout will depend on underlying hardware
-        let model = model.into_typed()?.into_decluttered()?;
+        Ok((model.into_typed()?.into_decluttered()?, batch))
+    }
 
-        let mut nodes = BTreeMap::<usize, Node<F>>::new();
-        for (i, n) in model.nodes.iter().enumerate() {
-            let n = Node::<F>::new(n.clone(), &mut nodes, scale, public_params, i)?;
-            nodes.insert(i, n);
+    /// Loads ezkl nodes from a tract graph (see [Model::load_onnx_model]).
+    /// Additionally returns the index of the ezkl node loaded for each tract node id.
+    fn load_nodes(
+        model: &TypedModel,
+        scale: u32,
+        public_params: bool,
+        bits: usize,
+        profile: &QuantizationProfile,
+    ) -> Result<
+        (
+            Vec<usize>,
+            Vec<Outlet>,
+            NodeGraph<F>,
+            BTreeMap<usize, usize>,
+        ),
+        Box<dyn Error>,
+    > {
+        // loaded nodes keyed by their onnx node id. Unrolled nodes (e.g of recurrent layers) take
+        // up identifiers of their own, so node identifiers are assigned in load order instead.
+        let mut loaded = BTreeMap::<usize, Node<F>>::new();
+        let mut unrolled = vec![];
+        let mut idx = 0;
+        for n in model.nodes.iter() {
+            if n.op().name() == "Scan" {
+                let mut steps = unroll_scan(
+                    n,
+                    model,
+                    &loaded,
+                    scale,
+                    profile,
+                    public_params,
+                    bits,
+                    &mut idx,
+                )?;
+                // the last unrolled node stands in for the scan
+                if let Some(out) = steps.pop() {
+                    loaded.insert(n.id, out);
+                }
+                unrolled.extend(steps);
+            } else {
+                let node = Node::<F>::new(
+                    n.clone(),
+                    &mut loaded,
+                    scale,
+                    profile.scale(idx, scale),
+                    public_params,
+                    bits,
+                    idx,
+                    model,
+                )?;
+                loaded.insert(n.id, node);
+                idx += 1;
+            }
         }
 
+        let node_outlet = |o: &OutletId| {
+            loaded
+                .get(&o.node)
+                .map(|n| (n.idx, o.slot))
+                .ok_or(GraphError::MissingNode(o.node))
+        };
+        let inputs = model
+            .inputs
+            .iter()
+            .map(|o| node_outlet(o).map(|(idx, _)| idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = model
+            .outputs
+            .iter()
+            .map(node_outlet)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids = loaded.iter().map(|(id, n)| (*id, n.idx)).collect();
+        let mut nodes: NodeGraph<F> = unrolled
+            .into_iter()
+            .chain(loaded.into_values())
+            .map(|n| (n.idx, n))
+            .collect();
+        Self::prune_dead_nodes(&mut nodes, &inputs, &outputs);
+
         debug!("\n {}", model);
 
         debug!("\n {}", Table::new(nodes.iter()).to_string());
 
-        Ok((model, nodes))
+        Ok((inputs, outputs, nodes, ids))
+    }
+
+    /// Removes nodes which neither feed into the model outputs nor are model inputs,
+    /// e.g the intermediate nodes of patterns fused in [Node::new].
+    fn prune_dead_nodes(nodes: &mut NodeGraph<F>, inputs: &[usize], outputs: &[Outlet]) {
+        let mut live: Vec<usize> = inputs
+            .iter()
+            .copied()
+            .chain(outputs.iter().map(|(idx, _)| *idx))
+            .collect();
+        let mut stack = live.clone();
+        while let Some(idx) = stack.pop() {
+            if let Some(node) = nodes.get(&idx) {
+                for (i, _) in node.inputs.iter() {
+                    if !live.contains(i) {
+                        live.push(*i);
+                        stack.push(*i);
+                    }
+                }
+            }
+        }
+        nodes.retain(|idx, _| {
+            let keep = live.contains(idx);
+            if !keep {
+                trace!("pruning dead node {}", idx);
+            }
+            keep
+        });
     }
 
     /// Creates a `Model` from parsed CLI arguments
@@ -264,7 +809,7 @@ impl<F: FieldExt + TensorType> Model<F> {
         let lookup_ops: BTreeMap<&usize, &Node<F>> = self
             .nodes
             .iter()
-            .filter(|(_, n)| n.opkind.required_lookup().is_some())
+            .filter(|(_, n)| !n.opkind.required_lookups().is_empty())
             .collect();
 
         for node in lookup_ops.values() {
@@ -295,17 +840,17 @@ impl<F: FieldExt + TensorType> Model<F> {
         let input = &vars.advices[0];
         let output = &vars.advices[1];
 
-        let op = match &node.opkind.required_lookup() {
-            Some(nl) => nl.clone(),
-            None => {
-                return Err(Box::new(GraphError::WrongMethod(
-                    node.idx,
-                    node.opkind.as_str().to_string(),
-                )));
-            }
-        };
+        let ops = node.opkind.required_lookups();
+        if ops.is_empty() {
+            return Err(Box::new(GraphError::WrongMethod(
+                node.idx,
+                node.opkind.as_str().to_string(),
+            )));
+        }
 
-        config.configure_lookup(meta, input, output, self.run_args.bits, &op)?;
+        for op in ops.iter() {
+            config.configure_lookup(meta, input, output, self.run_args.bits, op)?;
+        }
 
         Ok(())
     }
@@ -316,20 +861,24 @@ impl<F: FieldExt + TensorType> Model<F> {
     /// * `config` - [ModelConfig] holding all node configs.
     /// * `layouter` - Halo2 Layouter.
     /// * `inputs` - The values to feed into the circuit.
+    ///
+    /// Returns the offsets each op was laid out at in the `"model"` region, such that failures of the
+    /// mock prover can be mapped back to nodes (see [LayoutRanges::describe]).
     pub fn layout(
         &self,
         mut config: ModelConfig<F>,
         layouter: &mut impl Layouter<F>,
         inputs: &[ValTensor<F>],
         vars: &ModelVars<F>,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<LayoutRanges, Box<dyn Error>> {
         info!("model layout");
-        let mut results = BTreeMap::<usize, ValTensor<F>>::new();
+        let mut results = BTreeMap::<usize, Vec<ValTensor<F>>>::new();
+        let mut ranges = LayoutRanges::default();
         for (i, input_idx) in self.inputs.iter().enumerate() {
             if self.visibility.input.is_public() {
-                results.insert(*input_idx, vars.instances[i].clone());
+                results.insert(*input_idx, vec![vars.instances[i].clone()]);
             } else {
-                results.insert(*input_idx, inputs[i].clone());
+                results.insert(*input_idx, vec![inputs[i].clone()]);
             }
         }
 
@@ -339,17 +888,23 @@ impl<F: FieldExt + TensorType> Model<F> {
             || "model",
             |mut region| {
                 let mut offset: usize = 0;
+                // the floor planner may call this more than once, so only keep the last layout's ranges
+                ranges = LayoutRanges {
+                    col_size: config.base.output.col_size(),
+                    ranges: vec![],
+                };
                 for (idx, node) in self.nodes.iter() {
                     let values: Vec<ValTensor<F>> = node
                         .inputs
                         .iter()
-                        .map(|i| results.get(i).unwrap().clone())
+                        .map(|(idx, slot)| results.get(idx).unwrap()[*slot].clone())
                         .collect_vec();
 
                     trace!("laying out offset {}", offset);
+                    let start = offset;
                     let res = config
                         .base
-                        .layout(
+                        .layout_outputs(
                             Some(&mut region),
                             &values,
                             &mut offset,
@@ -359,50 +914,64 @@ impl<F: FieldExt + TensorType> Model<F> {
                             error!("{}", e);
                             halo2_proofs::plonk::Error::Synthesis
                         })?;
+                    ranges.ranges.push(LayoutRange {
+                        node: Some(*idx),
+                        op: node.opkind.as_str().to_string(),
+                        offsets: start..offset,
+                    });
 
-                    if let Some(vt) = res {
-                        // we get the max as for fused nodes this corresponds to the node output
-                        results.insert(*idx, vt);
+                    if !res.is_empty() {
                         //only use with mock prover
                         if matches!(self.mode, Mode::Mock) {
-                            trace!(
-                                "------------ output node {:?}: {:?}",
-                                idx,
-                                results.get(idx).unwrap().show()
-                            );
+                            for (slot, vt) in res.iter().enumerate() {
+                                trace!(
+                                    "------------ output node {:?}: {:?}",
+                                    (idx, slot),
+                                    vt.show()
+                                );
+                            }
                         }
+                        results.insert(*idx, res);
                     }
                 }
 
                 let output_nodes = self.outputs.iter();
                 info!(
-                    "model outputs are nodes: {:?}",
+                    "model outputs are node outlets: {:?}",
                     output_nodes.clone().collect_vec()
                 );
-                let mut outputs = output_nodes
-                    .map(|o| results.get(o).unwrap().clone())
+                let outputs = output_nodes
+                    .map(|(idx, slot)| results.get(idx).unwrap()[*slot].clone())
                     .collect_vec();
+                let mut outputs = self.split_batch(outputs).map_err(|e| {
+                    error!("{}", e);
+                    halo2_proofs::plonk::Error::Synthesis
+                })?;
 
                 // pack outputs if need be
                 if self.run_args.pack_base > 1 {
                     for i in 0..outputs.len() {
                         info!("packing outputs...");
+                        let start = offset;
+                        let op = PolyOp::<F>::Pack(self.run_args.pack_base, self.run_args.scale);
                         outputs[i] = config
                             .base
                             .layout(
                                 Some(&mut region),
                                 &outputs[i..i + 1],
                                 &mut offset,
-                                Box::new(PolyOp::Pack(
-                                    self.run_args.pack_base,
-                                    self.run_args.scale,
-                                )),
+                                Box::new(op.clone()),
                             )
                             .map_err(|e| {
                                 error!("{}", e);
                                 halo2_proofs::plonk::Error::Synthesis
                             })?
                             .unwrap();
+                        ranges.ranges.push(LayoutRange {
+                            node: None,
+                            op: op.as_str().to_string(),
+                            offsets: start..offset,
+                        });
                         // only use with mock prover
                         if matches!(self.mode, Mode::Mock) {
                             trace!("------------ packed output {:?}", outputs[i].show());
@@ -411,29 +980,88 @@ impl<F: FieldExt + TensorType> Model<F> {
                 }
 
                 if self.run_args.public_outputs {
-                    let _ = outputs
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, output)| {
-                            let mut instance_offset = 0;
-                            if self.visibility.input.is_public() {
-                                instance_offset += inputs.len();
-                            };
-                            config.base.layout(
-                                Some(&mut region),
-                                &[output, vars.instances[instance_offset + i].clone()],
-                                &mut offset,
-                                Box::new(PolyOp::RangeCheck(self.run_args.tolerance as i32)),
-                            )
-                        })
-                        .collect_vec();
+                    for (i, output) in outputs.into_iter().enumerate() {
+                        let mut instance_offset = 0;
+                        if self.visibility.input.is_public() {
+                            instance_offset += inputs.len();
+                        };
+                        let start = offset;
+                        let op = PolyOp::<F>::RangeCheck(self.run_args.tolerance as i32);
+                        let _ = config.base.layout(
+                            Some(&mut region),
+                            &[output, vars.instances[instance_offset + i].clone()],
+                            &mut offset,
+                            Box::new(op.clone()),
+                        );
+                        ranges.ranges.push(LayoutRange {
+                            node: None,
+                            op: op.as_str().to_string(),
+                            offsets: start..offset,
+                        });
+                    }
                 }
 
                 Ok(())
             },
         )?;
+
+        //only use with mock prover
+        if matches!(self.mode, Mode::Mock) {
+            if let Some(path) = &self.run_args.dump_tensors {
+                let dump = self.dump_tensors(inputs, &results)?;
+                dump.save(path)?;
+                match dump.first_divergence() {
+                    Some((idx, node)) => error!(
+                        "node {} ({}) is the first whose assigned values diverge from its reference values",
+                        idx, node.op
+                    ),
+                    None => info!("assigned values match the reference values of every node"),
+                }
+            }
+        }
         info!("computing...");
-        Ok(())
+        Ok(ranges)
+    }
+
+    /// Collects the reference (see [Op::f]) and assigned values of every node laid out by [Model::layout].
+    /// # Arguments
+    ///
+    /// * `inputs` - The values fed into the circuit.
+    /// * `results` - The values assigned to each node's outputs.
+    fn dump_tensors(
+        &self,
+        inputs: &[ValTensor<F>],
+        results: &BTreeMap<usize, Vec<ValTensor<F>>>,
+    ) -> Result<TensorDump, Box<dyn Error>> {
+        let model_inputs = inputs
+            .iter()
+            .map(|i| Ok(Tensor::new(Some(i.get_int_evals()?.as_slice()), i.dims())?))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        let reference = Self::forward_nodes(&self.nodes, &model_inputs)?;
+
+        let mut dump = TensorDump::default();
+        for (idx, node) in self.nodes.iter() {
+            let outputs = reference[idx]
+                .iter()
+                .enumerate()
+                .map(|(slot, r)| OutputDump {
+                    reference: r.to_vec(),
+                    // e.g public inputs are instances, which don't hold values
+                    assigned: results
+                        .get(idx)
+                        .and_then(|res| res.get(slot))
+                        .and_then(|vt| vt.get_int_evals().ok()),
+                })
+                .collect();
+            dump.nodes.insert(
+                *idx,
+                NodeDump {
+                    op: node.opkind.as_str().to_string(),
+                    outputs,
+                },
+            );
+        }
+        Ok(dump)
     }
 
     /// Assigns values to the regions created when calling `configure`.
@@ -444,7 +1072,7 @@ impl<F: FieldExt + TensorType> Model<F> {
     /// * `inputs` - The values to feed into the circuit.
     pub fn dummy_layout(&self, input_shapes: &[Vec<usize>]) -> Result<usize, Box<dyn Error>> {
         info!("model layout");
-        let mut results = BTreeMap::<usize, ValTensor<F>>::new();
+        let mut results = BTreeMap::<usize, Vec<ValTensor<F>>>::new();
 
         let inputs: Vec<ValTensor<F>> = input_shapes
             .iter()
@@ -455,7 +1083,7 @@ impl<F: FieldExt + TensorType> Model<F> {
             .collect_vec();
 
         for (i, input_idx) in self.inputs.iter().enumerate() {
-            results.insert(*input_idx, inputs[i].clone());
+            results.insert(*input_idx, vec![inputs[i].clone()]);
         }
 
         let mut dummy_config = PolyConfig::dummy(self.run_args.logrows as usize);
@@ -465,30 +1093,30 @@ impl<F: FieldExt + TensorType> Model<F> {
             let values: Vec<ValTensor<F>> = node
                 .inputs
                 .iter()
-                .map(|i| results.get(i).unwrap().clone())
+                .map(|(idx, slot)| results.get(idx).unwrap()[*slot].clone())
                 .collect_vec();
 
             let res = dummy_config
-                .layout(None, &values, &mut offset, node.opkind.clone_dyn())
+                .layout_outputs(None, &values, &mut offset, node.opkind.clone_dyn())
                 .map_err(|e| {
                     error!("{}", e);
                     halo2_proofs::plonk::Error::Synthesis
                 })?;
 
-            if let Some(vt) = res {
-                // we get the max as for fused nodes this corresponds to the node output
-                results.insert(*idx, vt);
+            if !res.is_empty() {
+                results.insert(*idx, res);
             }
         }
 
         let output_nodes = self.outputs.iter();
         info!(
-            "model outputs are nodes: {:?}",
+            "model outputs are node outlets: {:?}",
             output_nodes.clone().collect_vec()
         );
-        let mut outputs = output_nodes
-            .map(|o| results.get(o).unwrap().clone())
+        let outputs = output_nodes
+            .map(|(idx, slot)| results.get(idx).unwrap()[*slot].clone())
             .collect_vec();
+        let mut outputs = self.split_batch(outputs)?;
 
         // pack outputs if need be
         if self.run_args.pack_base > 1 {
@@ -536,32 +1164,55 @@ impl<F: FieldExt + TensorType> Model<F> {
     pub fn input_shapes(&self) -> Vec<Vec<usize>> {
         self.inputs
             .iter()
-            .map(|o| self.nodes.get(o).unwrap().out_dims.clone())
+            .map(|o| self.nodes.get(o).unwrap().out_dims[0].clone())
             .collect_vec()
     }
 
-    /// Returns the number of the computational graph's outputs
+    /// Returns the number of the computational graph's outputs, with one output per sample of a batch
     pub fn num_outputs(&self) -> usize {
-        let output_nodes = self.outputs.iter();
-        output_nodes.len()
+        self.output_shapes().len()
     }
 
-    /// Returns shapes of the computational graph's outputs
+    /// Returns shapes of the computational graph's outputs, with one output per sample of a batch
     pub fn output_shapes(&self) -> Vec<Vec<usize>> {
         self.outputs
             .iter()
-            .map(|o| self.nodes.get(o).unwrap().out_dims.clone())
+            .flat_map(|(idx, slot)| {
+                sample_dims(&self.nodes.get(idx).unwrap().out_dims[*slot], self.batch)
+            })
             .collect_vec()
     }
 
-    /// Returns the fixed point scale of the computational graph's outputs
+    /// Returns the fixed point scale of the computational graph's outputs, with one output per sample of a batch
     pub fn get_output_scales(&self) -> Vec<u32> {
         let output_nodes = self.outputs.iter();
         output_nodes
-            .map(|o| self.nodes.get(o).unwrap().out_scale)
+            .flat_map(|(idx, slot)| {
+                let node = self.nodes.get(idx).unwrap();
+                let samples = sample_dims(&node.out_dims[*slot], self.batch).len();
+                vec![node.out_scales[*slot]; samples]
+            })
             .collect_vec()
     }
 
+    /// Splits the outputs of the computational graph into one output per sample of a batch.
+    fn split_batch(&self, outputs: Vec<ValTensor<F>>) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+        let mut split = vec![];
+        for output in outputs {
+            let samples = sample_dims(output.dims(), self.batch);
+            if samples.len() == 1 {
+                split.push(output);
+                continue;
+            }
+            for (i, dims) in samples.iter().enumerate() {
+                let mut sample = output.get_slice(&[i..i + 1])?;
+                sample.reshape(dims)?;
+                split.push(sample);
+            }
+        }
+        Ok(split)
+    }
+
     /// Number of instances used by the circuit
     pub fn instance_shapes(&self) -> Vec<Vec<usize>> {
         // for now the number of instances corresponds to the number of graph / model outputs
@@ -575,3 +1226,591 @@ impl<F: FieldExt + TensorType> Model<F> {
         instance_shapes
     }
 }
+
+/// Splits `output` into one output per sample of a batch (see [sample_dims]).
+fn split_samples(
+    output: Tensor<f32>,
+    batch: Option<usize>,
+) -> Result<Vec<Tensor<f32>>, Box<dyn Error>> {
+    let samples = sample_dims(output.dims(), batch);
+    if samples.len() == 1 {
+        return Ok(vec![output]);
+    }
+    let mut split = vec![];
+    for (i, dims) in samples.iter().enumerate() {
+        let mut sample = output.get_slice(&[i..i + 1])?;
+        sample.reshape(dims);
+        split.push(sample);
+    }
+    Ok(split)
+}
+
+/// Builds the inputs of a forward pass of the float `model` from the (flattened) `input_data`.
+fn float_inputs(
+    model: &TypedModel,
+    input_data: &[Vec<f32>],
+) -> Result<TVec<tract_onnx::prelude::Tensor>, Box<dyn Error>> {
+    let mut inputs = tvec![];
+    for (i, data) in input_data.iter().enumerate() {
+        let fact = model.input_fact(i)?;
+        let dims = match fact.shape.as_concrete() {
+            Some(dims) => dims.to_vec(),
+            None => return Err(Box::new(GraphError::InvalidDims(i, "input".to_string()))),
+        };
+        inputs.push(tract_onnx::prelude::Tensor::from_shape(
+            &dims,
+            data.as_slice(),
+        )?);
+    }
+    Ok(inputs)
+}
+
+/// Returns by how many bits the values of `t` can be scaled up and still fit in `bits`-wide lookup
+/// tables (keeping a bit of margin for inputs larger than the sample), negative if they don't fit.
+fn headroom(t: &Tensor<i128>, bits: usize) -> i64 {
+    let max = t.iter().map(|x| x.unsigned_abs()).max().unwrap_or(0);
+    bits as i64 - 2 - (128 - max.leading_zeros()) as i64
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::circuit::base::BaseOp;
+    use crate::circuit::lookup::LookupOp;
+    use crate::circuit::{layouts, CheckMode};
+    use crate::fieldutils::i128_to_felt;
+    use crate::graph::{register_op, unregister_op, ModelCircuit};
+    use crate::pfsys::prepare_data;
+    use crate::tensor::{TensorError, ValType};
+    use halo2_proofs::circuit::{Region, SimpleFloorPlanner};
+    use halo2_proofs::dev::{MockProver, VerifyFailure};
+    use halo2_proofs::plonk::{Circuit, Error as PlonkError};
+    use halo2curves::pasta::Fp as F;
+    use tract_onnx::prelude::{Node as OnnxNode, TypedFact, TypedOp};
+
+    fn run_args() -> RunArgs {
+        RunArgs {
+            tolerance: 0,
+            scale: 7,
+            bits: 16,
+            logrows: 17,
+            public_inputs: false,
+            public_outputs: true,
+            public_params: false,
+            pack_base: 1,
+            check_mode: CheckMode::SAFE,
+            batch_size: 1,
+            quantization_profile: None,
+            dump_tensors: None,
+        }
+    }
+
+    fn load(example: &str) -> (Model<F>, ModelInput) {
+        load_with(example, run_args())
+    }
+
+    fn load_with(example: &str, run_args: RunArgs) -> (Model<F>, ModelInput) {
+        let data = prepare_data(format!("./examples/onnx/{}/input.json", example)).unwrap();
+        let model = Model::<F>::new(
+            format!("./examples/onnx/{}/network.onnx", example),
+            run_args.clone(),
+            Mode::Mock,
+            VarVisibility::from_args(run_args).unwrap(),
+        )
+        .unwrap();
+        (model, data)
+    }
+
+    fn forward(example: &str, data: &ModelInput) -> Vec<Tensor<f32>> {
+        let inputs = data
+            .input_data
+            .iter()
+            .zip(data.input_shapes.iter())
+            .map(|(v, shape)| vector_to_quantized(v, shape, 0.0, run_args().scale).unwrap())
+            .collect_vec();
+        Model::<F>::forward(
+            format!("./examples/onnx/{}/network.onnx", example),
+            &inputs,
+            run_args(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn channel_bias_is_added_along_channels() {
+        let (model, data) = load("1l_channel_bias");
+        // the [C, 1, 1] bias broadcasts over the spatial dims of the [C, H, W] input, i.e it isn't
+        // tiled along (or broadcast over) any other axis
+        let add = model
+            .nodes
+            .values()
+            .find(|n| n.opkind.as_str() == "ADD")
+            .unwrap();
+        assert_eq!(add.out_dims[0], vec![3, 2, 2]);
+
+        let outputs = forward("1l_channel_bias", &data);
+        assert_eq!(outputs[0].len(), data.output_data[0].len());
+        for (output, expected) in outputs[0].iter().zip(data.output_data[0].iter()) {
+            assert!((output - expected).abs() < 0.02);
+        }
+    }
+
+    /// Checks the quantized forward pass of `example` against the float model run by tract.
+    fn assert_matches_float(example: &str, data: &ModelInput, tol: f32) {
+        let outputs = forward(example, data);
+        let float_outputs = Model::<F>::float_forward(
+            format!("./examples/onnx/{}/network.onnx", example),
+            &data.input_data,
+            run_args().batch_size,
+        )
+        .unwrap();
+        assert_eq!(outputs.len(), float_outputs.len());
+        for (output, expected) in outputs.iter().zip(float_outputs.iter()) {
+            assert_eq!(output.len(), expected.len());
+            for (o, e) in output.iter().zip(expected.iter()) {
+                assert!((o - e).abs() < tol, "{} vs {}", o, e);
+            }
+        }
+    }
+
+    #[test]
+    fn instance_norm_is_fused() {
+        let (model, data) = load("1l_instance_norm_affine");
+        // tract's decomposition collapses into a single node
+        let ops = model
+            .nodes
+            .values()
+            .map(|n| n.opkind.as_str())
+            .collect_vec();
+        assert_eq!(ops.iter().filter(|op| **op == "INSTANCENORM").count(), 1);
+        assert!(!ops.contains(&"MEAN"));
+
+        assert_matches_float("1l_instance_norm_affine", &data, 0.05);
+    }
+
+    #[test]
+    fn asymmetric_padding_is_kept() {
+        let (model, data) = load("1l_conv_pool_asym");
+        // only padded after the image, so both outputs keep the input's spatial dims
+        assert_eq!(model.output_shapes(), vec![vec![1, 4, 4], vec![1, 4, 4]]);
+        assert_matches_float("1l_conv_pool_asym", &data, 0.05);
+    }
+
+    #[test]
+    fn clips_beyond_the_lookup_range_are_compared() {
+        let (model, data) = load("1l_clip_wide");
+        // the upper bound of 300 doesn't fit the 16 bit tables at scale 7, so the clip is laid out as comparisons
+        let clip = model
+            .nodes
+            .values()
+            .find(|n| n.opkind.as_str() == "CLIP")
+            .unwrap();
+        assert_eq!(
+            clip.opkind.required_lookups(),
+            vec![LookupOp::ReLU { scale: 1 }]
+        );
+        assert_matches_float("1l_clip_wide", &data, 0.01);
+    }
+
+    #[test]
+    fn truncating_casts_are_rejected() {
+        let err = Model::<F>::new(
+            "./examples/onnx/1l_cast_float_int/network.onnx",
+            run_args(),
+            Mode::Mock,
+            VarVisibility::from_args(run_args()).unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<GraphError>(),
+            Some(GraphError::MisformedParams(_))
+        ));
+    }
+
+    #[test]
+    fn batch_norm_is_fused() {
+        let (model, data) = load("1l_batch_norm_affine");
+        let ops = model
+            .nodes
+            .values()
+            .map(|n| n.opkind.as_str())
+            .collect_vec();
+        assert_eq!(ops.iter().filter(|op| **op == "BATCHNORM").count(), 1);
+        assert!(!ops.contains(&"MULT") && !ops.contains(&"ADD"));
+
+        assert_matches_float("1l_batch_norm_affine", &data, 0.05);
+    }
+
+    #[test]
+    fn comparisons_with_a_constant_left_operand() {
+        let (model, data) = load("1l_compare_const");
+        // `c > x` is laid out as `x < c` and `c < x` as `x > c`
+        let ops = model
+            .nodes
+            .values()
+            .map(|n| n.opkind.as_str())
+            .collect_vec();
+        assert!(ops.contains(&"GREATER") && ops.contains(&"LESS"));
+
+        let outputs = forward("1l_compare_const", &data);
+        assert_eq!(outputs.len(), data.output_data.len());
+        for (output, expected) in outputs.iter().zip(data.output_data.iter()) {
+            assert_eq!(output.to_vec(), *expected);
+        }
+        assert_matches_float("1l_compare_const", &data, 1e-6);
+    }
+
+    #[test]
+    fn scan_outputs_are_wired_to_their_slots() {
+        let (model, data) = load("1l_lstm_hidden");
+        // the unrolled LSTM is stood in for by a node carrying both Y and Y_h
+        let scan = model
+            .nodes
+            .values()
+            .find(|n| n.opkind.as_str() == "Tuple")
+            .unwrap();
+        let lens = scan
+            .out_dims
+            .iter()
+            .map(|d| d.iter().product::<usize>())
+            .collect_vec();
+        assert_eq!(lens, vec![6, 2]);
+        assert!(model
+            .nodes
+            .values()
+            .any(|n| n.inputs.contains(&(scan.idx, 1))));
+
+        let outputs = forward("1l_lstm_hidden", &data);
+        assert_eq!(outputs.len(), 2);
+        for (output, expected) in outputs.iter().zip(data.output_data.iter()) {
+            assert_eq!(output.len(), expected.len());
+            for (o, e) in output.iter().zip(expected.iter()) {
+                assert!((o - e).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_is_recorded_for_dynamic_batch_dims() {
+        let batched = RunArgs {
+            batch_size: 2,
+            ..run_args()
+        };
+        let (model, _) = load_with("1l_batch", batched.clone());
+        assert_eq!(model.batch, Some(2));
+        assert_eq!(model.output_shapes(), vec![vec![3], vec![3]]);
+
+        // the leading dim of the (static) LSTM output is its sequence length, not a batch
+        let (model, _) = load_with(
+            "1l_lstm",
+            RunArgs {
+                batch_size: 3,
+                ..batched
+            },
+        );
+        assert_eq!(model.batch, None);
+        assert_eq!(model.output_shapes().len(), 1);
+    }
+
+    #[test]
+    fn calibrated_settings_keep_the_quantization_profile() {
+        let dir = tempdir::TempDir::new("calibration").unwrap();
+        let profile = dir.path().join("profile.json");
+        QuantizationProfile::default().save(&profile).unwrap();
+
+        let data = prepare_data("./examples/onnx/1l_relu/input.json".to_string()).unwrap();
+        let settings = Model::<F>::calibrate_settings(
+            "./examples/onnx/1l_relu/network.onnx",
+            &data,
+            RunArgs {
+                quantization_profile: Some(profile.clone()),
+                ..run_args()
+            },
+            0.05,
+        )
+        .unwrap();
+        assert_eq!(settings.quantization_profile, Some(profile));
+    }
+
+    #[test]
+    fn batches_are_rejected_for_ops_on_single_samples() {
+        let batched = RunArgs {
+            batch_size: 2,
+            ..run_args()
+        };
+        // convolutions of any spatial rank take a single sample
+        for (example, batch_dims) in [
+            ("1l_conv_batch", vec![2, 1, 3, 3]),
+            ("1l_conv1d_batch", vec![2, 1, 4]),
+        ] {
+            let err = Model::<F>::new(
+                format!("./examples/onnx/{}/network.onnx", example),
+                batched.clone(),
+                Mode::Mock,
+                VarVisibility::from_args(batched.clone()).unwrap(),
+            )
+            .unwrap_err();
+            match err.downcast_ref::<GraphError>() {
+                Some(GraphError::UnsupportedBatch(_, name, dims)) => {
+                    assert_eq!(name, "CONV");
+                    assert_eq!(dims, &batch_dims);
+                }
+                _ => panic!("expected an unsupported batch error, got {}", err),
+            }
+        }
+
+        let path = "./examples/onnx/1l_conv_batch/network.onnx";
+
+        // a single sample is convolved as usual
+        let model = Model::<F>::new(
+            path,
+            run_args(),
+            Mode::Mock,
+            VarVisibility::from_args(run_args()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(model.output_shapes(), vec![vec![2, 2, 2]]);
+    }
+
+    /// An op ezkl doesn't support natively, laid out as `relu(x) + relu(-x)`.
+    #[derive(Clone, Debug)]
+    struct CustomAbs;
+
+    impl Op<F> for CustomAbs {
+        fn f(&self, x: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+            Ok(x[0].map(|v| v.abs()))
+        }
+
+        fn as_str(&self) -> &'static str {
+            "CUSTOM_ABS"
+        }
+
+        fn layout(
+            &self,
+            config: &mut PolyConfig<F>,
+            mut region: Option<&mut Region<F>>,
+            values: &[ValTensor<F>],
+            offset: &mut usize,
+        ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+            let zero: ValTensor<F> =
+                Tensor::from([ValType::Constant(F::from(0))].into_iter()).into();
+            let neg = layouts::pairwise(
+                config,
+                region.as_deref_mut(),
+                &[zero, values[0].clone()],
+                offset,
+                BaseOp::Sub,
+            )?;
+            let relus = [values[0].clone(), neg]
+                .into_iter()
+                .map(|x| {
+                    layouts::nonlinearity(
+                        config,
+                        region.as_deref_mut(),
+                        &[x],
+                        &LookupOp::ReLU { scale: 1 },
+                        offset,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(layouts::pairwise(
+                config,
+                region,
+                relus[..].try_into()?,
+                offset,
+                BaseOp::Add,
+            )?))
+        }
+
+        fn required_lookups(&self) -> Vec<LookupOp> {
+            vec![LookupOp::ReLU { scale: 1 }]
+        }
+
+        fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn custom_abs(
+        _: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+        _: u32,
+        _: bool,
+        _: &[Node<F>],
+    ) -> Result<Box<dyn Op<F>>, Box<dyn Error>> {
+        Ok(Box::new(CustomAbs))
+    }
+
+    // the registry is global, so the tests which register ops do so for ops (`Abs` and `Neg`) no other fixture
+    // holds, which the loads of other tests running alongside them can't pick up
+
+    #[test]
+    fn registered_ops_are_loaded_and_configured() {
+        register_op::<F>("Abs", custom_abs);
+        let loaded = std::panic::catch_unwind(|| load("1l_abs"));
+        unregister_op::<F>("Abs");
+        let (model, _) = loaded.unwrap();
+
+        // the registered constructor is used for an op ezkl doesn't support natively
+        let relu = LookupOp::ReLU { scale: 1 };
+        let node = model
+            .nodes
+            .values()
+            .find(|n| n.opkind.as_str() == "CUSTOM_ABS")
+            .unwrap();
+        assert_eq!(node.opkind.required_lookups(), vec![relu.clone()]);
+
+        // and its lookups are configured like those of any native op
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut vars = ModelVars::new(
+            &mut cs,
+            model.run_args.logrows as usize,
+            model.dummy_layout(&model.input_shapes()).unwrap(),
+            model.instance_shapes(),
+            model.visibility.clone(),
+            model.run_args.scale,
+        );
+        let config = model.configure(&mut cs, &mut vars).unwrap();
+        assert_eq!(config.base.tables.keys().collect_vec(), vec![&relu]);
+    }
+
+    /// A negation laid out as a division lookup, which assigns the wrong values to its outputs, i.e whose lookups
+    /// aren't satisfied.
+    #[derive(Clone, Debug)]
+    struct TamperedNeg(LookupOp);
+
+    impl Op<F> for TamperedNeg {
+        fn f(&self, x: &[Tensor<i128>]) -> Result<Tensor<i128>, TensorError> {
+            Op::<F>::f(&self.0, x)
+        }
+
+        fn as_str(&self) -> &'static str {
+            "TAMPERED_NEG"
+        }
+
+        fn layout(
+            &self,
+            config: &mut PolyConfig<F>,
+            mut region: Option<&mut Region<F>>,
+            values: &[ValTensor<F>],
+            offset: &mut usize,
+        ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+            let start = *offset;
+            let output = Op::<F>::layout(&self.0, config, region.as_deref_mut(), values, offset)?;
+            if let (Some(region), Some(output)) = (region, &output) {
+                if let Ok(evals) = output.get_int_evals() {
+                    // overwrite the outputs the lookup was laid out with
+                    let tampered: Tensor<Value<F>> = evals
+                        .iter()
+                        .map(|v| Value::known(i128_to_felt(v + 1)))
+                        .into();
+                    config
+                        .lookup_output
+                        .assign(Some(region), start, &tampered.into())?;
+                }
+            }
+            Ok(output)
+        }
+
+        fn required_lookups(&self) -> Vec<LookupOp> {
+            vec![self.0.clone()]
+        }
+
+        fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn tampered_neg(
+        _: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+        _: u32,
+        _: bool,
+        _: &[Node<F>],
+    ) -> Result<Box<dyn Op<F>>, Box<dyn Error>> {
+        Ok(Box::new(TamperedNeg(LookupOp::Div {
+            denom: crate::circuit::utils::F32(-1.0),
+        })))
+    }
+
+    /// Lays out 1l_neg like [ModelCircuit] does, without loading the model from the command line.
+    #[derive(Clone, Debug)]
+    struct NegCircuit(ModelCircuit<F>);
+
+    impl Circuit<F> for NegCircuit {
+        type Config = ModelConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let (model, _) = load("1l_neg");
+            let mut vars = ModelVars::new(
+                cs,
+                model.run_args.logrows as usize,
+                model.dummy_layout(&model.input_shapes()).unwrap(),
+                model.instance_shapes(),
+                model.visibility.clone(),
+                model.run_args.scale,
+            );
+            model.configure(cs, &mut vars).unwrap()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<F>,
+        ) -> Result<(), PlonkError> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    #[test]
+    fn mock_failures_are_mapped_to_nodes() {
+        register_op::<F>("Neg", tampered_neg);
+        let proved = std::panic::catch_unwind(|| {
+            let (model, data) = load("1l_neg");
+            let circuit = NegCircuit(ModelCircuit::new(&data, model).unwrap());
+            let public_inputs = circuit.0.prepare_public_inputs(&data).unwrap();
+            let logrows = circuit.0.model.run_args.logrows;
+            let prover = MockProver::run(logrows, &circuit, public_inputs).unwrap();
+            let verified = prover.verify();
+            (circuit, verified)
+        });
+        unregister_op::<F>("Neg");
+        let (circuit, verified) = proved.unwrap();
+
+        let neg = circuit
+            .0
+            .model
+            .nodes
+            .iter()
+            .find(|(_, n)| n.opkind.as_str() == "TAMPERED_NEG")
+            .map(|(idx, _)| *idx)
+            .unwrap();
+        let ranges = circuit.0.layout_ranges.lock().unwrap().clone();
+        let failures = verified.unwrap_err();
+        let lookups = failures
+            .iter()
+            .filter(|f| matches!(f, VerifyFailure::Lookup { .. }))
+            .collect_vec();
+        assert!(!lookups.is_empty());
+        // the failed lookups only occur in the rows the tampered negation was laid out at
+        for failure in lookups {
+            let descriptions = ranges.describe(failure);
+            assert_eq!(descriptions.len(), 1);
+            assert!(
+                descriptions[0].starts_with(&format!("node {} (TAMPERED_NEG) failed lookup", neg))
+            );
+        }
+    }
+}
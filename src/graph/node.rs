@@ -1,7 +1,7 @@
-use super::utilities::{node_output_shapes, scale_to_multiplier};
+use super::utilities::{node_output_shapes, rm_batch_dim, scale_to_multiplier};
 use crate::circuit::Op;
-use crate::graph::new_op_from_onnx;
 use crate::graph::GraphError;
+use crate::graph::{fuse_ops, new_op_from_onnx};
 use crate::tensor::TensorType;
 use anyhow::Result;
 use halo2_proofs::arithmetic::FieldExt;
@@ -12,13 +12,18 @@ use std::error::Error;
 use std::fmt;
 use tabled::Tabled;
 use tract_onnx;
+use tract_onnx::prelude::Graph;
 use tract_onnx::prelude::Node as OnnxNode;
+use tract_onnx::prelude::OutletId;
 use tract_onnx::prelude::TypedFact;
 use tract_onnx::prelude::TypedOp;
 
 /// Representation of an execution graph divided into execution 'buckets'.
 pub type NodeGraph<F> = BTreeMap<usize, Node<F>>;
 
+/// An output of a node, addressed as `(node index, output slot)`.
+pub type Outlet = (usize, usize);
+
 fn display_vector<T: fmt::Debug>(v: &Vec<T>) -> String {
     if !v.is_empty() {
         format!("{:?}", v)
@@ -35,9 +40,9 @@ fn display_opkind<F: FieldExt + TensorType>(v: &Box<dyn Op<F>>) -> String {
 /// # Arguments:
 /// * `opkind` - [OpKind] enum, i.e what operation this node represents.
 /// * `output_max` - The inferred maximum value that can appear in the output tensor given previous quantization choices.
-/// * `in_scale, out_scale` - The denominator in the fixed point representation. Tensors of differing scales should not be combined.
-/// * `in_dims, out_dims` - The shape of the activations which enter and leave the self.
-/// * `inputs` - The indices of other nodes that feed into this self.
+/// * `out_scales` - The denominator in the fixed point representation of each output. Tensors of differing scales should not be combined.
+/// * `out_dims` - The shape of each of the activations which leave the self.
+/// * `inputs` - The outputs of other nodes that feed into this self.
 /// * `const_value` - The constants potentially associated with this self.
 /// * `idx` - The node's unique identifier.
 /// * `bucket` - The execution bucket this node has been assigned to.
@@ -46,16 +51,17 @@ pub struct Node<F: FieldExt + TensorType> {
     /// [OpKind] enum, i.e what operation this node represents.
     #[tabled(display_with = "display_opkind")]
     pub opkind: Box<dyn Op<F>>,
-    /// The denominator in the fixed point representation for the node's output. Tensors of differing scales should not be combined.
-    pub out_scale: u32,
+    #[tabled(display_with = "display_vector")]
+    /// The denominator in the fixed point representation for each of the node's outputs. Tensors of differing scales should not be combined.
+    pub out_scales: Vec<u32>,
     // Usually there is a simple in and out shape of the node as an operator.  For example, an Affine node has three input_shapes (one for the input, weight, and bias),
     // but in_dim is [in], out_dim is [out]
     #[tabled(display_with = "display_vector")]
-    /// The indices of the node's inputs.
-    pub inputs: Vec<usize>,
+    /// The [Outlet]s the node's inputs are read from.
+    pub inputs: Vec<Outlet>,
     #[tabled(display_with = "display_vector")]
-    /// Dimensions of output.
-    pub out_dims: Vec<usize>,
+    /// Dimensions of each output.
+    pub out_dims: Vec<Vec<usize>>,
     /// The node's unique identifier.
     pub idx: usize,
 }
@@ -66,33 +72,68 @@ impl<F: FieldExt + TensorType> Node<F> {
     /// * `node` - [OnnxNode]
     /// * `other_nodes` - [BTreeMap] of other previously initialized [Node]s in the computational graph.
     /// * `scale` - The denominator in the fixed point representation. Tensors of differing scales should not be combined.
+    /// * `param_scale` - The denominator in the fixed point representation of the node's params (e.g weights), see [crate::graph::QuantizationProfile].
+    /// * `bits` - The number of bits used in lookup tables.
     /// * `idx` - The node's unique identifier.
     pub fn new(
-        mut node: OnnxNode<TypedFact, Box<dyn TypedOp>>,
+        node: OnnxNode<TypedFact, Box<dyn TypedOp>>,
         other_nodes: &mut BTreeMap<usize, Node<F>>,
         scale: u32,
+        param_scale: u32,
         public_params: bool,
+        bits: usize,
         idx: usize,
+        model: &Graph<TypedFact, Box<dyn TypedOp>>,
     ) -> Result<Self, Box<dyn Error>> {
         trace!("Create {:?}", node);
         trace!("Create op {:?}", node.op);
 
         // load the node inputs
-        let mut inputs = vec![];
-        for i in node.inputs.iter_mut() {
-            match other_nodes.get(&i.node) {
-                Some(n) => inputs.push(n.clone()),
-                None => return Err(Box::new(GraphError::MissingNode(i.node))),
+        let mut outlets = node.inputs.clone();
+        let mut inputs = Self::load_inputs(&outlets, other_nodes)?;
+
+        // decomposed ops (e.g activations) are collapsed into a single op on the pattern's input
+        let mut opkind = match fuse_ops(&node, model, other_nodes, public_params, bits)? {
+            Some((op, input)) => {
+                trace!("fusing node {} into {}", idx, op.as_str());
+                outlets = vec![input];
+                inputs = Self::load_inputs(&outlets, other_nodes)?;
+                op
             }
-        }
+            None => new_op_from_onnx(
+                idx,
+                param_scale,
+                public_params,
+                bits,
+                node.clone(),
+                &mut inputs,
+                model,
+            )?, // parses the op name
+        };
 
-        let mut opkind = new_op_from_onnx(idx, scale, public_params, node.clone(), &mut inputs)?; // parses the op name
+        // ops on a single sample (e.g convolutions) aren't applied to each sample of a batch, so reject a leading
+        // batch of several samples
+        if let Some(rank) = opkind.sample_rank() {
+            let dims = &other_nodes
+                .get(&outlets[0].node)
+                .ok_or(GraphError::MissingNode(outlets[0].node))?
+                .out_dims;
+            if let Some(dims) = dims.get(outlets[0].slot) {
+                if dims.len() > rank && dims[..dims.len() - rank].iter().any(|d| *d != 1) {
+                    return Err(Box::new(GraphError::UnsupportedBatch(
+                        idx,
+                        opkind.as_str().to_string(),
+                        dims.clone(),
+                    )));
+                }
+            }
+        }
 
         // if the op requires 3d inputs, we need to make sure the input shape is consistent with that
         if opkind.has_3d_input() {
-            let input_node = other_nodes.get_mut(&node.inputs[0].node).unwrap();
-            Self::format_3d_inputs(input_node)?;
-            inputs[0] = input_node.clone();
+            let input_node = other_nodes.get_mut(&outlets[0].node).unwrap();
+            Self::format_3d_inputs(input_node, outlets[0].slot)?;
+            inputs[0] = input_node.output(outlets[0].slot)?;
         };
 
         // creates a rescaled op if the inputs are not homogenous
@@ -101,73 +142,104 @@ impl<F: FieldExt + TensorType> Node<F> {
         }
 
         // rescale the inputs if necessary to get consistent fixed points
-        let in_scales: Vec<u32> = inputs.iter().map(|i| i.out_scale).collect();
+        let in_scales: Vec<u32> = inputs.iter().map(|i| i.out_scales[0]).collect();
         opkind = opkind.rescale(in_scales.clone(), scale);
-        let out_scale = match in_scales.len() {
-            0 => scale,
-            _ => opkind.out_scale(in_scales, scale),
+        let out_scales = match in_scales.len() {
+            // constants carry their own scale (e.g booleans are unscaled)
+            0 if opkind.is_constant() => opkind.out_scales(in_scales, scale),
+            0 => vec![scale; opkind.num_outputs()],
+            _ => opkind.out_scales(in_scales, scale),
         };
 
-        // get the output shape
-        let mut out_dims = {
-            let output_shapes = match node_output_shapes(&node) {
-                Ok(s) => Some(s),
-                _ => None,
-            };
-
-            if let Some([Some(v)]) = output_shapes.as_deref() {
-                v.to_vec()
-            } else {
-                // Turn  `outputs: [?,3,32,32,F32 >3/0]` into `vec![3,32,32]`  in two steps
-                node.outputs[0]
-                    .fact
-                    .shape
-                    .iter()
-                    // .filter_map(|x| x.concretize())
-                    .map(|x| x.to_i64().unwrap() as usize)
-                    .collect()
-            }
-        };
+        // get the output shapes
+        let output_shapes = node_output_shapes(&node).ok();
+        let out_dims = node
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| {
+                let dims = match output_shapes.as_ref().and_then(|s| s.get(i).cloned()) {
+                    Some(Some(v)) => v,
+                    // Turn  `outputs: [?,3,32,32,F32 >3/0]` into `vec![3,32,32]`  in two steps
+                    _ => output
+                        .fact
+                        .shape
+                        .iter()
+                        .map(|x| {
+                            // symbolic dims should have been concretised when loading the model inputs
+                            x.to_i64().map(|d| d as usize).map_err(|_| {
+                                GraphError::InvalidDims(idx, format!("symbolic dim {}", x))
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                };
+                // rm batch
+                Ok(rm_batch_dim(dims))
+            })
+            .collect::<Result<Vec<_>, GraphError>>()?;
 
-        // rm batch
-        if !out_dims.is_empty() && out_dims[0] == 1 && out_dims.len() > 1 {
-            out_dims = out_dims[1..].to_vec();
-        }
-        if out_dims.iter().product::<usize>() == 1 {
-            out_dims = vec![1];
-        };
+        let inputs = outlets
+            .iter()
+            .map(|o| (other_nodes[&o.node].idx, o.slot))
+            .collect();
 
         Ok(Node {
             idx,
             opkind,
-            inputs: inputs.iter().map(|i| i.idx).collect(),
+            inputs,
             out_dims,
-            out_scale,
+            out_scales,
         })
     }
 
+    /// Returns the node as seen by the consumers of its output `slot`, i.e with that output as its only output.
+    pub fn output(&self, slot: usize) -> Result<Self, GraphError> {
+        match (self.out_dims.get(slot), self.out_scales.get(slot)) {
+            (Some(dims), Some(scale)) => Ok(Node {
+                out_dims: vec![dims.clone()],
+                out_scales: vec![*scale],
+                ..self.clone()
+            }),
+            _ => Err(GraphError::MissingOutput(self.idx, slot)),
+        }
+    }
+
+    /// Loads the nodes, as seen by the node consuming them, which feed into the tract `outlets`.
+    fn load_inputs(
+        outlets: &[OutletId],
+        other_nodes: &BTreeMap<usize, Node<F>>,
+    ) -> Result<Vec<Self>, GraphError> {
+        outlets
+            .iter()
+            .map(|o| match other_nodes.get(&o.node) {
+                Some(n) => n.output(o.slot),
+                None => Err(GraphError::MissingNode(o.node)),
+            })
+            .collect()
+    }
+
     /// Ensures all inputs to a node have the same fixed point denominator.
     fn homogenize_input_scales(
         opkind: Box<dyn Op<F>>,
         inputs: Vec<Self>,
     ) -> Result<Box<dyn Op<F>>, Box<dyn Error>> {
         let mut multipliers = vec![1; inputs.len()];
-        let out_scales = inputs.windows(1).map(|w| w[0].out_scale).collect_vec();
+        let out_scales = inputs.windows(1).map(|w| w[0].out_scales[0]).collect_vec();
         if !out_scales.windows(2).all(|w| w[0] == w[1]) {
             let max_scale = out_scales.iter().max().unwrap();
             let _ = inputs
                 .iter()
                 .enumerate()
                 .map(|(idx, input)| {
-                    let scale_diff = max_scale - input.out_scale;
+                    let scale_diff = max_scale - input.out_scales[0];
                     if scale_diff > 0 {
                         let mult = scale_to_multiplier(scale_diff);
                         multipliers[idx] = mult as usize;
                         info!(
                             "------ scaled op node input {:?}: {:?} -> {:?}",
                             input.idx,
-                            input.out_scale,
-                            input.out_scale + scale_diff
+                            input.out_scales[0],
+                            input.out_scales[0] + scale_diff
                         );
                     }
                 })
@@ -186,45 +258,93 @@ impl<F: FieldExt + TensorType> Node<F> {
     }
 
     /// Formats 3d inputs if they have under or overspecified dims (casting 2D -> 3D and nD -> 3D)
-    fn format_3d_inputs(mut node: &mut Self) -> Result<(), Box<dyn Error>> {
+    fn format_3d_inputs(node: &mut Self, slot: usize) -> Result<(), Box<dyn Error>> {
+        let name = node.opkind.as_str().to_string();
+        let dims = match node.out_dims.get_mut(slot) {
+            Some(dims) => dims,
+            None => return Err(Box::new(GraphError::MissingOutput(node.idx, slot))),
+        };
         // input_nodes come in all shapes and sizes we gotta homogenize, especially for 2D (single channel images)
-        if node.out_dims.len() == 2 {
-            node = Self::pad_channel_input_node(node)?;
-        } else if node.out_dims.len() > 3 {
-            node = Self::rm_redundant_3d_channels(node)?;
+        if dims.len() == 2 {
+            Self::pad_channel_input_dims(dims);
+        } else if dims.len() > 3 {
+            Self::rm_redundant_3d_channels(dims, node.idx, &name)?;
         };
 
-        if node.out_dims.len() != 3 {
-            return Err(Box::new(GraphError::InvalidDims(
-                node.idx,
-                node.clone().opkind.as_str().to_string(),
-            )));
+        if dims.len() != 3 {
+            return Err(Box::new(GraphError::InvalidDims(node.idx, name)));
         }
         Ok(())
     }
 
-    /// Adds an extra channel dim to nodes that need it.
-    fn pad_channel_input_node(node: &mut Self) -> Result<&mut Self, Box<dyn Error>> {
-        let mut dims = vec![1];
-        dims.append(&mut node.out_dims);
-        node.out_dims = dims;
-        Ok(node)
+    /// Adds an extra channel dim to the dims of outputs that need it.
+    fn pad_channel_input_dims(dims: &mut Vec<usize>) {
+        dims.insert(0, 1);
     }
 
     /// Removes excess channels for an image
-    fn rm_redundant_3d_channels(node: &mut Self) -> Result<&mut Self, Box<dyn Error>> {
-        let dims = &node.out_dims;
-        let last_dims = &dims[dims.len() - 3..];
+    fn rm_redundant_3d_channels(
+        dims: &mut Vec<usize>,
+        idx: usize,
+        name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let last_dims = dims[dims.len() - 3..].to_vec();
         let channel_dims = &dims[..dims.len() - 3];
         for dim in channel_dims {
             if *dim != 1 {
-                return Err(Box::new(GraphError::InvalidDims(
-                    node.idx,
-                    node.opkind.as_str().to_string(),
-                )));
+                return Err(Box::new(GraphError::InvalidDims(idx, name.to_string())));
             }
         }
-        node.out_dims = last_dims.to_vec();
-        Ok(node)
+        *dims = last_dims;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::circuit::Tuple;
+    use halo2curves::pasta::Fp as F;
+
+    // a node with two outputs, e.g standing in for an LSTM's Y and Y_h
+    fn tuple() -> Node<F> {
+        Node {
+            opkind: Box::new(Tuple { len: 2 }),
+            out_scales: vec![7, 14],
+            inputs: vec![(0, 0), (1, 0)],
+            out_dims: vec![vec![3, 2], vec![2]],
+            idx: 2,
+        }
+    }
+
+    #[test]
+    fn output_slots() {
+        let node = tuple();
+        let y = node.output(0).unwrap();
+        assert_eq!(
+            (y.idx, y.out_dims, y.out_scales),
+            (2, vec![vec![3, 2]], vec![7])
+        );
+        let h = node.output(1).unwrap();
+        assert_eq!(
+            (h.idx, h.out_dims, h.out_scales),
+            (2, vec![vec![2]], vec![14])
+        );
+        assert!(matches!(
+            node.output(2),
+            Err(GraphError::MissingOutput(2, 2))
+        ));
+    }
+
+    #[test]
+    fn load_inputs_from_output_slots() {
+        let mut other_nodes = BTreeMap::new();
+        other_nodes.insert(5, tuple());
+        let inputs =
+            Node::load_inputs(&[OutletId::new(5, 1), OutletId::new(5, 0)], &other_nodes).unwrap();
+        assert_eq!(inputs[0].out_dims, vec![vec![2]]);
+        assert_eq!(inputs[1].out_dims, vec![vec![3, 2]]);
+        assert!(Node::load_inputs(&[OutletId::new(5, 2)], &other_nodes).is_err());
     }
 }
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+/// The scales the params (e.g weights) of a model's nodes are quantized at, as picked by
+/// calibrating the model on sample inputs (see [crate::graph::Model::calibrate]).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantizationProfile {
+    /// Param scales keyed by node index. Nodes without an entry use the global scale of the [crate::commands::RunArgs].
+    /// The nodes unrolled from a scan (e.g an LSTM) are keyed by their own indices, so are profiled like any other.
+    pub scales: BTreeMap<usize, u32>,
+}
+
+impl QuantizationProfile {
+    /// Loads a profile from a .json file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Saves the profile to a .json file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer(&File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Returns the scale the params of node `idx` should be quantized at, falling back to `global_scale`.
+    pub fn scale(&self, idx: usize, global_scale: u32) -> u32 {
+        *self.scales.get(&idx).unwrap_or(&global_scale)
+    }
+}
@@ -0,0 +1,130 @@
+use halo2_proofs::dev::{FailureLocation, VerifyFailure};
+use std::ops::Range;
+
+/// The (linear) offsets an op was laid out at in the `"model"` region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutRange {
+    /// The index of the node the op belongs to, `None` for the ops laid out on the model outputs (e.g packing, range checks).
+    pub node: Option<usize>,
+    /// The name of the op.
+    pub op: String,
+    /// The offsets the op's cells were assigned at.
+    pub offsets: Range<usize>,
+}
+
+impl LayoutRange {
+    /// Returns the row of the op (relative to its first offset) laid out at region row `row`, if any.
+    pub fn row(&self, row: usize, col_size: usize) -> Option<usize> {
+        if col_size == 0 || row >= col_size || self.offsets.is_empty() {
+            return None;
+        }
+        // offsets wrap around the columns, so find the first offset of the range in `row`
+        let start = self.offsets.start;
+        let first = start + (row + col_size - start % col_size) % col_size;
+        if first < self.offsets.end {
+            Some(first - start)
+        } else {
+            None
+        }
+    }
+}
+
+/// The offsets each op of a model was laid out at, as recorded by [crate::graph::Model::layout].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayoutRanges {
+    /// The number of rows in each column, i.e the period at which offsets wrap around to the next column.
+    pub col_size: usize,
+    /// The range of each op, in layout order.
+    pub ranges: Vec<LayoutRange>,
+}
+
+impl LayoutRanges {
+    /// Returns the ops laid out at region row `row`, along with their row relative to their first offset.
+    /// Ops spread over several columns can share rows, so more than one op may be returned.
+    pub fn ops_at_row(&self, row: usize) -> Vec<(&LayoutRange, usize)> {
+        self.ranges
+            .iter()
+            .filter_map(|r| r.row(row, self.col_size).map(|op_row| (r, op_row)))
+            .collect()
+    }
+
+    /// Translates a [VerifyFailure] of the mock prover into the ops it occurred in,
+    /// e.g "node 17 (Conv) failed constraint DOT at its row 312".
+    pub fn describe(&self, failure: &VerifyFailure) -> Vec<String> {
+        let (what, row) = match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => (format!("constraint {}", constraint), location_row(location)),
+            VerifyFailure::CellNotAssigned { gate, offset, .. } => (
+                format!("gate {} (unassigned cell)", gate),
+                usize::try_from(*offset).ok(),
+            ),
+            VerifyFailure::Lookup { name, location, .. } => {
+                (format!("lookup {}", name), location_row(location))
+            }
+            VerifyFailure::Permutation { location, .. } => {
+                ("copy constraint".to_string(), location_row(location))
+            }
+            _ => return vec![],
+        };
+        let row = match row {
+            Some(row) => row,
+            None => return vec![],
+        };
+        self.ops_at_row(row)
+            .into_iter()
+            .map(|(r, op_row)| {
+                let op = match r.node {
+                    Some(idx) => format!("node {} ({})", idx, r.op),
+                    None => format!("model output ({})", r.op),
+                };
+                format!("{} failed {} at its row {}", op, what, op_row)
+            })
+            .collect()
+    }
+}
+
+/// Returns the row a failure occurred at, if it occurred in a region.
+fn location_row(location: &FailureLocation) -> Option<usize> {
+    match location {
+        FailureLocation::InRegion { offset, .. } => Some(*offset),
+        FailureLocation::OutsideRegion { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn ops_at_row() {
+        let range = |node, offsets| LayoutRange {
+            node,
+            op: "op".to_string(),
+            offsets,
+        };
+        let ranges = LayoutRanges {
+            col_size: 10,
+            ranges: vec![
+                range(Some(0), 0..4),
+                range(Some(1), 4..17),
+                range(None, 17..19),
+            ],
+        };
+        let at = |row| {
+            ranges
+                .ops_at_row(row)
+                .into_iter()
+                .map(|(r, op_row)| (r.node, op_row))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(at(2), vec![(Some(0), 2), (Some(1), 8)]);
+        assert_eq!(at(5), vec![(Some(1), 1)]);
+        assert_eq!(at(7), vec![(Some(1), 3), (None, 0)]);
+        assert_eq!(at(9), vec![(Some(1), 5)]);
+        assert_eq!(at(10), vec![]);
+    }
+}
@@ -0,0 +1,90 @@
+use super::node::Node;
+use crate::circuit::Op;
+use crate::tensor::TensorType;
+use halo2curves::FieldExt;
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{PoisonError, RwLock};
+use tract_onnx::prelude::{Node as OnnxNode, TypedFact, TypedOp};
+
+/// Constructs an op for an onnx node the graph loader does not support natively.
+/// # Arguments:
+/// * `node` - The onnx node (as loaded by tract) to construct the op for.
+/// * `scale` - The denominator in the fixed point representation the op's params (e.g weights) are quantized at.
+/// * `public_params` - Whether the op's params (e.g constants) should be public.
+/// * `inputs` - The [Node]s feeding into the op.
+pub type OpConstructor<F> = fn(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    scale: u32,
+    public_params: bool,
+    inputs: &[Node<F>],
+) -> Result<Box<dyn Op<F>>, Box<dyn Error>>;
+
+/// Registered constructors keyed by the field they are defined over and the op name they are registered for.
+static REGISTRY: RwLock<BTreeMap<(TypeId, String), Box<dyn Any + Send + Sync>>> =
+    RwLock::new(BTreeMap::new());
+
+/// Registers a constructor for the op `name`, i.e the name of the op as loaded by tract (e.g `onnx.Erf`).
+/// Registered ops are consulted before the ops ezkl supports natively, so can also be used to
+/// override those. Returns the previously registered constructor if there was one.
+///
+/// The op's [Op::required_lookups] are configured like those of any other op.
+pub fn register_op<F: FieldExt + TensorType>(
+    name: &str,
+    constructor: OpConstructor<F>,
+) -> Option<OpConstructor<F>> {
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert((TypeId::of::<F>(), name.to_string()), Box::new(constructor))
+        .and_then(|c| c.downcast_ref::<OpConstructor<F>>().copied())
+}
+
+/// Removes the constructor registered for the op `name`, returning it if there was one.
+pub fn unregister_op<F: FieldExt + TensorType>(name: &str) -> Option<OpConstructor<F>> {
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&(TypeId::of::<F>(), name.to_string()))
+        .and_then(|c| c.downcast_ref::<OpConstructor<F>>().copied())
+}
+
+/// Returns the constructor registered for the op `name`, if there is one.
+pub fn registered_op<F: FieldExt + TensorType>(name: &str) -> Option<OpConstructor<F>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&(TypeId::of::<F>(), name.to_string()))
+        .and_then(|c| c.downcast_ref::<OpConstructor<F>>().copied())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::circuit::lookup::LookupOp;
+    use halo2curves::pasta::Fp as F;
+
+    fn erf(
+        _: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+        scale: u32,
+        _: bool,
+        _: &[Node<F>],
+    ) -> Result<Box<dyn Op<F>>, Box<dyn Error>> {
+        Ok(Box::new(LookupOp::Erf {
+            scales: (scale as usize, scale as usize),
+        }))
+    }
+
+    #[test]
+    fn register_and_unregister() {
+        assert!(registered_op::<F>("TestErf").is_none());
+        assert!(register_op::<F>("TestErf", erf).is_none());
+        assert!(registered_op::<F>("TestErf").is_some());
+        // registrations are per field
+        assert!(registered_op::<halo2curves::bn256::Fr>("TestErf").is_none());
+        assert!(unregister_op::<F>("TestErf").is_some());
+        assert!(registered_op::<F>("TestErf").is_none());
+    }
+}
@@ -1,6 +1,7 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use super::{node::*, GraphError};
+use super::{node::*, registered_op, GraphError, QuantizationProfile};
 use crate::circuit::hybrid::HybridOp;
 use crate::circuit::lookup::LookupOp;
 use crate::circuit::poly::PolyOp;
@@ -9,11 +10,17 @@ use crate::tensor::{Tensor, TensorError, TensorType, ValTensor};
 use anyhow::Result;
 use halo2_proofs::circuit::Value;
 use halo2curves::FieldExt;
-use log::warn;
-use tract_onnx::prelude::{DatumType, Node as OnnxNode, TypedFact, TypedOp};
+use log::{debug, warn};
+use tract_onnx::prelude::{
+    DatumType, Graph, IntoArcTensor, Node as OnnxNode, OutletId, TypedFact, TypedOp,
+};
+use tract_onnx::tract_core::ops::array::{ConcatSlice, Gather, Slice, TypedConcat};
 use tract_onnx::tract_core::ops::binary::UnaryOp;
-use tract_onnx::tract_core::ops::matmul::MatMulUnary;
-use tract_onnx::tract_core::ops::nn::LeakyRelu;
+use tract_onnx::tract_core::ops::cast::Cast;
+use tract_onnx::tract_core::ops::konst::Const;
+use tract_onnx::tract_core::ops::matmul::{MatMul, MatMulUnary};
+use tract_onnx::tract_core::ops::nn::{LeakyRelu, Reduce, Reducer, Softmax};
+use tract_onnx::tract_core::ops::scan::{InputMapping, Scan, StateInitializer};
 use tract_onnx::tract_hir::internal::AxisOp;
 use tract_onnx::tract_hir::ops::cnn::ConvUnary;
 use tract_onnx::tract_hir::ops::element_wise::ElementWiseOp;
@@ -83,6 +90,7 @@ fn extract_tensor_value<F: FieldExt + TensorType>(
     };
 
     let const_value: Tensor<i128>;
+    let mut scale = scale;
     match dt {
         DatumType::F32 => {
             let vec = input.as_slice::<f32>()?.to_vec();
@@ -95,6 +103,13 @@ fn extract_tensor_value<F: FieldExt + TensorType>(
             let cast: Vec<i128> = vec.iter().map(|x| *x as i128).collect();
             const_value = Tensor::<i128>::new(Some(&cast), &dims)?;
         }
+        DatumType::Bool => {
+            // booleans are plain integers, like the outputs of comparisons
+            let vec = input.as_slice::<bool>()?.to_vec();
+            let cast: Vec<i128> = vec.iter().map(|x| *x as i128).collect();
+            const_value = Tensor::<i128>::new(Some(&cast), &dims)?;
+            scale = 0;
+        }
         _ => todo!(),
     }
 
@@ -111,6 +126,20 @@ fn extract_tensor_value<F: FieldExt + TensorType>(
     Ok(value)
 }
 
+/// Removes the leading unit dims (e.g the batch dim) of a constant operand which has a higher rank than
+/// the node's input, so that broadcasting the two does not reintroduce the dims ezkl removes from node outputs.
+fn match_input_rank<F: FieldExt + TensorType>(
+    value: &mut ValTensor<F>,
+    rank: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dims = value.dims().to_vec();
+    let leading = dims.len().saturating_sub(rank);
+    if leading > 0 && dims[..leading].iter().all(|d| *d == 1) {
+        value.reshape(&dims[leading..])?;
+    }
+    Ok(())
+}
+
 /// Extracts a unary op from an onnx node.
 fn load_unary_op(
     op: &dyn tract_onnx::prelude::Op,
@@ -143,17 +172,991 @@ fn load_axis_op(
     Ok(op.clone())
 }
 
+/// Returns 1 if ezkl removes the batch dimension of the node's output (see [Node::new]), else 0.
+/// Axis attributes of the onnx node need to be shifted by this amount.
+fn batch_offset(node: &OnnxNode<TypedFact, Box<dyn TypedOp>>) -> usize {
+    match node_output_shapes(node).as_deref() {
+        Ok([Some(dims), ..]) if dims.len() > 1 && dims[0] == 1 => 1,
+        _ => 0,
+    }
+}
+
+/// Shifts an onnx axis attribute to account for a removed batch dimension.
+fn shift_axis(axis: usize, offset: usize, name: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    if axis < offset {
+        return Err(Box::new(GraphError::MisformedParams(format!(
+            "ezkl currently does not support {} along the batch dimension",
+            name
+        ))));
+    }
+    Ok(axis - offset)
+}
+
+/// Tolerance used when matching the constants of decomposed activations.
+const ACTIVATION_TOL: f32 = 1e-4;
+
+/// Returns the scalar constant of a unary op (e.g `MulUnary`) if `node` is an op named `name`.
+fn scalar_unary_const(node: &OnnxNode<TypedFact, Box<dyn TypedOp>>, name: &str) -> Option<f32> {
+    if node.op().name() != name {
+        return None;
+    }
+    let op = node.op().downcast_ref::<UnaryOp>()?;
+    if op.a.shape().iter().product::<usize>() != 1 {
+        return None;
+    }
+    op.a.as_slice::<f32>().ok()?.first().copied()
+}
+
+/// Returns true if `node` is a unary op named `name` whose scalar constant is `value`.
+fn is_unary_const(node: &OnnxNode<TypedFact, Box<dyn TypedOp>>, name: &str, value: f32) -> bool {
+    scalar_unary_const(node, name)
+        .map(|c| (c - value).abs() < ACTIVATION_TOL)
+        .unwrap_or(false)
+}
+
+/// Matches tract's decomposition of HardSigmoid, `min(max(alpha * x + beta, 0), 1)` (the clamps can come
+/// in either order), ending at `node`. Returns `alpha`, `beta` and the outlet of `x`.
+fn match_hard_sigmoid(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<(f32, f32, OutletId)> {
+    let input_of =
+        |n: &OnnxNode<TypedFact, Box<dyn TypedOp>>| n.inputs.first().map(|o| model.node(o.node));
+    let inner = input_of(node)?;
+    let clamped = if is_unary_const(node, "MinUnary", 1.0) && is_unary_const(inner, "MaxUnary", 0.0)
+        || is_unary_const(node, "MaxUnary", 0.0) && is_unary_const(inner, "MinUnary", 1.0)
+    {
+        input_of(inner)?
+    } else {
+        return None;
+    };
+    let beta = scalar_unary_const(clamped, "AddUnary")?;
+    let scaled = input_of(clamped)?;
+    let alpha = scalar_unary_const(scaled, "MulUnary")?;
+    Some((alpha, beta, *scaled.inputs.first()?))
+}
+
+/// Matches tract's decomposition of Clip (e.g ReLU6), `min(max(x, min), max)` with scalar bounds (the clamps can come
+/// in either order), ending at `node`. Returns the bounds and the outlet of `x`.
+fn match_clip(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<(f32, f32, OutletId)> {
+    let inner = model.node(node.inputs.first()?.node);
+    let (min, max) = match (
+        scalar_unary_const(node, "MinUnary"),
+        scalar_unary_const(node, "MaxUnary"),
+    ) {
+        (Some(max), _) => (scalar_unary_const(inner, "MaxUnary")?, max),
+        (_, Some(min)) => (min, scalar_unary_const(inner, "MinUnary")?),
+        _ => return None,
+    };
+    if min > max {
+        return None;
+    }
+    Some((min, max, *inner.inputs.first()?))
+}
+
+/// Builds a clip of `input` to `[min, max]`. Bounds within the range of the lookup tables at the input scale give a
+/// single lookup, otherwise the clip is laid out as comparisons against the quantized bounds, where an unbounded
+/// side (e.g the `inf` of a one-sided clamp) is left out.
+fn fuse_clip<F: FieldExt + TensorType>(
+    min: f32,
+    max: f32,
+    input: &Node<F>,
+    bits: usize,
+) -> Result<Box<dyn crate::circuit::Op<F>>, Box<dyn std::error::Error>> {
+    let scale = input.out_scales[0];
+    let multiplier = scale_to_multiplier(scale);
+    let table_range = -(2_f32.powi(bits as i32 - 1))..=(2_f32.powi(bits as i32 - 1) - 1.0);
+    if table_range.contains(&(min * multiplier)) && table_range.contains(&(max * multiplier)) {
+        return Ok(Box::new(LookupOp::Clip {
+            scales: (1, 1),
+            min: crate::circuit::utils::F32(min),
+            max: crate::circuit::utils::F32(max),
+        }));
+    }
+
+    let quantize_bound = |bound: f32| -> Option<ValTensor<F>> {
+        let bound = (bound * multiplier).round();
+        if !bound.is_finite() {
+            return None;
+        }
+        let mut value: ValTensor<F> = Tensor::from(
+            [crate::tensor::ValType::Constant(i128_to_felt::<F>(
+                bound as i128,
+            ))]
+            .into_iter(),
+        )
+        .into();
+        value.set_scale(scale);
+        Some(value)
+    };
+    Ok(match (quantize_bound(min), quantize_bound(max)) {
+        (Some(min), Some(max)) => Box::new(HybridOp::Clip { min, max }),
+        (Some(min), None) => Box::new(HybridOp::EltWiseMax { a: Some(min) }),
+        (None, Some(max)) => Box::new(HybridOp::EltWiseMin { a: Some(max) }),
+        (None, None) => Box::new(PolyOp::Identity),
+    })
+}
+
+/// Matches tract's decomposition of GELU, `0.5 * x * (1 + erf(x / sqrt(2)))`, ending at `node`.
+/// Returns the outlet of `x`.
+fn match_gelu(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<OutletId> {
+    if !is_unary_const(node, "MulUnary", 0.5) {
+        return None;
+    }
+    let mul = model.node(node.inputs.first()?.node);
+    if mul.op().name() != "Mul" || mul.inputs.len() != 2 {
+        return None;
+    }
+    [(0, 1), (1, 0)].into_iter().find_map(|(x, other)| {
+        let add = model.node(mul.inputs[other].node);
+        if !is_unary_const(add, "AddUnary", 1.0) {
+            return None;
+        }
+        let erf = model.node(add.inputs.first()?.node);
+        if erf.op().name() != "onnx.Erf" {
+            return None;
+        }
+        let scaled = model.node(erf.inputs.first()?.node);
+        (is_unary_const(scaled, "MulUnary", std::f32::consts::FRAC_1_SQRT_2)
+            && scaled.inputs.first() == Some(&mul.inputs[x]))
+        .then_some(mul.inputs[x])
+    })
+}
+
+/// Matches the patterns tract decomposes GELU, SiLU, HardSigmoid and HardSwish into, and which end at `node`.
+/// Returns the equivalent single-table [LookupOp] and the index of the node feeding the activation,
+/// such that the intermediate nodes of the pattern can be pruned from the graph.
+fn fuse_activation(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<(LookupOp, OutletId)> {
+    if let Some(x) = match_gelu(node, model) {
+        return Some((LookupOp::Gelu { scales: (1, 1) }, x));
+    }
+    if let Some((alpha, beta, x)) = match_hard_sigmoid(node, model) {
+        return Some((
+            LookupOp::HardSigmoid {
+                scales: (1, 1),
+                alpha: crate::circuit::utils::F32(alpha),
+                beta: crate::circuit::utils::F32(beta),
+            },
+            x,
+        ));
+    }
+    // x * sigmoid(x) and x * hard_sigmoid(x)
+    if node.op().name() != "Mul" || node.inputs.len() != 2 {
+        return None;
+    }
+    [(0, 1), (1, 0)].into_iter().find_map(|(x, other)| {
+        let gate = model.node(node.inputs[other].node);
+        if gate.op().name() == "Sigmoid" && gate.inputs.first() == Some(&node.inputs[x]) {
+            return Some((LookupOp::Silu { scales: (1, 1) }, node.inputs[x]));
+        }
+        match match_hard_sigmoid(gate, model) {
+            Some((alpha, beta, input))
+                if input == node.inputs[x]
+                    && (alpha - 1.0 / 6.0).abs() < ACTIVATION_TOL
+                    && (beta - 0.5).abs() < ACTIVATION_TOL =>
+            {
+                Some((LookupOp::HardSwish { scales: (1, 1) }, node.inputs[x]))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// The parameters of a layer norm matched by [match_layer_norm].
+struct LayerNormPattern {
+    input: OutletId,
+    axes: Vec<usize>,
+    epsilon: f32,
+    gamma: Option<Arc<tract_onnx::prelude::Tensor>>,
+    beta: Option<Arc<tract_onnx::prelude::Tensor>>,
+}
+
+/// Returns the reduction axes of `node` if it is a `Reduce<Mean>` of `input`.
+fn match_mean_of(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    input: OutletId,
+) -> Option<Vec<usize>> {
+    if node.op().name() != "Reduce<Mean>" || node.inputs.first() != Some(&input) {
+        return None;
+    }
+    let op = node.op().downcast_ref::<Reduce>()?;
+    Some(op.axes.to_vec())
+}
+
+/// Matches tract's decomposition of LayerNormalization,
+/// `(x - mean(x)) / sqrt(mean((x - mean(x))^2) + epsilon) * gamma + beta`, ending at `node`.
+/// The division can come as a `Div` by a `Sqrt` or as a `Mul` by a `Rsqrt` or by the `Recip` of a `Sqrt`.
+fn match_layer_norm(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<LayerNormPattern> {
+    let input_of =
+        |n: &OnnxNode<TypedFact, Box<dyn TypedOp>>| n.inputs.first().map(|o| model.node(o.node));
+
+    // optional affine transform
+    let mut core = node;
+    let mut beta = None;
+    let mut gamma = None;
+    if core.op().name() == "AddUnary" {
+        beta = Some(core.op().downcast_ref::<UnaryOp>()?.a.clone());
+        core = input_of(core)?;
+    }
+    if core.op().name() == "MulUnary" {
+        gamma = Some(core.op().downcast_ref::<UnaryOp>()?.a.clone());
+        core = input_of(core)?;
+    }
+
+    // the centred input and the node holding `var + epsilon`
+    if core.inputs.len() != 2 {
+        return None;
+    }
+    let (diff, var_eps) = match core.op().name().as_ref() {
+        "Div" => {
+            let sqrt = model.node(core.inputs[1].node);
+            (sqrt.op().name() == "Sqrt").then_some((core.inputs[0], input_of(sqrt)?))?
+        }
+        "Mul" => [(0, 1), (1, 0)].into_iter().find_map(|(d, other)| {
+            let inv_std = model.node(core.inputs[other].node);
+            match inv_std.op().name().as_ref() {
+                "Rsqrt" => Some((core.inputs[d], input_of(inv_std)?)),
+                "Recip" => {
+                    let sqrt = input_of(inv_std)?;
+                    (sqrt.op().name() == "Sqrt").then_some((core.inputs[d], input_of(sqrt)?))
+                }
+                _ => None,
+            }
+        })?,
+        _ => return None,
+    };
+
+    // the variance
+    let epsilon = scalar_unary_const(var_eps, "AddUnary")?;
+    let var = input_of(var_eps)?;
+    let sq = model.node(var.inputs.first()?.node);
+    let is_square = match sq.op().name().as_ref() {
+        "Square" => sq.inputs.first() == Some(&diff),
+        "PowUnary" => is_unary_const(sq, "PowUnary", 2.0) && sq.inputs.first() == Some(&diff),
+        "Mul" => sq.inputs.len() == 2 && sq.inputs.iter().all(|i| *i == diff),
+        _ => false,
+    };
+    if !is_square {
+        return None;
+    }
+    let var_axes = match_mean_of(var, OutletId::new(sq.id, 0))?;
+
+    // the centring
+    let sub = model.node(diff.node);
+    if sub.op().name() != "Sub" || sub.inputs.len() != 2 {
+        return None;
+    }
+    let input = sub.inputs[0];
+    let mean_axes = match_mean_of(model.node(sub.inputs[1].node), input)?;
+    if mean_axes != var_axes {
+        return None;
+    }
+
+    Some(LayerNormPattern {
+        input,
+        axes: mean_axes,
+        epsilon,
+        gamma,
+        beta,
+    })
+}
+
+/// Collapses a layer norm matched by [match_layer_norm] into a [HybridOp::LayerNorm] on the node `input`.
+fn fuse_layer_norm<F: FieldExt + TensorType>(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    pattern: LayerNormPattern,
+    input: &Node<F>,
+    public_params: bool,
+) -> Result<HybridOp<F>, Box<dyn std::error::Error>> {
+    let offset = batch_offset(node);
+    let mut axes = pattern
+        .axes
+        .iter()
+        .map(|ax| shift_axis(*ax, offset, "layer norm"))
+        .collect::<Result<Vec<_>, _>>()?;
+    axes.sort();
+    if axes.iter().any(|ax| *ax >= input.out_dims[0].len()) {
+        return Err(Box::new(GraphError::MisformedParams(
+            "layer norm axes exceed the input rank".to_string(),
+        )));
+    }
+    let num_inputs = axes
+        .iter()
+        .map(|ax| input.out_dims[0][*ax])
+        .product::<usize>();
+
+    // gamma is at the input scale and beta at the scale of the normalised and weighted input
+    let one = tract_onnx::prelude::tensor0(1f32).into_arc_tensor();
+    let zero = tract_onnx::prelude::tensor0(0f32).into_arc_tensor();
+    let gamma = extract_tensor_value(
+        pattern.gamma.unwrap_or(one),
+        input.out_scales[0],
+        public_params,
+    )?;
+    let beta = extract_tensor_value(
+        pattern.beta.unwrap_or(zero),
+        2 * input.out_scales[0],
+        public_params,
+    )?;
+    if [&gamma, &beta]
+        .iter()
+        .any(|p| p.len() != 1 && p.len() != num_inputs)
+    {
+        return Err(Box::new(GraphError::MisformedParams(
+            "layer norm gamma and beta must match the normalised dims".to_string(),
+        )));
+    }
+
+    Ok(HybridOp::LayerNorm {
+        scale: 1,
+        epsilon: crate::circuit::utils::F32(pattern.epsilon),
+        axes,
+        num_inputs,
+        gamma,
+        beta,
+    })
+}
+
+/// Returns true if `param` holds one value per channel of a `[C, H, W]` input, laid out as `[C, 1, 1]`
+/// (with or without leading unit dims).
+fn is_per_channel(param: &tract_onnx::prelude::Tensor, channels: usize) -> bool {
+    let dims = param.shape();
+    dims.len() >= 3
+        && dims[dims.len() - 3] == channels
+        && dims[..dims.len() - 3]
+            .iter()
+            .chain(&dims[dims.len() - 2..])
+            .all(|d| *d == 1)
+}
+
+/// Collapses a layer norm matched by [match_layer_norm] into a [HybridOp::InstanceNorm2d] on the node `input`,
+/// if it normalises each channel of a `[C, H, W]` input over its spatial dims and `gamma` and `beta` are per
+/// channel, as in tract's decomposition of InstanceNormalization. Returns `None` otherwise.
+fn fuse_instance_norm<F: FieldExt + TensorType>(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    pattern: &LayerNormPattern,
+    input: &Node<F>,
+    public_params: bool,
+) -> Result<Option<HybridOp<F>>, Box<dyn std::error::Error>> {
+    let dims = &input.out_dims[0];
+    let offset = batch_offset(node);
+    let mut axes = pattern
+        .axes
+        .iter()
+        .map(|ax| ax.checked_sub(offset))
+        .collect::<Option<Vec<_>>>();
+    if let Some(axes) = axes.as_mut() {
+        axes.sort();
+    }
+    if dims.len() != 3 || axes != Some(vec![1, 2]) {
+        return Ok(None);
+    }
+    let channels = dims[0];
+    if [&pattern.gamma, &pattern.beta]
+        .iter()
+        .any(|p| matches!(p, Some(p) if !is_per_channel(p, channels)))
+    {
+        return Ok(None);
+    }
+
+    // gamma is at the input scale and beta at the scale of the normalised and weighted input
+    let param = |p: &Option<Arc<tract_onnx::prelude::Tensor>>, default: f32, scale: u32| {
+        let p = p.clone().unwrap_or_else(|| {
+            tract_onnx::prelude::tensor1(&vec![default; channels]).into_arc_tensor()
+        });
+        extract_tensor_value(p, scale, public_params)
+    };
+    Ok(Some(HybridOp::InstanceNorm2d {
+        scale: 1,
+        epsilon: crate::circuit::utils::F32(pattern.epsilon),
+        num_inputs: dims[1] * dims[2],
+        gamma: param(&pattern.gamma, 1.0, input.out_scales[0])?,
+        beta: param(&pattern.beta, 0.0, 2 * input.out_scales[0])?,
+    }))
+}
+
+/// Matches tract's decomposition of inference mode BatchNormalization, `x * slope + intercept`, ending at `node`.
+/// tract folds `gamma`, `beta` and the running statistics into the constant `slope` and `intercept`.
+/// Returns `slope`, `intercept` and the outlet of `x`.
+fn match_batch_norm(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<(
+    Arc<tract_onnx::prelude::Tensor>,
+    Arc<tract_onnx::prelude::Tensor>,
+    OutletId,
+)> {
+    if node.op().name() != "AddUnary" {
+        return None;
+    }
+    let intercept = node.op().downcast_ref::<UnaryOp>()?.a.clone();
+    let mul = model.node(node.inputs.first()?.node);
+    if mul.op().name() != "MulUnary" {
+        return None;
+    }
+    let slope = mul.op().downcast_ref::<UnaryOp>()?.a.clone();
+    Some((slope, intercept, *mul.inputs.first()?))
+}
+
+/// Collapses a batch norm matched by [match_batch_norm] into a [HybridOp::BatchNorm] on the node `input`,
+/// if `slope` and `intercept` are per channel constants of a `[C, H, W]` input. Returns `None` otherwise.
+fn fuse_batch_norm<F: FieldExt + TensorType>(
+    slope: Arc<tract_onnx::prelude::Tensor>,
+    intercept: Arc<tract_onnx::prelude::Tensor>,
+    input: &Node<F>,
+    public_params: bool,
+) -> Result<Option<HybridOp<F>>, Box<dyn std::error::Error>> {
+    let dims = &input.out_dims[0];
+    if dims.len() != 3 || !is_per_channel(&slope, dims[0]) || !is_per_channel(&intercept, dims[0]) {
+        return Ok(None);
+    }
+    let scale = input.out_scales[0];
+    // the running statistics are folded into the slope, so the op's own mean and variance are 0 and 1
+    let mean = tract_onnx::prelude::tensor1(&vec![0f32; dims[0]]).into_arc_tensor();
+    let var = tract_onnx::prelude::tensor1(&vec![1f32; dims[0]]).into_arc_tensor();
+    Ok(Some(HybridOp::BatchNorm {
+        scale: 1,
+        epsilon: crate::circuit::utils::F32(0.0),
+        gamma: extract_tensor_value(slope, scale, public_params)?,
+        beta: extract_tensor_value(intercept, 2 * scale, public_params)?,
+        mean: extract_tensor_value(mean, scale, public_params)?,
+        var: extract_tensor_value(var, scale, public_params)?,
+    }))
+}
+
+/// Checks that a softmax node is applied along the last axis, the only one we support.
+fn check_softmax_axes(
+    idx: usize,
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let softmax_op: &Softmax = match node.op().downcast_ref::<Softmax>() {
+        Some(b) => b,
+        None => {
+            return Err(Box::new(GraphError::OpMismatch(idx, "softmax".to_string())));
+        }
+    };
+
+    let rank = node.outputs[0].fact.shape.rank();
+    if softmax_op.axes.len() != 1 || softmax_op.axes[0] != rank - 1 {
+        return Err(Box::new(GraphError::MisformedParams(
+            "softmax is only supported along the last axis".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// Additive attention mask values at or below this are treated as masking out a position.
+const MASK_THRESHOLD: f32 = -1e4;
+
+/// Matches a softmax of `x + mask` ending at `node`, where the constant `mask` only holds 0s (kept positions)
+/// and large negative values (masked positions), as exported for attention masks.
+/// Returns the outlet of `x` and the mask as 0s (masked) and 1s (kept).
+fn match_masked_softmax(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+) -> Option<(OutletId, Tensor<i128>)> {
+    if node.op().name() != "Softmax" {
+        return None;
+    }
+    let add = model.node(node.inputs.first()?.node);
+    if add.op().name() != "AddUnary" {
+        return None;
+    }
+    let mask = &add.op().downcast_ref::<UnaryOp>()?.a;
+    let values = mask.as_slice::<f32>().ok()?;
+    if !values.iter().all(|v| *v == 0.0 || *v <= MASK_THRESHOLD) {
+        return None;
+    }
+    let keep = values
+        .iter()
+        .map(|v| if *v == 0.0 { 1 } else { 0 })
+        .collect::<Vec<i128>>();
+    let mut dims = mask.shape().to_vec();
+    if dims.is_empty() {
+        dims.push(1);
+    }
+    Some((*add.inputs.first()?, Tensor::new(Some(&keep), &dims).ok()?))
+}
+
+/// Matches the patterns tract decomposes ops without a native ezkl counterpart into, and which end at `node`.
+/// Returns the equivalent fused op and the index of the node it is applied to, such that the intermediate
+/// nodes of the pattern can be pruned from the graph.
+pub fn fuse_ops<F: FieldExt + TensorType>(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+    other_nodes: &BTreeMap<usize, Node<F>>,
+    public_params: bool,
+    bits: usize,
+) -> Result<Option<(Box<dyn crate::circuit::Op<F>>, OutletId)>, Box<dyn std::error::Error>> {
+    // the pattern's input, as seen by the fused op
+    let load_input = |x: OutletId| match other_nodes.get(&x.node) {
+        Some(n) => n.output(x.slot),
+        None => Err(GraphError::MissingNode(x.node)),
+    };
+    if let Some((op, input)) = fuse_activation(node, model) {
+        return Ok(Some((Box::new(op), input)));
+    }
+    if let Some((min, max, x)) = match_clip(node, model) {
+        let input = load_input(x)?;
+        let op = fuse_clip(min, max, &input, bits)?;
+        return Ok(Some((op, x)));
+    }
+    if let Some(pattern) = match_layer_norm(node, model) {
+        let x = pattern.input;
+        let input = load_input(x)?;
+        if let Some(op) = fuse_instance_norm(node, &pattern, &input, public_params)? {
+            return Ok(Some((Box::new(op), x)));
+        }
+        let op = fuse_layer_norm(node, pattern, &input, public_params)?;
+        return Ok(Some((Box::new(op), x)));
+    }
+    if let Some((slope, intercept, x)) = match_batch_norm(node, model) {
+        let input = load_input(x)?;
+        if let Some(op) = fuse_batch_norm(slope, intercept, &input, public_params)? {
+            return Ok(Some((Box::new(op), x)));
+        }
+    }
+    if let Some((x, mask)) = match_masked_softmax(node, model) {
+        check_softmax_axes(node.id, node)?;
+        let input = load_input(x)?;
+        let mut mask: ValTensor<F> = mask
+            .map(|m| crate::tensor::ValType::Constant(i128_to_felt::<F>(m)))
+            .into();
+        match_input_rank(&mut mask, input.out_dims[0].len())?;
+        let op = HybridOp::Softmax {
+            scales: (1, 1),
+            bits,
+            mask: Some(mask),
+        };
+        return Ok(Some((Box::new(op), x)));
+    }
+    Ok(None)
+}
+
+/// Removes the batch dimension of a node's output dims, as ezkl does for every node it loads.
+pub fn rm_batch_dim(mut dims: Vec<usize>) -> Vec<usize> {
+    if !dims.is_empty() && dims[0] == 1 && dims.len() > 1 {
+        dims = dims[1..].to_vec();
+    }
+    if dims.iter().product::<usize>() == 1 {
+        dims = vec![1];
+    };
+    dims
+}
+
+/// Returns the dims of each of the samples held by a tensor of shape `dims`: one entry per sample if
+/// the tensor holds a `batch` of more than one sample along its leading axis, else `dims` itself.
+pub fn sample_dims(dims: &[usize], batch: Option<usize>) -> Vec<Vec<usize>> {
+    match batch {
+        Some(batch_size) if batch_size > 1 => vec![dims[1..].to_vec(); batch_size],
+        _ => vec![dims.to_vec()],
+    }
+}
+
+/// Returns the ezkl dims of the output `slot` of `node` and the number of leading dims removed from it.
+fn scan_dims(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    slot: usize,
+) -> Result<(Vec<usize>, usize), Box<dyn std::error::Error>> {
+    match node_output_shapes(node)?.get(slot) {
+        Some(Some(dims)) => {
+            let ezkl_dims = rm_batch_dim(dims.clone());
+            let offset = dims.len().saturating_sub(ezkl_dims.len());
+            Ok((ezkl_dims, offset))
+        }
+        _ => Err(Box::new(GraphError::MisformedParams(format!(
+            "scan node {} has unknown output dims",
+            node.id
+        )))),
+    }
+}
+
+/// Creates a [Node] for an op ezkl inserts when unrolling a scan, i.e which has no onnx counterpart.
+/// The op reads the first output of each of its `inputs`.
+fn scan_node<F: FieldExt + TensorType>(
+    opkind: Box<dyn crate::circuit::Op<F>>,
+    inputs: &[&Node<F>],
+    out_dims: Vec<usize>,
+    scale: u32,
+    idx: &mut usize,
+) -> Node<F> {
+    let in_scales: Vec<u32> = inputs.iter().map(|i| i.out_scales[0]).collect();
+    let node = Node {
+        idx: *idx,
+        out_scales: opkind.out_scales(in_scales, scale),
+        opkind,
+        inputs: inputs.iter().map(|i| (i.idx, 0)).collect(),
+        out_dims: vec![out_dims],
+    };
+    *idx += 1;
+    node
+}
+
+/// Returns a node which brings the output of `node` to the fixed point scale `scale`, if it isn't
+/// already at that scale.
+fn rescale_scan_node<F: FieldExt + TensorType>(
+    node: &Node<F>,
+    scale: u32,
+    idx: &mut usize,
+) -> Option<Node<F>> {
+    let opkind: Box<dyn crate::circuit::Op<F>> = match node.out_scales[0].cmp(&scale) {
+        std::cmp::Ordering::Equal => return None,
+        std::cmp::Ordering::Greater => crate::circuit::Op::<F>::rescale(
+            &LookupOp::Div {
+                denom: crate::circuit::utils::F32(1.0),
+            },
+            vec![node.out_scales[0]],
+            scale,
+        ),
+        std::cmp::Ordering::Less => Box::new(crate::circuit::Rescaled {
+            inner: Box::new(PolyOp::Identity),
+            scale: vec![(0, scale_to_multiplier(scale - node.out_scales[0]) as usize)],
+        }),
+    };
+    Some(scan_node(
+        opkind,
+        &[node],
+        node.out_dims[0].clone(),
+        scale,
+        idx,
+    ))
+}
+
+/// Returns a node whose first output is the output `slot` of `node`, as the nodes inserted when unrolling a scan
+/// read the first output of their inputs.
+fn first_output<F: FieldExt + TensorType>(
+    node: &Node<F>,
+    slot: usize,
+    idx: &mut usize,
+    unrolled: &mut Vec<Node<F>>,
+) -> Result<Node<F>, GraphError> {
+    if slot == 0 {
+        return Ok(node.clone());
+    }
+    let output = node.output(slot)?;
+    let identity = Node {
+        idx: *idx,
+        opkind: Box::new(PolyOp::Identity),
+        inputs: vec![(node.idx, slot)],
+        out_dims: output.out_dims,
+        out_scales: output.out_scales,
+    };
+    *idx += 1;
+    unrolled.push(identity.clone());
+    Ok(identity)
+}
+
+/// Unrolls a tract `Scan` node, which is what onnx `LSTM`, `GRU` and `RNN` nodes are lowered to, over its
+/// (static) sequence length. The scan's body is loaded once per timestep: scanned inputs are sliced along their
+/// scan axis and the recurrent states of a timestep feed into the next one. States and scanned outputs are
+/// brought back to the global scale at every timestep, such that their scale doesn't grow with the sequence length.
+///
+/// Returns the unrolled [Node]s, in execution order, the last of which stands in for the scan, i.e carries each
+/// of the scan's outputs at the same output slot.
+/// # Arguments
+/// * `node` - the `Scan` node.
+/// * `model` - the graph `node` belongs to.
+/// * `other_nodes` - the previously loaded [Node]s, keyed by their onnx node id.
+/// * `profile` - the [QuantizationProfile] the params of the unrolled nodes are quantized with, keyed by their identifier.
+/// * `idx` - the next free node identifier, incremented for every unrolled node.
+#[allow(clippy::too_many_arguments)]
+pub fn unroll_scan<F: FieldExt + TensorType>(
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
+    other_nodes: &BTreeMap<usize, Node<F>>,
+    scale: u32,
+    profile: &QuantizationProfile,
+    public_params: bool,
+    bits: usize,
+    idx: &mut usize,
+) -> Result<Vec<Node<F>>, Box<dyn std::error::Error>> {
+    let op: &Scan = match node.op().downcast_ref::<Scan>() {
+        Some(b) => b,
+        None => {
+            return Err(Box::new(GraphError::OpMismatch(
+                node.id,
+                "scan".to_string(),
+            )));
+        }
+    };
+    let body = &op.body;
+
+    let mut unrolled: Vec<Node<F>> = vec![];
+    let mut outer = vec![];
+    for i in node.inputs.iter() {
+        match other_nodes.get(&i.node) {
+            Some(n) => outer.push(first_output(n, i.slot, idx, &mut unrolled)?),
+            None => return Err(Box::new(GraphError::MissingNode(i.node))),
+        }
+    }
+
+    let mut seq_len = None;
+    for mapping in op.input_mapping.iter() {
+        if let InputMapping::Scan { slot, axis, chunk } = mapping {
+            let dim = model.outlet_fact(node.inputs[*slot])?.shape[*axis].to_usize()?;
+            seq_len = Some(dim / chunk.unsigned_abs());
+        }
+    }
+    let seq_len = match seq_len {
+        Some(l) => l,
+        None => {
+            return Err(Box::new(GraphError::MisformedParams(
+                "scan without a scanned input".to_string(),
+            )))
+        }
+    };
+
+    // body constants (e.g the weights) are shared by all timesteps
+    let mut constants = BTreeMap::<usize, Node<F>>::new();
+    let mut states: Vec<Node<F>> = vec![];
+    let mut outputs: Vec<Vec<Node<F>>> = vec![vec![]; op.output_mapping.len()];
+    for t in 0..seq_len {
+        let mut step = constants.clone();
+        let prev_states = std::mem::take(&mut states);
+        let mut state = 0;
+        for (mapping, source) in op.input_mapping.iter().zip(body.inputs.iter()) {
+            let (dims, _) = scan_dims(body.node(source.node), source.slot)?;
+            let bound = match mapping {
+                InputMapping::Full { slot } => outer[*slot].clone(),
+                InputMapping::State { initializer } => {
+                    state += 1;
+                    match (t, initializer) {
+                        (0, StateInitializer::FromInput(slot)) => outer[*slot].clone(),
+                        (0, StateInitializer::Value(v)) => {
+                            let mut values = extract_tensor_value(v.clone(), scale, public_params)?;
+                            values.reshape(&dims)?;
+                            let c = scan_node(
+                                Box::new(crate::circuit::ops::Constant { values }),
+                                &[],
+                                dims.clone(),
+                                scale,
+                                idx,
+                            );
+                            unrolled.push(c.clone());
+                            c
+                        }
+                        _ => prev_states[state - 1].clone(),
+                    }
+                }
+                InputMapping::Scan { slot, axis, chunk } => {
+                    let x = &outer[*slot];
+                    let rank = model.outlet_fact(node.inputs[*slot])?.rank();
+                    let axis = shift_axis(*axis, rank - x.out_dims[0].len(), "scan")?;
+                    let len = chunk.unsigned_abs();
+                    let start = match *chunk < 0 {
+                        true => (seq_len - 1 - t) * len,
+                        false => t * len,
+                    };
+                    let mut slice_dims = x.out_dims[0].clone();
+                    slice_dims[axis] = len;
+                    let mut slice = scan_node(
+                        Box::new(PolyOp::Slice {
+                            axis,
+                            start,
+                            end: start + len,
+                        }),
+                        &[x],
+                        slice_dims,
+                        scale,
+                        idx,
+                    );
+                    if slice.out_dims[0] != dims {
+                        unrolled.push(slice.clone());
+                        slice = scan_node(
+                            Box::new(PolyOp::Reshape(dims.clone())),
+                            &[&slice],
+                            dims,
+                            scale,
+                            idx,
+                        );
+                    }
+                    unrolled.push(slice.clone());
+                    slice
+                }
+            };
+            step.insert(source.node, bound);
+        }
+
+        let mut loaded = vec![];
+        for n in body.nodes.iter() {
+            if step.contains_key(&n.id) {
+                continue;
+            }
+            let sub = Node::new(
+                n.clone(),
+                &mut step,
+                scale,
+                profile.scale(*idx, scale),
+                public_params,
+                bits,
+                *idx,
+                body,
+            )?;
+            *idx += 1;
+            if t == 0 && sub.inputs.is_empty() && sub.opkind.is_constant() {
+                constants.insert(n.id, sub.clone());
+            }
+            step.insert(n.id, sub);
+            loaded.push(n.id);
+        }
+        unrolled.extend(loaded.iter().map(|id| step[id].clone()));
+
+        for (k, (mapping, outlet)) in op
+            .output_mapping
+            .iter()
+            .zip(body.outputs.iter())
+            .enumerate()
+        {
+            let mut out = first_output(&step[&outlet.node], outlet.slot, idx, &mut unrolled)?;
+            if let Some(rescaled) = rescale_scan_node(&out, scale, idx) {
+                unrolled.push(rescaled.clone());
+                out = rescaled;
+            }
+            if mapping.state {
+                states.push(out.clone());
+            }
+            outputs[k].push(out);
+        }
+        debug!(
+            "unrolled timestep {} of scan node {} into nodes {:?}",
+            t,
+            node.id,
+            loaded.iter().map(|id| step[id].idx).collect::<Vec<_>>()
+        );
+    }
+
+    // each output of the scan is assembled from the outputs of its timesteps
+    let mut scan_outputs = vec![];
+    for slot in 0..node.outputs.len() {
+        let (k, mapping) = match op
+            .output_mapping
+            .iter()
+            .enumerate()
+            .find(|(_, m)| m.full_slot == Some(slot) || m.last_value_slot == Some(slot))
+        {
+            Some(m) => m,
+            None => {
+                return Err(Box::new(GraphError::MisformedParams(format!(
+                    "scan without a mapping for output {}",
+                    slot
+                ))))
+            }
+        };
+        let (out_dims, offset) = scan_dims(node, slot)?;
+        if mapping.full_slot == Some(slot) {
+            let axis = shift_axis(mapping.axis, offset, "scan")?;
+            let mut chunk_dims = out_dims.clone();
+            chunk_dims[axis] = mapping.chunk.unsigned_abs();
+            let mut chunks = vec![];
+            for out in outputs[k].iter() {
+                if out.out_dims[0] == chunk_dims {
+                    chunks.push(out.clone());
+                } else {
+                    let reshaped = scan_node(
+                        Box::new(PolyOp::Reshape(chunk_dims.clone())),
+                        &[out],
+                        chunk_dims.clone(),
+                        scale,
+                        idx,
+                    );
+                    unrolled.push(reshaped.clone());
+                    chunks.push(reshaped);
+                }
+            }
+            if mapping.chunk < 0 {
+                chunks.reverse();
+            }
+            let concat = PolyOp::Concat {
+                axis,
+                constants: vec![None; chunks.len()],
+            };
+            let inputs: Vec<&Node<F>> = chunks.iter().collect();
+            scan_outputs.push(scan_node(Box::new(concat), &inputs, out_dims, scale, idx));
+        } else {
+            let last = &outputs[k][seq_len - 1];
+            let opkind = Box::new(PolyOp::Reshape(out_dims.clone()));
+            scan_outputs.push(scan_node(opkind, &[last], out_dims, scale, idx));
+        }
+    }
+    if scan_outputs.is_empty() {
+        return Err(Box::new(GraphError::MisformedParams(
+            "scan without outputs".to_string(),
+        )));
+    }
+    unrolled.extend(scan_outputs.iter().cloned());
+
+    // a scan with several outputs is stood in for by a node forwarding each to its slot
+    if scan_outputs.len() > 1 {
+        unrolled.push(Node {
+            idx: *idx,
+            opkind: Box::new(crate::circuit::Tuple {
+                len: scan_outputs.len(),
+            }),
+            inputs: scan_outputs.iter().map(|o| (o.idx, 0)).collect(),
+            out_dims: scan_outputs.iter().map(|o| o.out_dims[0].clone()).collect(),
+            out_scales: scan_outputs.iter().map(|o| o.out_scales[0]).collect(),
+        });
+        *idx += 1;
+    }
+
+    Ok(unrolled)
+}
+
 /// Matches an onnx node to a [OpKind] and returns a [Node] with the corresponding [OpKind].  
+/// Ops with a constructor registered using [crate::graph::register_op] are matched first.
+/// `bits` is the width of the lookup tables, which bounds the intermediate values of some ops (e.g softmax).
+/// `model` is the graph holding `node`, which gives the types of its inputs.
 pub fn new_op_from_onnx<F: FieldExt + TensorType>(
     idx: usize,
     scale: u32,
     public_params: bool,
+    bits: usize,
     node: OnnxNode<TypedFact, Box<dyn TypedOp>>,
     inputs: &mut Vec<Node<F>>,
+    model: &Graph<TypedFact, Box<dyn TypedOp>>,
 ) -> Result<Box<dyn crate::circuit::Op<F>>, Box<dyn std::error::Error>> {
+    // registered (custom) ops take precedence over the ops supported natively
+    if let Some(constructor) = registered_op::<F>(node.op().name().as_ref()) {
+        debug!("using registered constructor for {}", node.op().name());
+        return constructor(&node, scale, public_params, inputs);
+    }
     Ok(match node.op().name().as_ref() {
         "Reduce<Min>" => Box::new(HybridOp::Min),
         "Reduce<Max>" => Box::new(HybridOp::Max),
+        "Reduce<ArgMax(false)>"
+        | "Reduce<ArgMax(true)>"
+        | "Reduce<ArgMin(false)>"
+        | "Reduce<ArgMin(true)>" => {
+            let op = match node.op().downcast_ref::<Reduce>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "argmax".to_string())));
+                }
+            };
+            if op.axes.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(idx, "argmax".to_string())));
+            }
+            let axis = shift_axis(op.axes[0], batch_offset(&node), "argmax")?;
+            match op.reducer {
+                Reducer::ArgMax(select_last) => Box::new(HybridOp::ArgMax { axis, select_last }),
+                Reducer::ArgMin(select_last) => Box::new(HybridOp::ArgMin { axis, select_last }),
+                _ => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "argmax".to_string())));
+                }
+            }
+        }
         // TODO: this is a hack to get around the fact that onnx replace ReLU with Max(0, x) -- we should probably implement
         "MaxUnary" => {
             // Extract the slope layer hyperparams
@@ -164,13 +1167,24 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
                 && max_op.a.as_slice::<f32>()?.to_vec()[0] == 0.0
             {
                 Box::new(LookupOp::ReLU {
-                    scale: inputs[0].out_scale as usize,
+                    scale: inputs[0].out_scales[0] as usize,
                 })
             } else {
-                let matrix = extract_tensor_value(max_op.a.clone(), scale, public_params)?;
+                let mut matrix =
+                    extract_tensor_value(max_op.a.clone(), inputs[0].out_scales[0], public_params)?;
+                match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
                 Box::new(HybridOp::EltWiseMax { a: Some(matrix) })
             }
         }
+        "MinUnary" => {
+            let min_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
+            let mut matrix =
+                extract_tensor_value(min_op.a.clone(), inputs[0].out_scales[0], public_params)?;
+            match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
+            Box::new(HybridOp::EltWiseMin { a: Some(matrix) })
+        }
+        "Max" => Box::new(HybridOp::EltWiseMax { a: None }),
+        "Min" => Box::new(HybridOp::EltWiseMin { a: None }),
         "Prelu" => {
             unreachable!("Prelu should be converted to a more complex format in Onnx");
         }
@@ -206,18 +1220,90 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
         "Rsqrt" => Box::new(LookupOp::Rsqrt { scales: (1, 1) }),
         "Tanh" => Box::new(LookupOp::Tanh { scales: (1, 1) }),
         "onnx.Erf" => Box::new(LookupOp::Erf { scales: (1, 1) }),
+        "Exp" => Box::new(LookupOp::Exp { scales: (1, 1) }),
+        "Ln" => Box::new(LookupOp::Ln { scales: (1, 1) }),
+        "Recip" => Box::new(LookupOp::Recip { scales: (1, 1) }),
+        "onnx.Gelu" | "Gelu" => Box::new(LookupOp::Gelu { scales: (1, 1) }),
+        "onnx.HardSwish" | "HardSwish" => Box::new(LookupOp::HardSwish { scales: (1, 1) }),
+        "PowUnary" => {
+            // Extract the exponent
+            let pow_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
+            if pow_op.a.shape().iter().product::<usize>() != 1 {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "ezkl currently only supports pow with a scalar exponent".to_string(),
+                )));
+            }
+            let exponent = pow_op.a.cast_to_scalar::<f32>()?;
+
+            // positive integer powers can be expressed as repeated multiplication
+            if exponent.fract() == 0.0 && exponent >= 1.0 {
+                Box::new(PolyOp::Pow(exponent as u32))
+            } else {
+                Box::new(LookupOp::Pow {
+                    scales: (1, 1),
+                    a: crate::circuit::utils::F32(exponent),
+                })
+            }
+        }
         "Source" => Box::new(crate::circuit::ops::Input),
         "Add" => Box::new(PolyOp::Add { a: None }),
-        "Sub" => Box::new(PolyOp::Sub),
+        "Sub" => Box::new(PolyOp::Sub { a: None }),
         "Mul" => Box::new(PolyOp::Mult { a: None }),
         "Gemm" => Box::new(PolyOp::Affine),
         "Greater" => Box::new(HybridOp::Greater { a: None }),
-        "GreaterUnary" => {
-            // Extract the slope layer hyperparams
-            let greater = load_unary_op(node.op(), idx, node.op().name().to_string())?;
-            let matrix = extract_tensor_value(greater.a.clone(), scale, public_params)?;
+        "GreaterEqual" => Box::new(HybridOp::GreaterEqual { a: None }),
+        "Lesser" => Box::new(HybridOp::Less { a: None }),
+        "LesserEqual" => Box::new(HybridOp::LessEqual { a: None }),
+        "Equals" => Box::new(HybridOp::Equal { a: None }),
+        "And" => Box::new(HybridOp::And { a: None }),
+        "Or" => Box::new(HybridOp::Or { a: None }),
+        "Xor" => Box::new(HybridOp::Xor { a: None }),
+        "Not" => Box::new(HybridOp::Not),
+        "Iff" => Box::new(HybridOp::Iff),
+        "GreaterUnary" | "GreaterEqualUnary" | "LesserUnary" | "LesserEqualUnary"
+        | "EqualsUnary" | "AndUnary" | "OrUnary" | "XorUnary" => {
+            let cmp_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
+            // compared constants are quantized at the scale of the input, boolean constants are unscaled
+            let mut matrix =
+                extract_tensor_value(cmp_op.a.clone(), inputs[0].out_scales[0], public_params)?;
+            match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
 
-            Box::new(HybridOp::Greater { a: Some(matrix) })
+            let a = Some(matrix);
+            // tract's unary ops hold their constant as the left operand, i.e these are `a OP x`, whereas
+            // the hybrid comparisons take `a` as their right operand, so `a > x` is `x < a` and so on
+            match node.op().name().as_ref() {
+                "GreaterUnary" => Box::new(HybridOp::Less { a }),
+                "GreaterEqualUnary" => Box::new(HybridOp::LessEqual { a }),
+                "LesserUnary" => Box::new(HybridOp::Greater { a }),
+                "LesserEqualUnary" => Box::new(HybridOp::GreaterEqual { a }),
+                "EqualsUnary" => Box::new(HybridOp::Equal { a }),
+                "AndUnary" => Box::new(HybridOp::And { a }),
+                "OrUnary" => Box::new(HybridOp::Or { a }),
+                _ => Box::new(HybridOp::Xor { a }),
+            }
+        }
+        "Cast" => {
+            let cast: &Cast = match node.op().downcast_ref::<Cast>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "cast".to_string())));
+                }
+            };
+            let from = model.outlet_fact(node.inputs[0])?.datum_type;
+            // booleans and numbers are all represented as field elements (at the scale of the input), so casts
+            // which keep the value are no-ops, whereas truncating a float to an integer isn't supported
+            let keeps_value = from == cast.to
+                || from == DatumType::Bool
+                || cast.to == DatumType::Bool
+                || (from.is_float() && cast.to.is_float())
+                || (from.is_integer() && (cast.to.is_integer() || cast.to.is_float()));
+            if !keeps_value {
+                return Err(Box::new(GraphError::MisformedParams(format!(
+                    "cast of node {} from {:?} to {:?} (ezkl only supports casts which keep the value)",
+                    idx, from, cast.to
+                ))));
+            }
+            Box::new(PolyOp::Identity)
         }
         "MatMulUnary" => {
             // Extract the slope layer hyperparams
@@ -230,17 +1316,46 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
 
             let matrix = extract_tensor_value(mm_op.a.clone(), scale, public_params)?;
 
-            Box::new(PolyOp::Matmul { a: Some(matrix) })
+            Box::new(PolyOp::Matmul {
+                a: Some(matrix),
+                a_trans: mm_op.a_trans,
+                b_trans: mm_op.b_trans,
+                c_trans: mm_op.c_trans,
+            })
+        }
+        "MatMul" => {
+            let mm_op: &MatMul = match node.op().downcast_ref::<MatMul>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "mm".to_string())));
+                }
+            };
+
+            Box::new(PolyOp::Matmul {
+                a: None,
+                a_trans: mm_op.a_trans,
+                b_trans: mm_op.b_trans,
+                c_trans: mm_op.c_trans,
+            })
         }
-        "MatMul" => Box::new(PolyOp::Matmul { a: None }),
         "AddUnary" => {
             // Extract the slope layer hyperparams
             let add_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
-            let matrix =
-                extract_tensor_value(add_op.a.clone(), inputs[0].out_scale, public_params)?;
+            let mut matrix =
+                extract_tensor_value(add_op.a.clone(), inputs[0].out_scales[0], public_params)?;
+            match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
 
             Box::new(PolyOp::Add { a: Some(matrix) })
         }
+        "SubUnary" => {
+            // tract's unary ops hold their constant as the left operand, i.e this is `a - x` (e.g in GRUs)
+            let sub_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
+            let mut matrix =
+                extract_tensor_value(sub_op.a.clone(), inputs[0].out_scales[0], public_params)?;
+            match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
+
+            Box::new(PolyOp::Sub { a: Some(matrix) })
+        }
         "MulUnary" => {
             // Extract the slope layer hyperparams
             let mul_op = load_unary_op(node.op(), idx, node.op().name().to_string())?;
@@ -254,7 +1369,8 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
                     denom: crate::circuit::utils::F32(denom),
                 })
             } else {
-                let matrix = extract_tensor_value(mul_op.a.clone(), scale, public_params)?;
+                let mut matrix = extract_tensor_value(mul_op.a.clone(), scale, public_params)?;
+                match_input_rank(&mut matrix, inputs[0].out_dims[0].len())?;
                 Box::new(PolyOp::Mult { a: Some(matrix) })
             }
         }
@@ -278,20 +1394,20 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
             }
 
             let stride = pool_spec.strides.clone().unwrap();
-            let padding = match &pool_spec.padding {
-                PaddingSpec::Explicit(p, _, _) => p,
+            // padding can differ before and after the image (e.g "same" padding exported by tf)
+            let (before, after) = match &pool_spec.padding {
+                PaddingSpec::Explicit(b, a, _) => (b, a),
                 _ => {
                     return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                 }
             };
             let kernel_shape = &pool_spec.kernel_shape;
 
-            let (padding_h, padding_w, stride_h, stride_w) =
-                (padding[0], padding[1], stride[0], stride[1]);
+            let (stride_h, stride_w) = (stride[0], stride[1]);
             let (kernel_height, kernel_width) = (kernel_shape[0], kernel_shape[1]);
 
             Box::new(HybridOp::MaxPool2d {
-                padding: (padding_h, padding_w),
+                padding: [(before[0], after[0]), (before[1], after[1])],
                 stride: (stride_h, stride_w),
                 pool_dims: (kernel_height, kernel_width),
             })
@@ -306,8 +1422,16 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
         }
         "Reduce<Mean>" => Box::new(HybridOp::Mean {
             scale: 1,
-            num_inputs: inputs[0].out_dims.iter().product::<usize>(),
+            num_inputs: inputs[0].out_dims[0].iter().product::<usize>(),
         }),
+        "Softmax" => {
+            check_softmax_axes(idx, &node)?;
+            Box::new(HybridOp::Softmax {
+                scales: (1, 1),
+                bits,
+                mask: None,
+            })
+        }
         "Square" => Box::new(PolyOp::Pow(2)),
         "ConvUnary" => {
             let conv_node: &ConvUnary = match node.op().downcast_ref::<ConvUnary>() {
@@ -332,21 +1456,54 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
                 }
             };
             let padding = match &conv_node.pool_spec.padding {
-                PaddingSpec::Explicit(p, _, _) => p,
+                PaddingSpec::Explicit(b, a, _) => {
+                    b.iter().copied().zip(a.iter().copied()).collect::<Vec<_>>()
+                }
                 _ => {
                     return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                 }
             };
 
-            let (padding_h, padding_w, stride_h, stride_w) =
-                (padding[0], padding[1], stride[0], stride[1]);
-
             let kernel = extract_tensor_value(conv_node.kernel.clone(), scale, public_params)?;
 
+            // the kernel is O x C/G x K_1 x ... x K_n, so this supports 1D, 2D, and 3D convolutions
+            let spatial_dims = kernel.dims().len() - 2;
+            let dilation = match &conv_node.pool_spec.dilations {
+                Some(d) => d.to_vec(),
+                None => vec![1; spatial_dims],
+            };
+            if (padding.len() != spatial_dims)
+                || (stride.len() != spatial_dims)
+                || (dilation.len() != spatial_dims)
+            {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "conv padding, strides or dilations".to_string(),
+                )));
+            }
+
+            let groups = conv_node.group;
+            // single channel inputs may not have an explicit channel dim
+            let in_dims = &inputs[0].out_dims[0];
+            let input_channels = match in_dims.len().checked_sub(spatial_dims + 1) {
+                Some(c) => in_dims[c],
+                None => 1,
+            };
+            if (groups == 0)
+                || (input_channels != kernel.dims()[1] * groups)
+                || (kernel.dims()[0] % groups != 0)
+            {
+                return Err(Box::new(GraphError::MisformedParams(format!(
+                    "conv with {} groups, {} input channels and {} output channels",
+                    groups,
+                    input_channels,
+                    kernel.dims()[0]
+                ))));
+            }
+
             let bias = match conv_node.bias.clone() {
                 Some(b) => Some(extract_tensor_value(
                     b,
-                    scale + inputs[0].out_scale,
+                    scale + inputs[0].out_scales[0],
                     public_params,
                 )?),
                 None => None,
@@ -355,8 +1512,10 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
             Box::new(PolyOp::Conv {
                 kernel,
                 bias,
-                padding: (padding_h, padding_w),
-                stride: (stride_h, stride_w),
+                padding,
+                stride: stride.to_vec(),
+                dilation,
+                groups,
             })
         }
 
@@ -380,8 +1539,8 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
             }
 
             let stride = pool_spec.strides.clone().unwrap();
-            let padding = match &pool_spec.padding {
-                PaddingSpec::Explicit(p, _, _) => p,
+            let (before, after, ceil_mode) = match &pool_spec.padding {
+                PaddingSpec::Explicit(b, a, c) => (b, a, *c),
                 _ => {
                     return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                 }
@@ -389,19 +1548,56 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
             let kernel_shape = &pool_spec.kernel_shape;
 
             let (padding_h, padding_w, stride_h, stride_w) =
-                (padding[0], padding[1], stride[0], stride[1]);
+                (before[0], before[1], stride[0], stride[1]);
             let (kernel_height, kernel_width) = (kernel_shape[0], kernel_shape[1]);
 
-            Box::new(PolyOp::SumPool {
-                padding: (padding_h, padding_w),
-                stride: (stride_h, stride_w),
-                kernel_shape: (kernel_height, kernel_width),
-            })
+            // an onnx AveragePool is a normalized sum pool
+            if sumpool_node.normalize {
+                let padding = [(before[0], after[0]), (before[1], after[1])];
+                let image_dims = (inputs[0].out_dims[0][1], inputs[0].out_dims[0][2]);
+                // the windows (and so the lookups) of the pool are only known if it fits the image
+                if let Err(e) = crate::tensor::ops::avg_pool2d_geometry(
+                    image_dims,
+                    padding,
+                    (stride_h, stride_w),
+                    (kernel_height, kernel_width),
+                    ceil_mode,
+                    sumpool_node.count_include_pad,
+                ) {
+                    return Err(Box::new(GraphError::MisformedParams(format!(
+                        "average pool of kernel {:?} over an image of dims {:?}: {}",
+                        kernel_shape, image_dims, e
+                    ))));
+                }
+                Box::new(HybridOp::AvgPool2d {
+                    padding,
+                    stride: (stride_h, stride_w),
+                    kernel_shape: (kernel_height, kernel_width),
+                    ceil_mode,
+                    count_include_pad: sumpool_node.count_include_pad,
+                    image_dims,
+                })
+            } else {
+                // sum pools are only padded symmetrically
+                if before[..] != after[..] {
+                    return Err(Box::new(GraphError::MisformedParams(
+                        "sum pool with asymmetric padding".to_string(),
+                    )));
+                }
+                Box::new(PolyOp::SumPool {
+                    padding: (padding_h, padding_w),
+                    stride: (stride_h, stride_w),
+                    kernel_shape: (kernel_height, kernel_width),
+                })
+            }
         }
-        "GlobalAvgPool" => Box::new(PolyOp::SumPool {
-            padding: (0, 0),
+        "GlobalAvgPool" => Box::new(HybridOp::AvgPool2d {
+            padding: [(0, 0), (0, 0)],
             stride: (1, 1),
-            kernel_shape: (inputs[0].out_dims[1], inputs[0].out_dims[2]),
+            kernel_shape: (inputs[0].out_dims[0][1], inputs[0].out_dims[0][2]),
+            ceil_mode: false,
+            count_include_pad: false,
+            image_dims: (inputs[0].out_dims[0][1], inputs[0].out_dims[0][2]),
         }),
         "Pad" => {
             let pad_node: &Pad = match node.op().downcast_ref::<Pad>() {
@@ -410,46 +1606,178 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
                     return Err(Box::new(GraphError::OpMismatch(idx, "pad".to_string())));
                 }
             };
-            // we only support constant 0 padding
-            if pad_node.mode
-                != PadMode::Constant(tract_onnx::prelude::Arc::new(
-                    tract_onnx::prelude::Tensor::zero::<f32>(&[])?,
-                ))
-            {
+            let mode = match &pad_node.mode {
+                PadMode::Constant(c) => {
+                    // the padding value is quantized at the same scale as the input
+                    let c = c.cast_to_scalar::<f32>()?;
+                    let c = vector_to_quantized(&[c], &[1], 0f32, inputs[0].out_scales[0])?[0];
+                    crate::tensor::ops::PadMode::Constant(c)
+                }
+                PadMode::Reflect => crate::tensor::ops::PadMode::Reflect,
+                PadMode::Edge => crate::tensor::ops::PadMode::Edge,
+            };
+
+            // the input may have had its batch dim removed, in which case it must not be padded
+            let rank = inputs[0].out_dims[0].len();
+            if pad_node.pads.len() < rank {
                 return Err(Box::new(GraphError::MisformedParams(
-                    "pad mode or pad type".to_string(),
+                    "pad has fewer pads than input dims".to_string(),
+                )));
+            }
+            let (removed, padding) = pad_node.pads.split_at(pad_node.pads.len() - rank);
+            if removed.iter().any(|p| *p != (0, 0)) {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "ezkl currently does not support padding the batch dimension".to_string(),
                 )));
             }
 
-            let padding_len = pad_node.pads.len();
-
-            // we only support symmetrical padding that affects the last 2 dims (height and width params)
-            for (i, pad_params) in pad_node.pads.iter().enumerate() {
-                if (i < padding_len - 2) && ((pad_params.0 != 0) || (pad_params.1 != 0)) {
+            Box::new(PolyOp::Pad {
+                padding: padding.to_vec(),
+                mode,
+            })
+        }
+        "Const" => {
+            let op: &Const = match node.op().downcast_ref::<Const>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "const".to_string())));
+                }
+            };
+            let values = extract_tensor_value(op.0.clone(), scale, public_params)?;
+            Box::new(crate::circuit::ops::Constant { values })
+        }
+        "MoveAxis" => {
+            let op = load_axis_op(node.op(), idx, node.op().name().to_string())?;
+            let offset = batch_offset(&node);
+            let perm = match op {
+                AxisOp::Move(from, to) => {
+                    let (from, to) = (
+                        shift_axis(from, offset, "transpose")?,
+                        shift_axis(to, offset, "transpose")?,
+                    );
+                    let mut perm: Vec<usize> = (0..inputs[0].out_dims[0].len()).collect();
+                    let axis = perm.remove(from);
+                    perm.insert(to, axis);
+                    perm
+                }
+                _ => {
                     return Err(Box::new(GraphError::MisformedParams(
-                        "ezkl currently only supports padding height and width dimensions"
-                            .to_string(),
+                        "transpose".to_string(),
                     )));
                 }
-                if pad_params.0 != pad_params.1 {
+            };
+            Box::new(PolyOp::Transpose { perm })
+        }
+        "Concat" | "TypedConcat" => {
+            let op: &TypedConcat = match node.op().downcast_ref::<TypedConcat>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "concat".to_string())));
+                }
+            };
+            let offset = batch_offset(&node);
+            let axis = shift_axis(op.axis, offset, "concat")?;
+            // constant slices are quantized at the scale the inputs will be homogenized to
+            let const_scale = inputs
+                .iter()
+                .map(|i| i.out_scales[0])
+                .max()
+                .unwrap_or(scale);
+            let mut constants = vec![];
+            for slice in op.slices.iter() {
+                constants.push(match slice {
+                    ConcatSlice::Const(t) => {
+                        let mut c = extract_tensor_value(t.clone(), const_scale, public_params)?;
+                        c.reshape(&t.shape()[offset..])?;
+                        Some(c)
+                    }
+                    ConcatSlice::Var => None,
+                });
+            }
+            Box::new(PolyOp::Concat { axis, constants })
+        }
+        "Slice" => {
+            let op: &Slice = match node.op().downcast_ref::<Slice>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "slice".to_string())));
+                }
+            };
+            let axis = shift_axis(op.axis, batch_offset(&node), "slice")?;
+            let (start, end) = (op.start.to_i64()? as usize, op.end.to_i64()? as usize);
+            Box::new(PolyOp::Slice { axis, start, end })
+        }
+        "Gather" => {
+            let op: &Gather = match node.op().downcast_ref::<Gather>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "gather".to_string())));
+                }
+            };
+            if inputs.len() != 2 {
+                return Err(Box::new(GraphError::MisformedParams("gather".to_string())));
+            }
+            // an embedding, i.e a lookup into a constant table at indices computed by the model
+            if inputs[0].opkind.is_constant() && !inputs[1].opkind.is_constant() {
+                if op.axis != 0 {
                     return Err(Box::new(GraphError::MisformedParams(
-                        "ezkl currently only supports symmetric padding".to_string(),
+                        "ezkl currently only supports embeddings along the first axis of the table"
+                            .to_string(),
                     )));
                 }
+                return Ok(Box::new(HybridOp::Embedding { index_scale: 1 }));
             }
-
-            let (padding_h, padding_w) = (
-                pad_node.pads[padding_len - 2].0,
-                pad_node.pads[padding_len - 1].0,
-            );
-            Box::new(PolyOp::Pad(padding_h, padding_w))
+            if !inputs[1].opkind.is_constant() {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "ezkl currently only supports gather with constant indices or a constant table"
+                        .to_string(),
+                )));
+            }
+            let offset = batch_offset(&node);
+            let axis = shift_axis(op.axis, offset, "gather")?;
+            let data_dims = &inputs[0].out_dims[0];
+            let out_dims = match node_output_shapes(&node)?.first() {
+                Some(Some(dims)) => dims[offset..].to_vec(),
+                _ => return Err(Box::new(GraphError::MisformedParams("gather".to_string()))),
+            };
+            // the output dims are data_dims[..axis] ++ indices_dims ++ data_dims[axis + 1..]
+            let indices_rank = match (out_dims.len() + 1).checked_sub(data_dims.len()) {
+                Some(r) => r,
+                None => return Err(Box::new(GraphError::MisformedParams("gather".to_string()))),
+            };
+            let indices_dims = out_dims[axis..axis + indices_rank].to_vec();
+            let indices: Vec<usize> = inputs[1]
+                .opkind
+                .f(&[])?
+                .iter()
+                .map(|i| match *i < 0 {
+                    true => (*i + data_dims[axis] as i128) as usize,
+                    false => *i as usize,
+                })
+                .collect();
+            if indices.len() != indices_dims.iter().product::<usize>() {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "gather indices".to_string(),
+                )));
+            }
+            Box::new(PolyOp::Gather {
+                axis,
+                indices: Tensor::new(Some(&indices), &indices_dims)?,
+            })
+        }
+        "AddAxis" => {
+            let new_dims = match node_output_shapes(&node)?.first() {
+                Some(Some(dims)) => rm_batch_dim(dims.clone()),
+                _ => return Err(Box::new(GraphError::MisformedParams("reshape".to_string()))),
+            };
+            Box::new(PolyOp::Reshape(new_dims))
         }
         "RmAxis" => {
             // Extract the slope layer hyperparams
             let reshape = load_axis_op(node.op(), idx, node.op().name().to_string())?;
 
             let new_dims: Vec<usize> = match reshape {
-                AxisOp::Rm(_) => inputs[0].out_dims.clone(),
+                AxisOp::Rm(_) => inputs[0].out_dims[0].clone(),
                 _ => {
                     return Err(Box::new(GraphError::MisformedParams("reshape".to_string())));
                 }
@@ -474,12 +1802,63 @@ pub fn new_op_from_onnx<F: FieldExt + TensorType>(
             Box::new(PolyOp::Reshape(new_dims.to_vec()))
         }
         "Flatten" => {
-            let new_dims: Vec<usize> = vec![inputs[0].out_dims.iter().product::<usize>()];
+            let new_dims: Vec<usize> = vec![inputs[0].out_dims[0].iter().product::<usize>()];
             Box::new(PolyOp::Flatten(new_dims))
         }
         c => {
-            warn!("{:?} is not currently supported", c);
+            warn!(
+                "{:?} is not currently supported, a constructor for it can be registered using `register_op`",
+                c
+            );
             Box::new(crate::circuit::ops::Unknown)
         }
     })
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::circuit::{Input, Op};
+    use halo2curves::pasta::Fp as F;
+
+    fn input(scale: u32) -> Node<F> {
+        Node {
+            opkind: Box::new(Input),
+            out_scales: vec![scale],
+            inputs: vec![],
+            out_dims: vec![vec![4]],
+            idx: 0,
+        }
+    }
+
+    #[test]
+    fn clip_with_out_of_range_bounds() {
+        let x = Tensor::<i128>::new(Some(&[-128, -1, 0, 127]), &[4]).unwrap();
+        let clip = |min: f32, max: f32| {
+            let op = fuse_clip::<F>(min, max, &input(7), 8).unwrap();
+            let op = op.rescale(vec![7], 7);
+            Op::<F>::f(&*op, &[x.clone()]).unwrap()
+        };
+
+        // bounds within the 8 bit tables are a single lookup
+        let op = fuse_clip::<F>(-0.5, 0.5, &input(7), 8).unwrap();
+        assert_eq!(
+            op.required_lookups(),
+            vec![LookupOp::Clip {
+                scales: (1, 1),
+                min: crate::circuit::utils::F32(-0.5),
+                max: crate::circuit::utils::F32(0.5),
+            }]
+        );
+        assert_eq!(clip(-0.5, 0.5).to_vec(), vec![-64, -1, 0, 64]);
+
+        // bounds beyond the tables are compared against instead, a one-sided clamp (i.e relu) only against its
+        // finite bound
+        let op = fuse_clip::<F>(0.0, f32::INFINITY, &input(7), 8).unwrap();
+        assert_eq!(op.required_lookups(), vec![LookupOp::ReLU { scale: 1 }]);
+        assert_eq!(clip(0.0, f32::INFINITY).to_vec(), vec![0, 0, 0, 127]);
+        assert_eq!(clip(2.0, 3.0).to_vec(), vec![256; 4]);
+        assert_eq!(clip(f32::NEG_INFINITY, -2.0).to_vec(), vec![-256; 4]);
+    }
+}
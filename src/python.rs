@@ -45,6 +45,9 @@ fn table(model: String) -> Result<String, PyErr> {
         public_params: false,
         pack_base: 1,
         check_mode: CheckMode::SAFE,
+        batch_size: 1,
+        quantization_profile: None,
+        dump_tensors: None,
     };
 
     // use default values to initialize model
@@ -74,6 +77,9 @@ fn gen_srs(params_path: PathBuf, logrows: u32) -> PyResult<()> {
         public_params: false,
         pack_base: 1,
         check_mode: CheckMode::SAFE,
+        batch_size: 1,
+        quantization_profile: None,
+        dump_tensors: None,
     };
     let params = ezkl_gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
     save_params::<KZGCommitmentScheme<Bn256>>(&params_path, &params)?;
@@ -92,7 +98,9 @@ fn gen_srs(params_path: PathBuf, logrows: u32) -> PyResult<()> {
     public_outputs=true,
     public_params=false,
     pack_base=1,
-    check_mode="safe"
+    check_mode="safe",
+    batch_size=1,
+    quantization_profile=None
 ))]
 fn forward(
     data: String,
@@ -107,6 +115,8 @@ fn forward(
     public_params: bool,
     pack_base: u32,
     check_mode: &str,
+    batch_size: usize,
+    quantization_profile: Option<PathBuf>,
 ) -> PyResult<()> {
     let data = prepare_data(data);
 
@@ -122,12 +132,15 @@ fn forward(
                 public_params: public_params,
                 pack_base: pack_base,
                 check_mode: CheckMode::from(check_mode.to_string()),
+                batch_size: batch_size,
+                quantization_profile: quantization_profile,
+                dump_tensors: None,
             };
             let mut new_data = m;
             let mut model_inputs = vec![];
             // quantize the supplied data using the provided scale.
-            for v in new_data.input_data.iter() {
-                match vector_to_quantized(v, &Vec::from([v.len()]), 0.0, run_args.scale) {
+            for (v, shape) in new_data.input_data.iter().zip(new_data.input_shapes.iter()) {
+                match vector_to_quantized(v, shape, 0.0, run_args.scale) {
                     Ok(t) => model_inputs.push(t),
                     Err(_) => return Err(PyValueError::new_err("Failed to quantize vector")),
                 }
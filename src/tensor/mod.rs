@@ -43,6 +43,47 @@ pub enum TensorError {
     WrongMethod,
 }
 
+/// Returns the shape two tensors broadcast to, following numpy's multidirectional broadcasting rules.
+/// ```
+/// use ezkl_lib::tensor::get_broadcasted_shape;
+/// let shape = get_broadcasted_shape(&[3, 1, 1], &[3, 2, 2]).unwrap();
+/// assert_eq!(shape, vec![3, 2, 2]);
+///
+/// let shape = get_broadcasted_shape(&[2, 1], &[4]).unwrap();
+/// assert_eq!(shape, vec![2, 4]);
+///
+/// assert!(get_broadcasted_shape(&[2, 3], &[2]).is_err());
+/// ```
+pub fn get_broadcasted_shape(
+    shape_a: &[usize],
+    shape_b: &[usize],
+) -> Result<Vec<usize>, TensorError> {
+    let rank = max(shape_a.len(), shape_b.len());
+    // pad both shapes with leading 1s up to the common rank
+    let pad = |shape: &[usize]| {
+        let mut padded = vec![1; rank - shape.len()];
+        padded.extend_from_slice(shape);
+        padded
+    };
+    let (shape_a, shape_b) = (pad(shape_a), pad(shape_b));
+
+    let mut shape = Vec::with_capacity(rank);
+    for (a, b) in shape_a.iter().zip(shape_b.iter()) {
+        shape.push(match (a, b) {
+            (a, b) if a == b => *a,
+            (1, b) => *b,
+            (a, 1) => *a,
+            _ => {
+                return Err(TensorError::DimMismatch(format!(
+                    "cannot broadcast {:?} with {:?}",
+                    shape_a, shape_b
+                )))
+            }
+        });
+    }
+    Ok(shape)
+}
+
 /// The (inner) type of tensor elements.
 pub trait TensorType: Clone + Debug + 'static {
     /// Returns the zero value.
@@ -699,6 +740,42 @@ impl<T: Clone + TensorType> Tensor<T> {
         Ok(tiled)
     }
 
+    /// Broadcasts a tensor to `shape`, following numpy's broadcasting rules: dims are aligned from
+    /// the right and any dim of size 1 (or missing) is repeated to match the target dim.
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// let a = Tensor::<i32>::new(Some(&[1, 2, 3]), &[3, 1, 1]).unwrap();
+    /// let c = a.expand(&[3, 2, 2]).unwrap();
+    /// let expected = Tensor::<i32>::new(Some(&[1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3]), &[3, 2, 2]).unwrap();
+    /// assert_eq!(c, expected);
+    ///
+    /// let a = Tensor::<i32>::new(Some(&[1, 2]), &[2]).unwrap();
+    /// let c = a.expand(&[3, 2]).unwrap();
+    /// let expected = Tensor::<i32>::new(Some(&[1, 2, 1, 2, 1, 2]), &[3, 2]).unwrap();
+    /// assert_eq!(c, expected);
+    /// ```
+    pub fn expand(&self, shape: &[usize]) -> Result<Tensor<T>, TensorError> {
+        if self.dims() == shape {
+            return Ok(self.clone());
+        }
+        if get_broadcasted_shape(self.dims(), shape)? != shape {
+            return Err(TensorError::DimMismatch("expand".to_string()));
+        }
+
+        // leading dims that self does not have
+        let offset = shape.len() - self.dims().len();
+        let mut res = Vec::with_capacity(shape.iter().product());
+        for coord in shape.iter().map(|d| 0..*d).multi_cartesian_product() {
+            let source = coord[offset..]
+                .iter()
+                .zip(self.dims())
+                .map(|(c, d)| if *d == 1 { 0 } else { *c })
+                .collect::<Vec<_>>();
+            res.push(self.get(&source));
+        }
+        Tensor::new(Some(&res), shape)
+    }
+
     /// Extends another tensor to rows
     /// ```
     /// use ezkl_lib::tensor::Tensor;
@@ -769,140 +846,93 @@ impl<T: Clone + TensorType> Tensor<T> {
         Ok(tiled)
     }
 
-    /// Multi-ch doubly blocked toeplitz matrix
+    /// Multi-ch toeplitz matrix for a kernel of shape `O x C/G x K_1 x ... x K_n` sliding over a
+    /// (padded) image of shape `C x P_1 x ... x P_n`, where `G` is the number of groups. Multiplying the
+    /// result by the flattened image yields the flattened convolution output of shape `O x S_1 x ... x S_n`.
     /// ```
     /// use ezkl_lib::tensor::Tensor;
-    /// let mut a = Tensor::<i32>::new(Some(&[1, 2, 3, 4, 0, 0, 0, 0]), &[1, 1, 2, 4]).unwrap();
-    /// let mut c = a.multi_ch_doubly_blocked_toeplitz(2, 2, 3, 4, 1, 1).unwrap();
+    /// let mut a = Tensor::<i32>::new(Some(&[1, 2]), &[1, 1, 2]).unwrap();
+    /// let mut c = a.multi_ch_toeplitz(&[4], &[1], &[1], 1).unwrap();
     /// let mut expected = Tensor::<i32>::new(
-    /// Some(&[1, 2, 3, 4, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0, 0, 0,
-    ///     1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0,
-    ///      0, 0, 1, 2]), &[6, 8]).unwrap();
+    /// Some(&[1, 2, 0, 0, 0, 1, 2, 0, 0, 0, 1, 2]), &[3, 4]).unwrap();
     /// assert_eq!(c, expected);
-    /// ```
-    pub fn multi_ch_doubly_blocked_toeplitz(
-        &self,
-        h_blocks: usize,
-        w_blocks: usize,
-        num_rows: usize,
-        num_cols: usize,
-        h_stride: usize,
-        w_stride: usize,
-    ) -> Result<Tensor<T>, TensorError> {
-        assert!(self.dims().len() > 2);
-        let first_channels = self.dims()[0];
-        let second_channels = self.dims()[1];
-
-        let mut toeplitz_tower = vec![];
-        for i in 0..first_channels {
-            let mut row = vec![
-                Tensor::new(None, &[h_blocks * num_rows, w_blocks * num_cols])?;
-                second_channels
-            ];
-            for (j, row_block) in row.iter_mut().enumerate().take(second_channels) {
-                let mut r = self.get_slice(&[i..i + 1, j..j + 1])?;
-                let dims = r.dims()[2..].to_vec();
-                r.reshape(&dims);
-                *row_block = r.doubly_blocked_toeplitz(
-                    h_blocks, w_blocks, num_rows, num_cols, h_stride, w_stride,
-                )?;
-            }
-            let mut concatenated_tensor = row[0].clone();
-            for r in row[1..].iter() {
-                concatenated_tensor = concatenated_tensor.append_to_row(r.clone())?;
-            }
-
-            toeplitz_tower.push(concatenated_tensor);
-        }
-
-        let mut toeplitz_tower =
-            Tensor::new(Some(&toeplitz_tower[..]), &[toeplitz_tower.len()])?.combine()?;
-
-        toeplitz_tower.reshape(&[
-            first_channels * h_blocks * num_rows,
-            second_channels * w_blocks * num_cols,
-        ]);
-
-        Ok(toeplitz_tower)
-    }
-
-    /// Doubly blocked toeplitz matrix
-    /// ```
-    /// use ezkl_lib::tensor::Tensor;
-    /// let mut a = Tensor::<i32>::new(Some(&[1, 2, 3, 4, 0, 0, 0, 0]), &[2, 4]).unwrap();
-    /// let mut c = a.doubly_blocked_toeplitz(2, 2, 3, 4, 1, 1).unwrap();
+    ///
+    /// let mut c = a.multi_ch_toeplitz(&[4], &[2], &[1], 1).unwrap();
     /// let mut expected = Tensor::<i32>::new(
-    /// Some(&[1, 2, 3, 4, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0, 0, 0,
-    ///     1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0,
-    ///      0, 0, 1, 2]), &[6, 8]).unwrap();
+    /// Some(&[1, 2, 0, 0, 0, 0, 1, 2]), &[2, 4]).unwrap();
+    /// assert_eq!(c, expected);
+    ///
+    /// let mut c = a.multi_ch_toeplitz(&[4], &[1], &[2], 1).unwrap();
+    /// let mut expected = Tensor::<i32>::new(
+    /// Some(&[1, 0, 2, 0, 0, 1, 0, 2]), &[2, 4]).unwrap();
+    /// assert_eq!(c, expected);
+    ///
+    /// let mut a = Tensor::<i32>::new(Some(&[1, 2]), &[2, 1, 1]).unwrap();
+    /// let mut c = a.multi_ch_toeplitz(&[2], &[1], &[1], 2).unwrap();
+    /// let mut expected = Tensor::<i32>::new(
+    /// Some(&[1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2]), &[4, 4]).unwrap();
     /// assert_eq!(c, expected);
     /// ```
-    pub fn doubly_blocked_toeplitz(
+    pub fn multi_ch_toeplitz(
         &self,
-        h_blocks: usize,
-        w_blocks: usize,
-        num_rows: usize,
-        num_cols: usize,
-        h_stride: usize,
-        _w_stride: usize,
+        padded_dims: &[usize],
+        stride: &[usize],
+        dilation: &[usize],
+        groups: usize,
     ) -> Result<Tensor<T>, TensorError> {
-        let mut t_matrices = vec![];
-        for i in 0..self.dims[0] {
-            t_matrices.push(self.toeplitz(i, num_rows, num_cols)?);
-        }
-
-        let mut doubly_blocked_toeplitz: Vec<Tensor<T>> = vec![];
-        for j in (0..h_stride * h_blocks).step_by(h_stride) {
-            let mut row = vec![Tensor::new(None, t_matrices[0].dims())?; w_blocks];
-            for i in 0..t_matrices.len() {
-                if i + j < w_blocks {
-                    row[i + j] = t_matrices[i].clone();
-                }
-            }
-
-            let mut concatenated_tensor = row[0].clone();
-            for r in row[1..].iter() {
-                concatenated_tensor = concatenated_tensor.append_to_row(r.clone())?;
-            }
-
-            doubly_blocked_toeplitz.push(concatenated_tensor);
+        if (self.dims().len() != padded_dims.len() + 2)
+            || (stride.len() != padded_dims.len())
+            || (dilation.len() != padded_dims.len())
+            || (groups == 0)
+            || (self.dims()[0] % groups != 0)
+        {
+            return Err(TensorError::DimMismatch("toeplitz".to_string()));
         }
+        let (first_channels, second_channels) = (self.dims()[0], self.dims()[1]);
+        let outputs_per_group = first_channels / groups;
+        let kernel_shape = &self.dims()[2..];
 
-        let mut doubly_blocked_toeplitz = Tensor::new(
-            Some(&doubly_blocked_toeplitz[..]),
-            &[doubly_blocked_toeplitz.len()],
-        )?
-        .combine()?;
-
-        doubly_blocked_toeplitz.reshape(&[h_blocks * num_rows, w_blocks * num_cols]);
-
-        if _w_stride > 1 {
-            let mut shifted_rows = vec![];
-            for r in 0..h_blocks * num_rows {
-                let offset = r % num_rows;
-                let row = doubly_blocked_toeplitz.get_slice(&[r..r + 1])?;
-
-                if offset > 0 {
-                    let mut shifted_row = Tensor::new(None, &[row.len()])?;
-                    let local_offset = offset * (_w_stride - 1);
-                    for i in 0..shifted_row.len() - local_offset {
-                        shifted_row.set(&[local_offset + i], row.get(&[0, i]).clone());
+        let slides = padded_dims
+            .iter()
+            .zip(kernel_shape)
+            .zip(stride.iter().zip(dilation))
+            .map(|((d, k), (s, dil))| (d - (k - 1) * dil - 1) / s + 1)
+            .collect::<Vec<_>>();
+        let num_slides = slides.iter().product::<usize>();
+        let padded_len = padded_dims.iter().product::<usize>();
+
+        let mut toeplitz = Tensor::new(
+            None,
+            &[
+                first_channels * num_slides,
+                groups * second_channels * padded_len,
+            ],
+        )?;
+
+        for (row, coord) in slides
+            .iter()
+            .map(|s| 0..*s)
+            .multi_cartesian_product()
+            .enumerate()
+        {
+            for kernel_coord in kernel_shape.iter().map(|k| 0..*k).multi_cartesian_product() {
+                // flattened index of the image element this kernel element is applied to
+                let col = (0..padded_dims.len()).fold(0, |acc, d| {
+                    acc * padded_dims[d] + coord[d] * stride[d] + kernel_coord[d] * dilation[d]
+                });
+                for i in 0..first_channels {
+                    let first_channel = (i / outputs_per_group) * second_channels;
+                    for j in 0..second_channels {
+                        toeplitz.set(
+                            &[i * num_slides + row, (first_channel + j) * padded_len + col],
+                            self.get(&[&[i, j], &kernel_coord[..]].concat()),
+                        );
                     }
-                    shifted_rows.push(shifted_row);
-                } else {
-                    shifted_rows.push(row);
                 }
             }
-
-            let mut doubly_blocked_toeplitz =
-                Tensor::new(Some(&shifted_rows[..]), &[shifted_rows.len()])?.combine()?;
-
-            doubly_blocked_toeplitz.reshape(&[h_blocks * num_rows, w_blocks * num_cols]);
-
-            Ok(doubly_blocked_toeplitz)
-        } else {
-            Ok(doubly_blocked_toeplitz)
         }
+
+        Ok(toeplitz)
     }
 
     /// Toeplitz matrix of a given row.
@@ -1013,56 +1043,18 @@ impl<T: TensorType + Add<Output = T>> Add for Tensor<T> {
     /// ).unwrap();
     /// let k = Tensor::<i32>::new(
     ///     Some(&[2, 3]),
-    ///     &[2]).unwrap();
+    ///     &[2, 1]).unwrap();
     /// let result = x.add(k).unwrap();
     /// let expected = Tensor::<i32>::new(Some(&[4, 3, 4, 4, 4, 4]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
     /// ```
     fn add(self, rhs: Self) -> Self::Output {
-        // calculate value of output
-        let mut output: Tensor<T> = self.clone();
+        let broadcasted_shape = get_broadcasted_shape(self.dims(), rhs.dims())?;
+        let mut output = self.expand(&broadcasted_shape)?;
+        let rhs = rhs.expand(&broadcasted_shape)?;
 
-        if self.len() != rhs.len() {
-            if self.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && rhs.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(rhs.dims()[0], self.dims().iter().product::<usize>());
-                let mut lhs = self.clone();
-                lhs.reshape(&[rhs.dims()[0]]);
-                lhs = self.repeat_rows(rhs.dims()[1..].iter().product::<usize>())?;
-                lhs.reshape(rhs.dims());
-                return lhs + rhs;
-            } else if rhs.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && self.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(self.dims()[0], rhs.dims().iter().product::<usize>());
-                let mut rhs = rhs.clone();
-                rhs.reshape(&[self.dims()[0]]);
-                rhs = rhs.repeat_rows(self.dims()[1..].iter().product::<usize>())?;
-                rhs.reshape(self.dims());
-                return self + rhs;
-            }
-            // casts a 1D addition
-            else if rhs.dims().len() == 1 && rhs.dims()[0] == 1 {
-                for i in 0..output.len() {
-                    output[i] = output[i].clone() + rhs[0].clone();
-                }
-            }
-            // make 1D casting commutative
-            else if self.dims().len() == 1 && self.dims()[0] == 1 {
-                output = rhs.clone();
-                for i in 0..rhs.len() {
-                    output[i] = output[i].clone() + self[0].clone();
-                }
-            } else {
-                return Err(TensorError::DimMismatch("add".to_string()));
-            }
-        } else {
-            for (i, e_i) in rhs.iter().enumerate() {
-                output[i] = output[i].clone() + e_i.clone()
-            }
+        for (i, e_i) in rhs.iter().enumerate() {
+            output[i] = output[i].clone() + e_i.clone()
         }
         Ok(output)
     }
@@ -1111,57 +1103,19 @@ impl<T: TensorType + Sub<Output = T>> Sub for Tensor<T> {
     /// ).unwrap();
     /// let k = Tensor::<i32>::new(
     ///     Some(&[2, 3]),
-    ///     &[2],
+    ///     &[2, 1],
     /// ).unwrap();
     /// let result = x.sub(k).unwrap();
     /// let expected = Tensor::<i32>::new(Some(&[0, -1, 0, -2, -2, -2]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
     /// ```
     fn sub(self, rhs: Self) -> Self::Output {
-        // calculate value of output
-        let mut output: Tensor<T> = self.clone();
+        let broadcasted_shape = get_broadcasted_shape(self.dims(), rhs.dims())?;
+        let mut output = self.expand(&broadcasted_shape)?;
+        let rhs = rhs.expand(&broadcasted_shape)?;
 
-        if self.len() != rhs.len() {
-            if self.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && rhs.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(rhs.dims()[0], self.dims().iter().product::<usize>());
-                let mut lhs = self.clone();
-                lhs.reshape(&[rhs.dims()[0]]);
-                lhs = self.repeat_rows(rhs.dims()[1..].iter().product::<usize>())?;
-                lhs.reshape(rhs.dims());
-                return lhs - rhs;
-            } else if rhs.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && self.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(self.dims()[0], rhs.dims().iter().product::<usize>());
-                let mut rhs = rhs.clone();
-                rhs.reshape(&[self.dims()[0]]);
-                rhs = rhs.repeat_rows(self.dims()[1..].iter().product::<usize>())?;
-                rhs.reshape(self.dims());
-                return self - rhs;
-            }
-            // casts a 1D addition
-            else if rhs.dims().len() == 1 && rhs.dims()[0] == 1 {
-                for i in 0..output.len() {
-                    output[i] = output[i].clone() - rhs[0].clone();
-                }
-            }
-            // make 1D casting commutative
-            else if self.dims().len() == 1 && self.dims()[0] == 1 {
-                output = rhs.clone();
-                for i in 0..rhs.len() {
-                    output[i] = self[0].clone() - output[i].clone();
-                }
-            } else {
-                return Err(TensorError::DimMismatch("sub".to_string()));
-            }
-        } else {
-            for (i, e_i) in rhs.iter().enumerate() {
-                output[i] = output[i].clone() - e_i.clone()
-            }
+        for (i, e_i) in rhs.iter().enumerate() {
+            output[i] = output[i].clone() - e_i.clone()
         }
         Ok(output)
     }
@@ -1209,56 +1163,18 @@ impl<T: TensorType + Mul<Output = T>> Mul for Tensor<T> {
     /// ).unwrap();
     /// let k = Tensor::<i32>::new(
     ///     Some(&[2, 2]),
-    ///     &[2]).unwrap();
+    ///     &[2, 1]).unwrap();
     /// let result = x.mul(k).unwrap();
     /// let expected = Tensor::<i32>::new(Some(&[4, 2, 4, 2, 2, 2]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
     /// ```
     fn mul(self, rhs: Self) -> Self::Output {
-        // calculate value of output
-        let mut output: Tensor<T> = self.clone();
+        let broadcasted_shape = get_broadcasted_shape(self.dims(), rhs.dims())?;
+        let mut output = self.expand(&broadcasted_shape)?;
+        let rhs = rhs.expand(&broadcasted_shape)?;
 
-        if self.len() != rhs.len() {
-            if self.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && rhs.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(rhs.dims()[0], self.dims().iter().product::<usize>());
-                let mut lhs = self.clone();
-                lhs.reshape(&[rhs.dims()[0]]);
-                lhs = self.repeat_rows(rhs.dims()[1..].iter().product::<usize>())?;
-                lhs.reshape(rhs.dims());
-                return lhs * rhs;
-            } else if rhs.dims().iter().map(|x| (x > &1) as usize).sum::<usize>() == 1
-                && self.dims().len() > 1
-                && self.dims() != rhs.dims()
-            {
-                assert_eq!(self.dims()[0], rhs.dims().iter().product::<usize>());
-                let mut rhs = rhs.clone();
-                rhs.reshape(&[self.dims()[0]]);
-                rhs = rhs.repeat_rows(self.dims()[1..].iter().product::<usize>())?;
-                rhs.reshape(self.dims());
-                return self * rhs;
-            }
-            // cast 1D mul
-            else if rhs.dims().len() == 1 && rhs.dims()[0] == 1 {
-                for i in 0..output.len() {
-                    output[i] = output[i].clone() * rhs[0].clone();
-                }
-            }
-            // make 1D casting commutative
-            else if self.dims().len() == 1 && self.dims()[0] == 1 {
-                output = rhs.clone();
-                for i in 0..rhs.len() {
-                    output[i] = output[i].clone() * self[0].clone();
-                }
-            } else {
-                return Err(TensorError::DimMismatch("mul".to_string()));
-            }
-        } else {
-            for (i, e_i) in rhs.iter().enumerate() {
-                output[i] = output[i].clone() * e_i.clone()
-            }
+        for (i, e_i) in rhs.iter().enumerate() {
+            output[i] = output[i].clone() * e_i.clone()
         }
         Ok(output)
     }
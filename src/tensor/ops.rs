@@ -1,5 +1,5 @@
 use super::TensorError;
-use crate::tensor::{Tensor, TensorType};
+use crate::tensor::{get_broadcasted_shape, Tensor, TensorType};
 use itertools::Itertools;
 use puruspe::erf;
 pub use std::ops::{Add, Div, Mul, Sub};
@@ -103,7 +103,8 @@ pub fn scale_and_shift<T: TensorType + Mul<Output = T> + Add<Output = T>>(
     Ok(output)
 }
 
-/// Matrix multiplies two 2D tensors.
+/// Matrix multiplies two tensors. Tensors with more than 2 dims are treated as stacks of matrices
+/// over their leading (batch) dims, which are broadcast against each other.
 /// # Arguments
 ///
 /// * `inputs` - Vector of tensors of length 2
@@ -123,6 +124,19 @@ pub fn scale_and_shift<T: TensorType + Mul<Output = T> + Add<Output = T>>(
 /// let result = matmul(&vec![k, x]).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[26, 7, 11, 3, 15, 3, 7, 2]), &[2, 4]).unwrap();
 /// assert_eq!(result, expected);
+///
+/// // a stack of 2 matrices multiplied by a single (broadcast) matrix
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6, 7, 8]),
+///     &[2, 2, 2],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 0, 1, 1]),
+///     &[2, 2],
+/// ).unwrap();
+/// let result = matmul(&vec![x, k]).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, 2, 7, 4, 11, 6, 15, 8]), &[2, 2, 2]).unwrap();
+/// assert_eq!(result, expected);
 /// ```
 pub fn matmul<T: TensorType + Mul<Output = T> + Add<Output = T>>(
     inputs: &[Tensor<T>],
@@ -136,13 +150,16 @@ pub fn matmul<T: TensorType + Mul<Output = T> + Add<Output = T>>(
         b.reshape(&[b.dims()[0], 1]);
     }
 
-    if (inputs.len() != 2)
-        || (a.dims()[a.dims().len() - 1] != b.dims()[a.dims().len() - 2])
-        || (a.dims()[0..a.dims().len() - 2] != b.dims()[0..a.dims().len() - 2])
-    {
+    let (a_rank, b_rank) = (a.dims().len(), b.dims().len());
+    if (inputs.len() != 2) || (a.dims()[a_rank - 1] != b.dims()[b_rank - 2]) {
         return Err(TensorError::DimMismatch("matmul".to_string()));
     }
 
+    // the batch dims are broadcast
+    let batch_dims = get_broadcasted_shape(&a.dims()[..a_rank - 2], &b.dims()[..b_rank - 2])?;
+    a = a.expand(&[&batch_dims[..], &a.dims()[a_rank - 2..]].concat())?;
+    b = b.expand(&[&batch_dims[..], &b.dims()[b_rank - 2..]].concat())?;
+
     let mut dims = Vec::from(&a.dims()[0..a.dims().len() - 2]);
     dims.push(a.dims()[a.dims().len() - 2]);
     dims.push(b.dims()[a.dims().len() - 1]);
@@ -300,6 +317,378 @@ pub fn mult<T: TensorType + Mul<Output = T>>(t: &[Tensor<T>]) -> Result<Tensor<T
     Ok(output)
 }
 
+/// Elementwise compares two tensors, returning one where `a > b` and zero otherwise. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::greater;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = greater(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 0, 1, 0, 0, 0]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn greater<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    compare(a, b, |a_i, b_i| a_i > b_i)
+}
+
+/// Elementwise compares two tensors, returning one where `a >= b` and zero otherwise. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::greater_equal;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = greater_equal(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 1, 1, 0, 0, 0]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn greater_equal<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    compare(a, b, |a_i, b_i| a_i >= b_i)
+}
+
+/// Elementwise compares two tensors, returning one where `a < b` and zero otherwise. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::less;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = less(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 0, 0, 1, 1, 1]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn less<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    compare(a, b, |a_i, b_i| a_i < b_i)
+}
+
+/// Elementwise compares two tensors, returning one where `a <= b` and zero otherwise. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::less_equal;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = less_equal(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 0, 1, 1, 1]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn less_equal<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    compare(a, b, |a_i, b_i| a_i <= b_i)
+}
+
+/// Elementwise compares two tensors, returning one where `a == b` and zero otherwise. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::equal;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = equal(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 0, 0, 0, 0]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn equal<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    compare(a, b, |a_i, b_i| a_i == b_i)
+}
+
+/// Elementwise applies `cmp` to two tensors broadcast to a common shape, returning one where it holds and zero otherwise.
+fn compare<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+    cmp: impl Fn(&T, &T) -> bool,
+) -> Result<Tensor<T>, TensorError> {
+    let shape = get_broadcasted_shape(a.dims(), b.dims())?;
+    let (a, b) = (a.expand(&shape)?, b.expand(&shape)?);
+
+    let mut output = a.clone();
+    for (i, (a_i, b_i)) in a.iter().zip(b.iter()).enumerate() {
+        output[i] = match cmp(a_i, b_i) {
+            true => T::one().unwrap(),
+            false => T::zero().unwrap(),
+        };
+    }
+    Ok(output)
+}
+
+/// Elementwise logical and of two tensors, where any non-zero element is true. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::and;
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 0, 1, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1, 0, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let result = and(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 0, 0, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn and(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |a_i, b_i| *a_i != 0 && *b_i != 0)
+}
+
+/// Elementwise logical or of two tensors, where any non-zero element is true. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::or;
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 0, 1, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1, 0, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let result = or(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 1, 1, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn or(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |a_i, b_i| *a_i != 0 || *b_i != 0)
+}
+
+/// Elementwise logical xor of two tensors, where any non-zero element is true. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::xor;
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 0, 1, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1, 0, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let result = xor(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 1, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn xor(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |a_i, b_i| (*a_i != 0) != (*b_i != 0))
+}
+
+/// Elementwise logical not of a tensor, where any non-zero element is true.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::not;
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 0, 1, 0]),
+///     &[2, 2],
+/// ).unwrap();
+/// let result = not(&x);
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 0, 1]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn not(a: &Tensor<i128>) -> Tensor<i128> {
+    a.map(|a_i| (a_i == 0) as i128)
+}
+
+/// Elementwise selects from `a` where `mask` is non-zero and from `b` elsewhere. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `mask` - Tensor
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::iff;
+/// let mask = Tensor::<i128>::new(
+///     Some(&[1, 0]),
+///     &[2, 1],
+/// ).unwrap();
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[7]),
+///     &[1],
+/// ).unwrap();
+/// let result = iff(&mask, &x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[2, 1, 2, 7, 7, 7]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn iff<T: TensorType>(
+    mask: &Tensor<i128>,
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    let shape = get_broadcasted_shape(a.dims(), b.dims())?;
+    let shape = get_broadcasted_shape(mask.dims(), &shape)?;
+    let (mask, a, b) = (mask.expand(&shape)?, a.expand(&shape)?, b.expand(&shape)?);
+
+    let mut output = a.clone();
+    for (i, m) in mask.iter().enumerate() {
+        if *m == 0 {
+            output[i] = b[i].clone();
+        }
+    }
+    Ok(output)
+}
+
+/// Elementwise max of two tensors. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::eltwise_max;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2, 0]),
+///     &[3],
+/// ).unwrap();
+/// let result = eltwise_max(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[2, 2, 2, 1, 2, 1]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn eltwise_max<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    let shape = get_broadcasted_shape(a.dims(), b.dims())?;
+    let (a, b) = (a.expand(&shape)?, b.expand(&shape)?);
+
+    let mut output = a.clone();
+    for (i, b_i) in b.iter().enumerate() {
+        if *b_i > output[i] {
+            output[i] = b_i.clone();
+        }
+    }
+    Ok(output)
+}
+
+/// Elementwise min of two tensors. The inputs are broadcast to a common shape.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::eltwise_min;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 1, 2, 1, 1, 1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2, 0]),
+///     &[3],
+/// ).unwrap();
+/// let result = eltwise_min(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 1, 0, 1, 1, 0]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn eltwise_min<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<Tensor<T>, TensorError> {
+    let shape = get_broadcasted_shape(a.dims(), b.dims())?;
+    let (a, b) = (a.expand(&shape)?, b.expand(&shape)?);
+
+    let mut output = a.clone();
+    for (i, b_i) in b.iter().enumerate() {
+        if *b_i < output[i] {
+            output[i] = b_i.clone();
+        }
+    }
+    Ok(output)
+}
+
 /// Rescale a tensor with a const integer (similar to const_mult).
 /// # Arguments
 ///
@@ -356,12 +745,16 @@ pub fn sum<T: TensorType + Add<Output = T>>(a: &Tensor<T>) -> Result<Tensor<T>,
     Tensor::new(Some(&[res]), &[1])
 }
 
-/// Applies convolution over a 3D tensor of shape C x H x W (and adds a bias).
+/// Applies convolution over a tensor of shape C x D_1 x ... x D_n (and adds a bias).
+/// The number of spatial dimensions n is inferred from the kernel, which has shape O x C/G x K_1 x ... x K_n
+/// where G is the number of groups.
 /// # Arguments
 ///
 /// * `inputs` - A vector of tensors holding in order: input image, convolution kernel, convolution bias.
-/// * `padding` - Tuple of padding values in x and y directions.
-/// * `stride` - Tuple of stride values in x and y directions.
+/// * `padding` - The (before, after) padding of each spatial dimension.
+/// * `stride` - Stride values for each spatial dimension.
+/// * `dilation` - Dilation values for each spatial dimension.
+/// * `groups` - Number of groups the input and output channels are split into.
 /// # Examples
 /// ```
 /// use ezkl_lib::tensor::Tensor;
@@ -379,74 +772,140 @@ pub fn sum<T: TensorType + Add<Output = T>>(a: &Tensor<T>) -> Result<Tensor<T>,
 ///     Some(&[0]),
 ///     &[1],
 /// ).unwrap();
-/// let result = convolution::<i128>(&[x, k, b], (0, 0), (1, 1)).unwrap();
+/// let result = convolution::<i128>(&[x, k, b], &[(0, 0), (0, 0)], &[1, 1], &[1, 1], 1).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[31, 16, 8, 26]), &[1, 2, 2]).unwrap();
 /// assert_eq!(result, expected);
-/// ```
-pub fn convolution<T: TensorType + Mul<Output = T> + Add<Output = T>>(
-    inputs: &[Tensor<T>],
-    padding: (usize, usize),
-    stride: (usize, usize),
-) -> Result<Tensor<T>, TensorError> {
-    let has_bias = inputs.len() == 3;
-    let (image, kernel) = (inputs[0].clone(), inputs[1].clone());
-
-    if (image.dims().len() != 3)
-        || (kernel.dims().len() != 4)
-        || (image.dims()[0] != kernel.dims()[1])
-    {
-        return Err(TensorError::DimMismatch("conv".to_string()));
-    }
-
-    if has_bias {
-        let bias = inputs[2].clone();
-        if (bias.dims().len() != 1) || (bias.dims()[0] != kernel.dims()[0]) {
-            return Err(TensorError::DimMismatch("conv bias".to_string()));
-        }
+///
+/// // 1D convolution
+/// let x = Tensor::<i128>::new(
+///     Some(&[5, 2, 3, 0, 4, -1]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4]),
+///     &[1, 2, 2],
+/// ).unwrap();
+/// let result = convolution::<i128>(&[x, k], &[(1, 1)], &[2], &[1], 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[10, 16]), &[1, 2]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // dilated 1D convolution
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5]),
+///     &[1, 5],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1]),
+///     &[1, 1, 2],
+/// ).unwrap();
+/// let result = convolution::<i128>(&[x, k], &[(0, 0)], &[1], &[2], 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[4, 6, 8]), &[1, 3]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // depthwise 1D convolution
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1, 2, 0]),
+///     &[2, 1, 2],
+/// ).unwrap();
+/// let result = convolution::<i128>(&[x, k], &[(0, 0)], &[1], &[1], 2).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, 5, 8, 10]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // asymmetrically padded 1D convolution
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3]),
+///     &[1, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[1, 1]),
+///     &[1, 1, 2],
+/// ).unwrap();
+/// let result = convolution::<i128>(&[x, k], &[(1, 0)], &[1], &[1], 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 3, 5]), &[1, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn convolution<T: TensorType + Mul<Output = T> + Add<Output = T>>(
+    inputs: &[Tensor<T>],
+    padding: &[(usize, usize)],
+    stride: &[usize],
+    dilation: &[usize],
+    groups: usize,
+) -> Result<Tensor<T>, TensorError> {
+    let has_bias = inputs.len() == 3;
+    let (image, kernel) = (inputs[0].clone(), inputs[1].clone());
+
+    if (kernel.dims().len() < 3)
+        || (image.dims().len() != kernel.dims().len() - 1)
+        || (padding.len() != kernel.dims().len() - 2)
+        || (stride.len() != kernel.dims().len() - 2)
+        || (dilation.len() != kernel.dims().len() - 2)
+    {
+        return Err(TensorError::DimMismatch("conv".to_string()));
     }
 
-    let image_dims = image.dims();
-    let kernel_dims = kernel.dims();
+    if (groups == 0)
+        || (image.dims()[0] != kernel.dims()[1] * groups)
+        || (kernel.dims()[0] % groups != 0)
+    {
+        return Err(TensorError::DimMismatch("conv groups".to_string()));
+    }
+
+    if has_bias {
+        let bias = inputs[2].clone();
+        if (bias.dims().len() != 1) || (bias.dims()[0] != kernel.dims()[0]) {
+            return Err(TensorError::DimMismatch("conv bias".to_string()));
+        }
+    }
 
-    let (output_channels, input_channels, kernel_height, kernel_width) = (
-        kernel_dims[0],
-        kernel_dims[1],
-        kernel_dims[2],
-        kernel_dims[3],
-    );
+    let kernel_dims = kernel.dims();
 
-    let (image_height, image_width) = (image_dims[1], image_dims[2]);
+    let (output_channels, group_channels) = (kernel_dims[0], kernel_dims[1]);
+    let outputs_per_group = output_channels / groups;
+    let kernel_shape = &kernel_dims[2..];
 
-    let padded_image = pad::<T>(&image, padding)?;
+    let padding = [(0, 0)]
+        .into_iter()
+        .chain(padding.iter().copied())
+        .collect::<Vec<_>>();
+    let padded_image = pad::<T>(&image, &padding, &PadMode::Constant(T::zero().unwrap()))?;
 
-    let vert_slides = (image_height + 2 * padding.0 - kernel_height) / stride.0 + 1;
-    let horz_slides = (image_width + 2 * padding.1 - kernel_width) / stride.1 + 1;
+    let slides = padded_image.dims()[1..]
+        .iter()
+        .zip(kernel_shape)
+        .zip(stride.iter().zip(dilation))
+        .map(|((d, k), (s, dil))| (d - (k - 1) * dil - 1) / s + 1)
+        .collect::<Vec<_>>();
 
     // calculate value of output
     let mut output: Tensor<T> =
-        Tensor::new(None, &[output_channels, vert_slides, horz_slides]).unwrap();
+        Tensor::new(None, &[&[output_channels], &slides[..]].concat()).unwrap();
 
     for i in 0..output_channels {
-        for j in 0..vert_slides {
-            let rs = j * stride.0;
-            for k in 0..horz_slides {
-                let cs = k * stride.1;
-                let mut res = dot(&vec![
-                    &kernel.get_slice(&[i..i + 1])?.clone(),
-                    &padded_image.get_slice(&[
-                        0..input_channels,
-                        rs..(rs + kernel_height),
-                        cs..(cs + kernel_width),
-                    ])?,
-                ])?;
-
-                if has_bias {
-                    // increment result by the bias
-                    res[0] = res[0].clone() + inputs[2][i].clone();
+        let first_channel = (i / outputs_per_group) * group_channels;
+        for coord in slides.iter().map(|s| 0..*s).multi_cartesian_product() {
+            let mut res = T::zero().unwrap();
+            for j in 0..group_channels {
+                for kernel_coord in kernel_shape.iter().map(|k| 0..*k).multi_cartesian_product() {
+                    let mut image_coord = vec![first_channel + j];
+                    for (d, c) in coord.iter().enumerate() {
+                        image_coord.push(c * stride[d] + kernel_coord[d] * dilation[d]);
+                    }
+                    res = res
+                        + kernel.get(&[&[i, j], &kernel_coord[..]].concat())
+                            * padded_image.get(&image_coord);
                 }
+            }
 
-                output.set(&[i, j, k], res[0].clone());
+            if has_bias {
+                // increment result by the bias
+                res = res + inputs[2][i].clone();
             }
+
+            output.set(&[&[i], &coord[..]].concat(), res);
         }
     }
     Ok(output)
@@ -491,7 +950,11 @@ pub fn sumpool<T: TensorType + Mul<Output = T> + Add<Output = T>>(
     let (output_channels, kernel_height, kernel_width) =
         (image_channels, kernel_shape.0, kernel_shape.1);
 
-    let padded_image = pad::<T>(image, padding)?;
+    let padded_image = pad::<T>(
+        image,
+        &[(0, 0), (padding.0, padding.0), (padding.1, padding.1)],
+        &PadMode::Constant(T::zero().unwrap()),
+    )?;
 
     let vert_slides = (image_height + 2 * padding.0 - kernel_height) / stride.0 + 1;
     let horz_slides = (image_width + 2 * padding.1 - kernel_width) / stride.1 + 1;
@@ -517,11 +980,173 @@ pub fn sumpool<T: TensorType + Mul<Output = T> + Add<Output = T>>(
     Ok(output)
 }
 
+/// Returns the number of windows a 2D pool slides along a dim of length `len`.
+/// With `ceil_mode` the last window may run over the end of the padded dim, as long as it starts
+/// within the input or its leading padding (the onnx and pytorch convention).
+fn pool_output_len(
+    len: usize,
+    padding: (usize, usize),
+    stride: usize,
+    kernel: usize,
+    ceil_mode: bool,
+) -> Result<usize, TensorError> {
+    let padded = len + padding.0 + padding.1;
+    if padded < kernel || stride == 0 {
+        return Err(TensorError::DimMismatch("pool".to_string()));
+    }
+    let span = padded - kernel;
+    if !ceil_mode {
+        return Ok(span / stride + 1);
+    }
+    let mut num_windows = (span + stride - 1) / stride + 1;
+    if (num_windows - 1) * stride >= len + padding.0 {
+        num_windows -= 1;
+    }
+    Ok(num_windows)
+}
+
+/// Returns the number of elements averaged over by each window along a dim of length `len`.
+fn avg_pool_counts(
+    len: usize,
+    padding: (usize, usize),
+    stride: usize,
+    kernel: usize,
+    ceil_mode: bool,
+    count_include_pad: bool,
+) -> Result<Vec<usize>, TensorError> {
+    // bounds of the counted region, relative to the start of the input
+    let (lo, hi) = if count_include_pad {
+        (-(padding.0 as isize), (len + padding.1) as isize)
+    } else {
+        (0, len as isize)
+    };
+    Ok(
+        (0..pool_output_len(len, padding, stride, kernel, ceil_mode)?)
+            .map(|i| {
+                let start = (i * stride) as isize - padding.0 as isize;
+                let end = start + kernel as isize;
+                // windows which only cover padding are averaged over a single (zero) element
+                (end.min(hi) - start.max(lo)).max(1) as usize
+            })
+            .collect(),
+    )
+}
+
+/// Returns the padding (before, after) each spatial dim of a C x H x W tensor needs such that sum pooling the padded
+/// tensor without padding yields the windows of a 2D average pool, along with the divisor of each window
+/// as a tensor of shape `[out_h, out_w]`.
+/// # Arguments
+///
+/// * `image_dims` - Height and width of the pooled tensor.
+/// * `padding` - Padding (before, after) in the y and x directions.
+/// * `stride` - Tuple of stride values in y and x directions.
+/// * `kernel_shape` - Tuple of pooling window size in y and x directions.
+/// * `ceil_mode` - Whether partial windows at the end of a dim produce an output.
+/// * `count_include_pad` - Whether padded elements count towards the divisor.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::avg_pool2d_geometry;
+///
+/// let (padding, divisors) = avg_pool2d_geometry((3, 3), [(0, 0), (0, 0)], (2, 2), (2, 2), true, false).unwrap();
+/// assert_eq!(padding, [(0, 1), (0, 1)]);
+/// assert_eq!(divisors, Tensor::<usize>::new(Some(&[4, 2, 2, 1]), &[2, 2]).unwrap());
+/// ```
+pub fn avg_pool2d_geometry(
+    image_dims: (usize, usize),
+    padding: [(usize, usize); 2],
+    stride: (usize, usize),
+    kernel_shape: (usize, usize),
+    ceil_mode: bool,
+    count_include_pad: bool,
+) -> Result<([(usize, usize); 2], Tensor<usize>), TensorError> {
+    let dims = [
+        (image_dims.0, padding[0], stride.0, kernel_shape.0),
+        (image_dims.1, padding[1], stride.1, kernel_shape.1),
+    ];
+
+    let mut counts = vec![];
+    let mut sum_padding = [(0, 0); 2];
+    for (i, (len, pad, stride, kernel)) in dims.into_iter().enumerate() {
+        let c = avg_pool_counts(len, pad, stride, kernel, ceil_mode, count_include_pad)?;
+        // the trailing padding needed to fit the last window
+        let end = (c.len() - 1) * stride + kernel;
+        sum_padding[i] = (pad.0, end.saturating_sub(len + pad.0));
+        counts.push(c);
+    }
+
+    let divisors = counts[0]
+        .iter()
+        .cartesian_product(counts[1].iter())
+        .map(|(h, w)| h * w)
+        .collect::<Vec<_>>();
+    let divisors = Tensor::new(Some(&divisors), &[counts[0].len(), counts[1].len()])?;
+    Ok((sum_padding, divisors))
+}
+
+/// Applies 2D average pooling over a 3D tensor of shape C x H x W.
+/// Each window sum is divided by the number of elements in the window (see [avg_pool2d_geometry]).
+/// # Arguments
+///
+/// * `image` - Tensor.
+/// * `padding` - Padding (before, after) in the y and x directions.
+/// * `stride` - Tuple of stride values in y and x directions.
+/// * `kernel_shape` - Tuple of pooling window size in y and x directions.
+/// * `ceil_mode` - Whether partial windows at the end of a dim produce an output.
+/// * `count_include_pad` - Whether padded elements count towards the divisor.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::avg_pool2d;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[4, 2, 6, 0, 4, 2, 3, 1, 8]),
+///     &[1, 3, 3],
+/// ).unwrap();
+/// let pooled = avg_pool2d(&x, [(0, 0), (0, 0)], (2, 2), (2, 2), true, false).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, 4, 2, 8]), &[1, 2, 2]).unwrap();
+/// assert_eq!(pooled, expected);
+/// ```
+pub fn avg_pool2d(
+    image: &Tensor<i128>,
+    padding: [(usize, usize); 2],
+    stride: (usize, usize),
+    kernel_shape: (usize, usize),
+    ceil_mode: bool,
+    count_include_pad: bool,
+) -> Result<Tensor<i128>, TensorError> {
+    if image.dims().len() != 3 {
+        return Err(TensorError::DimMismatch("avg pool".to_string()));
+    }
+    let (sum_padding, divisors) = avg_pool2d_geometry(
+        (image.dims()[1], image.dims()[2]),
+        padding,
+        stride,
+        kernel_shape,
+        ceil_mode,
+        count_include_pad,
+    )?;
+
+    let padded = pad(
+        image,
+        &[(0, 0), sum_padding[0], sum_padding[1]],
+        &PadMode::Constant(0),
+    )?;
+    let sums = sumpool(&padded, (0, 0), stride, kernel_shape)?;
+
+    let mut output = sums.clone();
+    for (i, s) in sums.iter().enumerate() {
+        let d = divisors[i % divisors.len()];
+        output[i] = nonlinearities::const_div(&Tensor::from([*s].into_iter()), d as f32)[0];
+    }
+    Ok(output)
+}
+
 /// Applies 2D max pooling over a 3D tensor of shape C x H x W.
 /// # Arguments
 ///
 /// * `image` - Tensor.
-/// * `padding` - Tuple of padding values in x and y directions.
+/// * `padding` - The (before, after) padding of the image in the x and y directions.
 /// * `stride` - Tuple of stride values in x and y directions.
 /// * `pool_dims` - Tuple of pooling window size in x and y directions.
 /// # Examples
@@ -536,13 +1161,18 @@ pub fn sumpool<T: TensorType + Mul<Output = T> + Add<Output = T>>(
 ///     Some(&[5, 2, 3, 0, 4, -1, 3, 1, 6]),
 ///     &[1, 3, 3],
 /// ).unwrap();
-/// let pooled = max_pool2d::<i128>(&x, &(0, 0), &(1, 1), &(2, 2)).unwrap();
+/// let pooled = max_pool2d::<i128>(&x, &[(0, 0), (0, 0)], &(1, 1), &(2, 2)).unwrap();
 /// let expected: Tensor<i128> = Tensor::<i128>::new(Some(&[5, 4, 4, 6]), &[1, 2, 2]).unwrap();
 /// assert_eq!(pooled, expected);
+///
+/// // only padded after the image
+/// let pooled = max_pool2d::<i128>(&x, &[(0, 1), (0, 1)], &(2, 2), &(2, 2)).unwrap();
+/// let expected: Tensor<i128> = Tensor::<i128>::new(Some(&[5, 3, 3, 6]), &[1, 2, 2]).unwrap();
+/// assert_eq!(pooled, expected);
 /// ```
 pub fn max_pool2d<T: TensorType>(
     image: &Tensor<T>,
-    padding: &(usize, usize),
+    padding: &[(usize, usize); 2],
     stride: &(usize, usize),
     pool_dims: &(usize, usize),
 ) -> Result<Tensor<T>, TensorError> {
@@ -554,10 +1184,14 @@ pub fn max_pool2d<T: TensorType>(
     let input_channels = image_dims[0];
     let (image_height, image_width) = (image_dims[1], image_dims[2]);
 
-    let padded_image = pad::<T>(image, *padding)?;
+    let padded_image = pad::<T>(
+        image,
+        &[(0, 0), padding[0], padding[1]],
+        &PadMode::Constant(T::zero().unwrap()),
+    )?;
 
-    let horz_slides = (image_height + 2 * padding.0 - pool_dims.0) / stride.0 + 1;
-    let vert_slides = (image_width + 2 * padding.1 - pool_dims.1) / stride.1 + 1;
+    let horz_slides = (image_height + padding[0].0 + padding[0].1 - pool_dims.0) / stride.0 + 1;
+    let vert_slides = (image_width + padding[1].0 + padding[1].1 - pool_dims.1) / stride.1 + 1;
 
     let mut output: Tensor<T> =
         Tensor::new(None, &[input_channels, horz_slides, vert_slides]).unwrap();
@@ -621,103 +1255,676 @@ pub fn dot<T: TensorType + Mul<Output = T> + Add<Output = T>>(
     Tensor::new(Some(&[res]), &[1])
 }
 
-/// Pads a 3D tensor of shape `C x H x W` to a tensor of shape `C x (H + 2xPADDING) x (W + 2xPADDING)` using 0 values.
+/// The values used to fill the border of a padded tensor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PadMode<T: TensorType> {
+    /// Fills the border with a constant value.
+    Constant(T),
+    /// Mirrors the tensor about its edges, excluding the edge values themselves.
+    Reflect,
+    /// Repeats the edge values of the tensor.
+    Edge,
+}
+
+/// Pads each axis of a tensor by the (before, after) amounts specified in `padding`.
 /// # Arguments
 ///
 /// * `image` - Tensor.
-/// * `padding` - Tuple of padding values in x and y directions.
+/// * `padding` - Amount of padding before and after each axis of the tensor.
+/// * `mode` - How the border is filled.
 /// # Examples
 /// ```
 /// use ezkl_lib::tensor::Tensor;
-/// use ezkl_lib::tensor::ops::pad;
+/// use ezkl_lib::tensor::ops::{pad, PadMode};
 ///
 /// let x = Tensor::<i128>::new(
 ///     Some(&[5, 2, 3, 0, 4, -1, 3, 1, 6]),
 ///     &[1, 3, 3],
 /// ).unwrap();
-/// let result = pad::<i128>(&x, (1, 1)).unwrap();
+/// let result = pad::<i128>(&x, &[(0, 0), (1, 1), (1, 1)], &PadMode::Constant(0)).unwrap();
 /// let expected = Tensor::<i128>::new(
 ///     Some(&[0, 0, 0, 0, 0, 0, 5, 2, 3, 0, 0, 0, 4, -1, 0, 0, 3, 1, 6, 0, 0, 0, 0, 0, 0]),
 ///     &[1, 5, 5],
 /// ).unwrap();
 /// assert_eq!(result, expected);
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4]),
+///     &[1, 4],
+/// ).unwrap();
+/// let result = pad::<i128>(&x, &[(0, 0), (2, 1)], &PadMode::Constant(9)).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[9, 9, 1, 2, 3, 4, 9]), &[1, 7]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// let result = pad::<i128>(&x, &[(0, 0), (2, 1)], &PadMode::Reflect).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, 2, 1, 2, 3, 4, 3]), &[1, 7]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// let result = pad::<i128>(&x, &[(0, 0), (2, 1)], &PadMode::Edge).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 1, 1, 2, 3, 4, 4]), &[1, 7]).unwrap();
+/// assert_eq!(result, expected);
 /// ```
 pub fn pad<T: TensorType>(
     image: &Tensor<T>,
-    padding: (usize, usize),
+    padding: &[(usize, usize)],
+    mode: &PadMode<T>,
 ) -> Result<Tensor<T>, TensorError> {
-    if image.dims().len() != 3 {
+    if image.dims().len() != padding.len() {
         return Err(TensorError::DimMismatch("pad".to_string()));
     }
-    let (channels, height, width) = (image.dims()[0], image.dims()[1], image.dims()[2]);
-    let padded_height = height + 2 * padding.0;
-    let padded_width = width + 2 * padding.1;
 
-    let mut output = Tensor::<T>::new(None, &[channels, padded_height, padded_width]).unwrap();
+    for (d, (before, after)) in image.dims().iter().zip(padding) {
+        let max_pad = match mode {
+            PadMode::Constant(_) => continue,
+            // reflection can't extend further than the axis itself
+            PadMode::Reflect => d.saturating_sub(1),
+            // edge padding needs at least one value to repeat
+            PadMode::Edge if *d == 0 => 0,
+            PadMode::Edge => usize::MAX,
+        };
+        if (*before > max_pad) || (*after > max_pad) {
+            return Err(TensorError::DimMismatch("pad mode".to_string()));
+        }
+    }
+
+    let padded_dims = image
+        .dims()
+        .iter()
+        .zip(padding)
+        .map(|(d, (before, after))| d + before + after)
+        .collect::<Vec<_>>();
+
+    let mut output = Tensor::<T>::new(None, &padded_dims).unwrap();
+
+    for coord in padded_dims.iter().map(|d| 0..*d).multi_cartesian_product() {
+        let mut in_border = false;
+        let mut image_coord = vec![];
+        for ((c, (before, _)), d) in coord.iter().zip(padding).zip(image.dims()) {
+            let (i, d) = (*c as isize - *before as isize, *d as isize);
+            let i = if (i < 0) || (i >= d) {
+                in_border = true;
+                match mode {
+                    PadMode::Constant(_) => 0,
+                    PadMode::Reflect if i < 0 => -i,
+                    PadMode::Reflect => 2 * (d - 1) - i,
+                    PadMode::Edge => i.clamp(0, d - 1),
+                }
+            } else {
+                i
+            };
+            image_coord.push(i as usize);
+        }
+        match mode {
+            PadMode::Constant(v) if in_border => output.set(&coord, v.clone()),
+            _ => output.set(&coord, image.get(&image_coord)),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Permutes the axes of a tensor, such that axis `i` of the output is axis `perm[i]` of the input.
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `perm` - The permutation of the axes.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::permute_axes;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[1, 2, 3],
+/// ).unwrap();
+/// let result = permute_axes::<i128>(&x, &[2, 0, 1]).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 4, 2, 5, 3, 6]), &[3, 1, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn permute_axes<T: TensorType>(
+    a: &Tensor<T>,
+    perm: &[usize],
+) -> Result<Tensor<T>, TensorError> {
+    let rank = a.dims().len();
+    if (perm.len() != rank) || !(0..rank).all(|i| perm.contains(&i)) {
+        return Err(TensorError::DimMismatch("permute axes".to_string()));
+    }
+
+    let permuted_dims = perm.iter().map(|p| a.dims()[*p]).collect::<Vec<_>>();
+    let mut output = Tensor::<T>::new(None, &permuted_dims)?;
+
+    for coord in permuted_dims
+        .iter()
+        .map(|d| 0..*d)
+        .multi_cartesian_product()
+    {
+        let mut a_coord = vec![0; rank];
+        for (c, p) in coord.iter().zip(perm) {
+            a_coord[*p] = *c;
+        }
+        output.set(&coord, a.get(&a_coord));
+    }
+
+    Ok(output)
+}
+
+/// Returns the permutation which moves `axes` (in ascending order) behind all other axes of a tensor of rank `rank`,
+/// along with its inverse.
+/// # Arguments
+///
+/// * `rank` - The rank of the tensor.
+/// * `axes` - The axes to move last.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::ops::axes_last_permutation;
+///
+/// let (perm, inverse) = axes_last_permutation(3, &[0]).unwrap();
+/// assert_eq!(perm, vec![1, 2, 0]);
+/// assert_eq!(inverse, vec![2, 0, 1]);
+/// ```
+pub fn axes_last_permutation(
+    rank: usize,
+    axes: &[usize],
+) -> Result<(Vec<usize>, Vec<usize>), TensorError> {
+    if axes.is_empty() || axes.iter().any(|a| *a >= rank) {
+        return Err(TensorError::DimMismatch(
+            "axes last permutation".to_string(),
+        ));
+    }
+    let perm = (0..rank)
+        .filter(|i| !axes.contains(i))
+        .chain((0..rank).filter(|i| axes.contains(i)))
+        .collect::<Vec<_>>();
+    let mut inverse = vec![0; rank];
+    for (i, p) in perm.iter().enumerate() {
+        inverse[*p] = i;
+    }
+    Ok((perm, inverse))
+}
+
+/// Returns the index of the largest element along `axis`, which is kept as a dimension of size 1.
+/// Ties resolve to the first such index, or to the last if `select_last` is set.
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `axis` - The axis to reduce.
+/// * `select_last` - Whether ties resolve to the last index.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::argmax;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 7, 7, 5, -1, 3]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = argmax(&x, 1, false).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 0]), &[2, 1]).unwrap();
+/// assert_eq!(result, expected);
+/// let result = argmax(&x, 1, true).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[2, 0]), &[2, 1]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn argmax(
+    a: &Tensor<i128>,
+    axis: usize,
+    select_last: bool,
+) -> Result<Tensor<i128>, TensorError> {
+    arg_extremum(a, axis, select_last, |x, best| x > best)
+}
+
+/// Returns the index of the smallest element along `axis`, which is kept as a dimension of size 1.
+/// Ties resolve to the first such index, or to the last if `select_last` is set.
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `axis` - The axis to reduce.
+/// * `select_last` - Whether ties resolve to the last index.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::argmin;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 7, 2, 5, -1, 3]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = argmin(&x, 0, false).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 0]), &[1, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn argmin(
+    a: &Tensor<i128>,
+    axis: usize,
+    select_last: bool,
+) -> Result<Tensor<i128>, TensorError> {
+    arg_extremum(a, axis, select_last, |x, best| x < best)
+}
+
+/// Returns the index along `axis` of the element that is `better` than all others.
+fn arg_extremum(
+    a: &Tensor<i128>,
+    axis: usize,
+    select_last: bool,
+    better: impl Fn(i128, i128) -> bool,
+) -> Result<Tensor<i128>, TensorError> {
+    let (perm, inverse) = axes_last_permutation(a.dims().len(), &[axis])?;
+    let len = a.dims()[axis];
+    if len == 0 {
+        return Err(TensorError::DimMismatch("arg extremum".to_string()));
+    }
+    let mut permuted = permute_axes(a, &perm)?;
+    let mut out_dims = permuted.dims().to_vec();
+    *out_dims.last_mut().unwrap() = 1;
+    let num_rows = permuted.len() / len;
+    permuted.reshape(&[num_rows, len]);
+
+    let mut indices = vec![];
+    for i in 0..num_rows {
+        let row = permuted.get_slice(&[i..i + 1])?;
+        let mut best = 0;
+        for (j, x) in row.iter().enumerate() {
+            if better(*x, row[best]) || (select_last && *x == row[best]) {
+                best = j;
+            }
+        }
+        indices.push(best as i128);
+    }
+
+    let mut output = Tensor::new(Some(&indices), &[num_rows])?;
+    output.reshape(&out_dims);
+    permute_axes(&output, &inverse)
+}
+
+/// Concatenates tensors along an axis. All tensors must share the same dims, except along `axis`.
+/// # Arguments
+///
+/// * `inputs` - Tensors to concatenate.
+/// * `axis` - The axis to concatenate along.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::concat;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4]),
+///     &[2, 2],
+/// ).unwrap();
+/// let y = Tensor::<i128>::new(
+///     Some(&[5, 6]),
+///     &[2, 1],
+/// ).unwrap();
+/// let result = concat::<i128>(&[x, y], 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 2, 5, 3, 4, 6]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn concat<T: TensorType>(inputs: &[Tensor<T>], axis: usize) -> Result<Tensor<T>, TensorError> {
+    if inputs.is_empty() || (axis >= inputs[0].dims().len()) {
+        return Err(TensorError::DimMismatch("concat".to_string()));
+    }
+    let dims = inputs[0].dims();
+    for input in inputs {
+        if (input.dims().len() != dims.len())
+            || (input.dims()[..axis] != dims[..axis])
+            || (input.dims()[axis + 1..] != dims[axis + 1..])
+        {
+            return Err(TensorError::DimMismatch("concat".to_string()));
+        }
+    }
+
+    let mut output_dims = dims.to_vec();
+    output_dims[axis] = inputs.iter().map(|i| i.dims()[axis]).sum();
+
+    // each input contributes a contiguous block per index of the leading axes
+    let outer = dims[..axis].iter().product::<usize>();
+    let mut res = vec![];
+    for i in 0..outer {
+        for input in inputs {
+            let block = input.dims()[axis..].iter().product::<usize>();
+            res.extend_from_slice(&input[i * block..(i + 1) * block]);
+        }
+    }
+
+    Tensor::new(Some(&res), &output_dims)
+}
+
+/// Slices a tensor along an axis, keeping the elements with index in `start..end`.
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `axis` - The axis to slice.
+/// * `start` - The first index to keep.
+/// * `end` - One past the last index to keep.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::slice;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = slice::<i128>(&x, 1, 1, 3).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[2, 3, 5, 6]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn slice<T: TensorType>(
+    a: &Tensor<T>,
+    axis: usize,
+    start: usize,
+    end: usize,
+) -> Result<Tensor<T>, TensorError> {
+    if (axis >= a.dims().len()) || (start > end) || (end > a.dims()[axis]) {
+        return Err(TensorError::DimMismatch("slice".to_string()));
+    }
+    let mut ranges = a.dims().iter().map(|d| 0..*d).collect::<Vec<_>>();
+    ranges[axis] = start..end;
+    a.get_slice(&ranges)
+}
+
+/// Gathers the elements of a tensor along an axis, at the specified indices.
+/// The output has dims `a.dims()[..axis] ++ indices.dims() ++ a.dims()[axis + 1..]`.
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `indices` - The indices to gather along `axis`.
+/// * `axis` - The axis to gather along.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::gather;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[2, 3],
+/// ).unwrap();
+/// let indices = Tensor::<usize>::new(Some(&[2, 0]), &[2]).unwrap();
+/// let result = gather::<i128>(&x, &indices, 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, 1, 6, 4]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// let indices = Tensor::<usize>::new(Some(&[1]), &[]).unwrap();
+/// let result = gather::<i128>(&x, &indices, 0).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[4, 5, 6]), &[3]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn gather<T: TensorType>(
+    a: &Tensor<T>,
+    indices: &Tensor<usize>,
+    axis: usize,
+) -> Result<Tensor<T>, TensorError> {
+    if (axis >= a.dims().len()) || indices.iter().any(|i| *i >= a.dims()[axis]) {
+        return Err(TensorError::DimMismatch("gather".to_string()));
+    }
+
+    let outer = a.dims()[..axis].iter().product::<usize>();
+    let inner = a.dims()[axis + 1..].iter().product::<usize>();
+    let mut res = vec![];
+    for i in 0..outer {
+        for index in indices.iter() {
+            let start = (i * a.dims()[axis] + index) * inner;
+            res.extend_from_slice(&a[start..start + inner]);
+        }
+    }
+
+    let output_dims = [&a.dims()[..axis], indices.dims(), &a.dims()[axis + 1..]].concat();
+    Tensor::new(Some(&res), &output_dims)
+}
+
+/// Looks up the rows of an embedding table at indices which are themselves tensor elements, i.e `out[i] = table[indices[i]]`.
+/// The output has dims `indices.dims() ++ table.dims()[1..]`.
+/// # Arguments
+///
+/// * `table` - Tensor whose first axis is indexed.
+/// * `indices` - The fixed point indices, which are rounded to the nearest integer index.
+/// * `index_scale` - The fixed point multiplier of `indices`.
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::embedding;
+///
+/// let table = Tensor::<i128>::new(
+///     Some(&[1, 2, 3, 4, 5, 6]),
+///     &[3, 2],
+/// ).unwrap();
+/// let indices = Tensor::<i128>::new(Some(&[4, 0, 4]), &[3]).unwrap();
+/// let result = embedding(&table, &indices, 2).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[5, 6, 1, 2, 5, 6]), &[3, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn embedding(
+    table: &Tensor<i128>,
+    indices: &Tensor<i128>,
+    index_scale: usize,
+) -> Result<Tensor<i128>, TensorError> {
+    let indices = nonlinearities::const_div(indices, index_scale as f32);
+    if indices.iter().any(|i| *i < 0) {
+        return Err(TensorError::DimMismatch("embedding".to_string()));
+    }
+    let indices = indices.map(|i| i as usize);
+    gather(table, &indices, 0)
+}
+
+/// Packs a multi-dim tensor into a single elem tensor
+/// # Arguments
+///
+/// * `a` - Tensor.
+/// * `base` - Base to use when packing
+/// * `scale` - fixed point representation scale
+/// # Examples
+/// ```
+/// use ezkl_lib::tensor::Tensor;
+/// use ezkl_lib::tensor::ops::pack;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[5, 2, 1]),
+///     &[1, 3],
+/// ).unwrap();
+/// let result = pack::<i128>(&x, 2, 2).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[90]),
+///     &[1],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn pack<T: TensorType>(a: &Tensor<T>, base: T, scale: u32) -> Result<Tensor<T>, TensorError>
+where
+    T: Add<Output = T>,
+    T: Mul<Output = T>,
+{
+    // base ^ (scale + tensor)
+    let mut output = T::zero().unwrap();
+    let base_tensor = Tensor::new(Some(&[base]), &[1])?;
+    for (i, a_i) in a.iter().enumerate() {
+        let pow_value = &base_tensor.pow((i as u32) * (scale + 1))?[0];
+        output = output + pow_value.clone() * a_i.clone();
+    }
+    Tensor::new(Some(&[output]), &[1])
+}
+
+// ---------------------------------------------------------------------------------------------------------
+// -- nonlinear Functions ---------------------------------------------------------------------------------
+// ---------------------------------------------------------------------------------------------------------
+// ---------------------------------------------------------------------------------------------------------
+// ---------------------------------------------------------------------------------------------------------
+// ---------------------------------------------------------------------------------------------------------
+
+/// Activation functions
+pub mod nonlinearities {
+    use super::*;
+    /// Elementwise applies sigmoid to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::sigmoid;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 15, 2, 1, 1, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = sigmoid(&x, 1, 1);
+    /// let expected = Tensor::<i128>::new(Some(&[1, 1, 1, 1, 1, 1]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn sigmoid(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) / (1.0 + (-kix).exp());
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies the (exact, erf based) gaussian error linear unit to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::gelu;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-8, -4, 0, 4, 8, 16]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = gelu(&x, 4, 4);
+    /// let expected = Tensor::<i128>::new(Some(&[0, -1, 0, 3, 8, 16]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn gelu(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let cdf = 0.5 * (1.0 + erf((kix / std::f32::consts::SQRT_2) as f64) as f32);
+            let fout = (scale_output as f32) * kix * cdf;
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies the sigmoid linear unit (swish), `x * sigmoid(x)`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::silu;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-8, -4, 0, 4, 8, 16]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = silu(&x, 4, 4);
+    /// let expected = Tensor::<i128>::new(Some(&[-1, -1, 0, 3, 7, 16]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn silu(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * kix / (1.0 + (-kix).exp());
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies hard sigmoid, `max(0, min(1, alpha * x + beta))`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `alpha` - Single value
+    /// * `beta` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::hard_sigmoid;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-16, -4, 0, 4, 8, 16]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = hard_sigmoid(&x, 4, 12, 1.0 / 6.0, 0.5);
+    /// let expected = Tensor::<i128>::new(Some(&[0, 4, 6, 8, 10, 12]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn hard_sigmoid(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        alpha: f32,
+        beta: f32,
+    ) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * (alpha * kix + beta).clamp(0.0, 1.0);
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise clamps a tensor of integers to `[min, max]`.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `min` - Single value
+    /// * `max` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::clip;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-16, -4, 0, 4, 8, 16]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = clip(&x, 4, 4, 0.0, 2.5);
+    /// let expected = Tensor::<i128>::new(Some(&[0, 0, 0, 4, 8, 10]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn clip(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        min: f32,
+        max: f32,
+    ) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
 
-    for channel in 0..channels {
-        for row in 0..height {
-            for col in 0..width {
-                output.set(
-                    &[channel, row + padding.0, col + padding.1],
-                    image.get(&[channel, row, col]).clone(),
-                );
-            }
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * kix.clamp(min, max);
+            let rounded = fout.round();
+            output[i] = rounded as i128;
         }
+        output
     }
 
-    output.reshape(&[channels, padded_height, padded_width]);
-    Ok(output)
-}
-
-/// Packs a multi-dim tensor into a single elem tensor
-/// # Arguments
-///
-/// * `a` - Tensor.
-/// * `base` - Base to use when packing
-/// * `scale` - fixed point representation scale
-/// # Examples
-/// ```
-/// use ezkl_lib::tensor::Tensor;
-/// use ezkl_lib::tensor::ops::pack;
-///
-/// let x = Tensor::<i128>::new(
-///     Some(&[5, 2, 1]),
-///     &[1, 3],
-/// ).unwrap();
-/// let result = pack::<i128>(&x, 2, 2).unwrap();
-/// let expected = Tensor::<i128>::new(
-///     Some(&[90]),
-///     &[1],
-/// ).unwrap();
-/// assert_eq!(result, expected);
-/// ```
-pub fn pack<T: TensorType>(a: &Tensor<T>, base: T, scale: u32) -> Result<Tensor<T>, TensorError>
-where
-    T: Add<Output = T>,
-    T: Mul<Output = T>,
-{
-    // base ^ (scale + tensor)
-    let mut output = T::zero().unwrap();
-    let base_tensor = Tensor::new(Some(&[base]), &[1])?;
-    for (i, a_i) in a.iter().enumerate() {
-        let pow_value = &base_tensor.pow((i as u32) * (scale + 1))?[0];
-        output = output + pow_value.clone() * a_i.clone();
-    }
-    Tensor::new(Some(&[output]), &[1])
-}
-
-// ---------------------------------------------------------------------------------------------------------
-// -- nonlinear Functions ---------------------------------------------------------------------------------
-// ---------------------------------------------------------------------------------------------------------
-// ---------------------------------------------------------------------------------------------------------
-// ---------------------------------------------------------------------------------------------------------
-// ---------------------------------------------------------------------------------------------------------
-
-/// Activation functions
-pub mod nonlinearities {
-    use super::*;
-    /// Elementwise applies sigmoid to a tensor of integers.
+    /// Elementwise applies hard swish, `x * max(0, min(1, x / 6 + 1 / 2))`, to a tensor of integers.
     /// # Arguments
     ///
     /// * `a` - Tensor
@@ -726,22 +1933,22 @@ pub mod nonlinearities {
     /// # Examples
     /// ```
     /// use ezkl_lib::tensor::Tensor;
-    /// use ezkl_lib::tensor::ops::nonlinearities::sigmoid;
+    /// use ezkl_lib::tensor::ops::nonlinearities::hard_swish;
     /// let x = Tensor::<i128>::new(
-    ///     Some(&[2, 15, 2, 1, 1, 0]),
+    ///     Some(&[-16, -4, 0, 4, 8, 16]),
     ///     &[2, 3],
     /// ).unwrap();
-    /// let result = sigmoid(&x, 1, 1);
-    /// let expected = Tensor::<i128>::new(Some(&[1, 1, 1, 1, 1, 1]), &[2, 3]).unwrap();
+    /// let result = hard_swish(&x, 4, 4);
+    /// let expected = Tensor::<i128>::new(Some(&[0, -1, 0, 3, 7, 16]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
     /// ```
-    pub fn sigmoid(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+    pub fn hard_swish(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
         // calculate value of output
         let mut output: Tensor<i128> = a.clone();
 
         for (i, a_i) in a.iter().enumerate() {
             let kix = (*a_i as f32) / (scale_input as f32);
-            let fout = (scale_output as f32) / (1.0 + (-kix).exp());
+            let fout = (scale_output as f32) * kix * (kix / 6.0 + 0.5).clamp(0.0, 1.0);
             let rounded = fout.round();
             output[i] = rounded as i128;
         }
@@ -873,6 +2080,149 @@ pub mod nonlinearities {
         output
     }
 
+    /// Elementwise applies exponential to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::exp;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 5, 2, 1, 1, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = exp(&x, 1, 1);
+    /// let expected = Tensor::<i128>::new(Some(&[7, 148, 7, 3, 3, 1]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn exp(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * kix.exp();
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies reciprocal to a tensor of integers. Zero is mapped to zero.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::recip;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[1, 2, 4, 8, 16, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = recip(&x, 1, 16);
+    /// let expected = Tensor::<i128>::new(Some(&[16, 8, 4, 2, 1, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn recip(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            if *a_i == 0 {
+                output[i] = 0;
+                continue;
+            }
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) / kix;
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies natural logarithm to a tensor of integers. Non-positive values are mapped to zero.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::ln;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[1, 2, 4, 8, 16, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = ln(&x, 4, 4);
+    /// let expected = Tensor::<i128>::new(Some(&[-6, -3, 0, 3, 6, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn ln(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            if *a_i <= 0 {
+                output[i] = 0;
+                continue;
+            }
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * kix.ln();
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise raises a tensor of integers to a (possibly fractional) power.
+    /// Negative values, for which a fractional power is undefined, are mapped to zero.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `exponent` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::pow;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[4, 25, 8, 1, -1, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = pow(&x, 1, 1, 1.5);
+    /// let expected = Tensor::<i128>::new(Some(&[8, 125, 23, 1, 0, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn pow(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        exponent: f32,
+    ) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f32) / (scale_input as f32);
+            let fout = (scale_output as f32) * kix.powf(exponent);
+            if !fout.is_finite() {
+                output[i] = 0;
+                continue;
+            }
+            let rounded = fout.round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
     /// Elementwise applies leaky relu to a tensor of integers.
     /// # Arguments
     ///
@@ -907,12 +2257,13 @@ pub mod nonlinearities {
         output
     }
 
-    /// Elementwise applies instance norm to a tensor of integers.
+    /// Applies instance norm to a `[C, H, W]` tensor of integers, normalising each channel by its own mean and variance.
+    /// The input and `gamma` are at scale `scale` whereas `beta` and the output are at scale `scale * scale`.
     /// # Arguments
     ///
-    /// * `a` - Tensor
-    /// * `gamma` - vector of scale values
-    /// * `beta` - vector of offset values
+    /// * `inputs` - input, per channel `gamma` and per channel `beta`
+    /// * `scale` - Single value
+    /// * `epsilon` - Single value
     /// # Examples
     /// ```
     /// use ezkl_lib::tensor::Tensor;
@@ -923,7 +2274,7 @@ pub mod nonlinearities {
     /// ).unwrap();
     ///
     /// let gamma = Tensor::<i128>::new(
-    ///     Some(&[1]),
+    ///     Some(&[4]),
     ///     &[1],
     /// ).unwrap();
     ///
@@ -932,11 +2283,11 @@ pub mod nonlinearities {
     ///     &[1],
     /// ).unwrap();
     ///
-    /// let result = instance_norm([x, gamma, beta], 1.0);
-    /// let expected = Tensor::<i128>::new(Some(&[25, 23, 29, 22, 22, 23, 23, 23, 24]), &[1, 3, 3]).unwrap();
+    /// let result = instance_norm([x, gamma, beta], 4, 1e-5);
+    /// let expected = Tensor::<i128>::new(Some(&[29, 17, 53, 11, 11, 17, 17, 17, 23]), &[1, 3, 3]).unwrap();
     /// assert_eq!(result, expected);
     /// ```
-    pub fn instance_norm(inputs: [Tensor<i128>; 3], epsilon: f32) -> Tensor<i128> {
+    pub fn instance_norm(inputs: [Tensor<i128>; 3], scale: usize, epsilon: f32) -> Tensor<i128> {
         let a = &inputs[0];
         let gamma = &inputs[1];
         let beta = &inputs[2];
@@ -946,29 +2297,154 @@ pub mod nonlinearities {
         assert_eq!(gamma.len(), beta.len());
         // assert num channels is same as num of parameters
         assert_eq!(gamma.len(), a.dims()[0]);
-        let mut output = vec![];
+        let mut diffs = vec![];
+        let mut vars = vec![];
         for i in 0..gamma.len() {
             let row = a.get_slice(&[i..i + 1]).unwrap();
-            let sum = sum(&row).unwrap();
-            let mean = sum.map(|x| (x) / (row.len() as i128));
+            let row_mean = mean(&row, 1)[0];
+            let diff = row.map(|e| e - row_mean);
 
             // unbiased = false in pytorch definition. if it was unbiased we would divide by row.len() - 1
-            let var = (row.clone() - mean.clone())
-                .unwrap()
-                .map(|e| (e as f32) / (row.len() as f32));
+            let var = mean(&(diff.clone() * diff.clone()).unwrap(), scale);
+
+            diffs.push(diff);
+            vars.push(var);
+        }
+
+        let mut diff = Tensor::from(diffs.into_iter()).combine().unwrap();
+        diff.reshape(a.dims());
+        let var = Tensor::from(vars.into_iter()).combine().unwrap();
+
+        normalise(&diff, &var, gamma, beta, scale, epsilon)
+    }
+
+    /// Applies inference mode batch norm to a `[C, H, W]` tensor of integers, using fixed per channel statistics.
+    /// The input, `gamma`, the running mean and the running variance are at scale `scale` whereas `beta` and the output are at scale `scale * scale`.
+    /// # Arguments
+    ///
+    /// * `inputs` - input, per channel `gamma`, `beta`, running mean and running variance
+    /// * `scale` - Single value
+    /// * `epsilon` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::batch_norm;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[4, 8, 2, 6]),
+    ///     &[2, 1, 2],
+    /// ).unwrap();
+    /// let gamma = Tensor::<i128>::new(Some(&[4, 4]), &[2]).unwrap();
+    /// let beta = Tensor::<i128>::new(Some(&[0, 16]), &[2]).unwrap();
+    /// let mean = Tensor::<i128>::new(Some(&[6, 4]), &[2]).unwrap();
+    /// let var = Tensor::<i128>::new(Some(&[4, 4]), &[2]).unwrap();
+    ///
+    /// let result = batch_norm([x, gamma, beta, mean, var], 4, 1e-5);
+    /// let expected = Tensor::<i128>::new(Some(&[-8, 8, 8, 24]), &[2, 1, 2]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn batch_norm(inputs: [Tensor<i128>; 5], scale: usize, epsilon: f32) -> Tensor<i128> {
+        let a = &inputs[0];
+        let gamma = &inputs[1];
+        let beta = &inputs[2];
+        let running_mean = &inputs[3];
+        let running_var = &inputs[4];
+        // assert num channels is same as num of parameters
+        for param in inputs[1..].iter() {
+            assert_eq!(param.len(), a.dims()[0]);
+        }
+
+        let per_channel = a.len() / running_mean.len();
+        let mut diff = a.clone();
+        for (i, a_i) in a.iter().enumerate() {
+            diff[i] = a_i - running_mean[i / per_channel];
+        }
 
-            let denom = var.map(|e| (e + epsilon).sqrt().round() as i128);
-            let numerator = (row - mean).unwrap() * vec![gamma[i]].into_iter().into();
+        normalise(&diff, running_var, gamma, beta, scale, epsilon)
+    }
 
-            let result =
-                ((numerator.unwrap() / denom).unwrap() + vec![beta[i]].into_iter().into()).unwrap();
+    /// Applies layer norm to a tensor of integers, normalising each slice spanned by `axes` by its own mean and variance.
+    /// The input and `gamma` are at scale `scale` whereas `beta` and the output are at scale `scale * scale`.
+    /// `gamma` and `beta` either hold a single value or one value per element of the normalised slice.
+    /// # Arguments
+    ///
+    /// * `inputs` - input, `gamma` and `beta`
+    /// * `axes` - The axes to normalise over
+    /// * `scale` - Single value
+    /// * `epsilon` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::layer_norm;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 6, 4, 4]),
+    ///     &[2, 2],
+    /// ).unwrap();
+    /// let gamma = Tensor::<i128>::new(Some(&[4, 4]), &[2]).unwrap();
+    /// let beta = Tensor::<i128>::new(Some(&[0, 16]), &[2]).unwrap();
+    ///
+    /// let result = layer_norm([x, gamma, beta], &[1], 4, 1e-5);
+    /// let expected = Tensor::<i128>::new(Some(&[-12, 28, 0, 16]), &[2, 2]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn layer_norm(
+        inputs: [Tensor<i128>; 3],
+        axes: &[usize],
+        scale: usize,
+        epsilon: f32,
+    ) -> Tensor<i128> {
+        let a = &inputs[0];
+        let (perm, inverse) = axes_last_permutation(a.dims().len(), axes).unwrap();
+        let group_len = axes.iter().map(|ax| a.dims()[*ax]).product::<usize>();
+        let num_groups = a.len() / group_len;
+
+        let mut gamma = inputs[1].clone();
+        let mut beta = inputs[2].clone();
+        for param in [&mut gamma, &mut beta] {
+            assert!(param.len() == 1 || param.len() == group_len);
+            param.reshape(&[param.len()]);
+        }
 
-            output.push(result);
+        let mut permuted = permute_axes(a, &perm).unwrap();
+        let permuted_dims = permuted.dims().to_vec();
+        permuted.reshape(&[num_groups, group_len]);
+
+        // epsilon is rounded up so that the rsqrt never sees a zero variance
+        let eps = (epsilon * scale as f32).ceil() as i128;
+        let mut rows = vec![];
+        for i in 0..num_groups {
+            let row = permuted.get_slice(&[i..i + 1]).unwrap();
+            let row_mean = mean(&row, 1)[0];
+            let diff = row.map(|e| e - row_mean);
+            let var = mean(&(diff.clone() * diff.clone()).unwrap(), scale);
+            let inv_std = rsqrt(&var.map(|v| v + eps), scale, scale)[0];
+            let normalised = const_div(&diff.map(|d| d * inv_std), scale as f32);
+            rows.push(((normalised * gamma.clone()).unwrap() + beta.clone()).unwrap());
         }
 
-        let mut output = Tensor::from(output.into_iter()).combine().unwrap();
-        output.reshape(a.dims());
+        let mut output = Tensor::from(rows.into_iter()).combine().unwrap();
+        output.reshape(&permuted_dims);
+        permute_axes(&output, &inverse).unwrap()
+    }
 
+    /// Scales the centred channels of `diff` by `gamma / sqrt(var + epsilon)` and shifts them by `beta`.
+    fn normalise(
+        diff: &Tensor<i128>,
+        var: &Tensor<i128>,
+        gamma: &Tensor<i128>,
+        beta: &Tensor<i128>,
+        scale: usize,
+        epsilon: f32,
+    ) -> Tensor<i128> {
+        // epsilon is rounded up so that the rsqrt never sees a zero variance
+        let eps = (epsilon * scale as f32).ceil() as i128;
+        let inv_std = rsqrt(&var.map(|v| v + eps), scale, scale);
+        let k = const_div(&(inv_std * gamma.clone()).unwrap(), scale as f32);
+
+        let per_channel = diff.len() / k.len();
+        let mut output = diff.clone();
+        for (i, d_i) in diff.iter().enumerate() {
+            output[i] = d_i * k[i / per_channel] + beta[i / per_channel];
+        }
         output
     }
 
@@ -1063,6 +2539,126 @@ pub mod nonlinearities {
         let sum = sum(a).unwrap();
         const_div(&sum, (scale * a.len()) as f32)
     }
+
+    /// The scale the reciprocal of a softmax row sum is quantized at. This is the largest power of two multiple
+    /// of `scale_output` for which the products of the exponentials (at most `scale_output`) and the reciprocal
+    /// stay within `bits`-wide lookup tables, so that they can be rescaled to `scale_output * scale_output`.
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::ops::nonlinearities::softmax_recip_scale;
+    /// assert_eq!(softmax_recip_scale(128, 16), 128);
+    /// assert_eq!(softmax_recip_scale(128, 20), 2048);
+    /// assert_eq!(softmax_recip_scale(8, 8), 8);
+    /// ```
+    pub fn softmax_recip_scale(scale_output: usize, bits: usize) -> usize {
+        let bound = 1usize << (bits.max(2) - 2);
+        let mut scale_recip = scale_output;
+        while scale_output * scale_recip * 2 <= bound {
+            scale_recip *= 2;
+        }
+        scale_recip
+    }
+
+    /// Applies softmax along the last axis of a tensor of integers.
+    /// The max of each row is subtracted before exponentiating, so every row sums to at least `scale_output`.
+    /// The exponentials are then multiplied by the reciprocal of their sum at scale `scale_recip` (see
+    /// [softmax_recip_scale]) and rescaled, such that the output is at scale `scale_output * scale_output`,
+    /// matching the circuit layout.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `scale_recip` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::softmax;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 2, 3, 2, 2, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = softmax(&x, 1, 128, 256);
+    /// let expected = Tensor::<i128>::new(Some(&[3478, 3478, 9472, 7680, 7680, 1020]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn softmax(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        scale_recip: usize,
+    ) -> Tensor<i128> {
+        normalise_exponentials(a, None, scale_input, scale_output, scale_recip)
+    }
+
+    /// Applies softmax along the last axis of a tensor of integers, only over the positions where `mask` is 1.
+    /// Masked positions (where `mask` is 0) are excluded from the normalisation and are 0 in the output.
+    /// `mask` is broadcast to the dims of `a`.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `mask` - Tensor of 0s and 1s
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `scale_recip` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl_lib::tensor::Tensor;
+    /// use ezkl_lib::tensor::ops::nonlinearities::masked_softmax;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 2, 3, 2, 2, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let mask = Tensor::<i128>::new(Some(&[1, 1, 0]), &[3]).unwrap();
+    /// let result = masked_softmax(&x, &mask, 1, 128, 256);
+    /// let expected = Tensor::<i128>::new(Some(&[8202, 8202, 0, 8192, 8192, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn masked_softmax(
+        a: &Tensor<i128>,
+        mask: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        scale_recip: usize,
+    ) -> Tensor<i128> {
+        normalise_exponentials(a, Some(mask), scale_input, scale_output, scale_recip)
+    }
+
+    /// Multiplies the exponentials of each row (along the last axis) of `a`, shifted by the row's max,
+    /// by the quantized reciprocal of their sum.
+    fn normalise_exponentials(
+        a: &Tensor<i128>,
+        mask: Option<&Tensor<i128>>,
+        scale_input: usize,
+        scale_output: usize,
+        scale_recip: usize,
+    ) -> Tensor<i128> {
+        let row_len = a.dims().last().copied().unwrap_or(1).max(1);
+        let mut shifted = a.clone();
+        for (i, row) in a.chunks(row_len).enumerate() {
+            let row_max = row.iter().max().copied().unwrap_or(0);
+            for j in 0..row_len {
+                shifted[i * row_len + j] = row[j] - row_max;
+            }
+        }
+        let mut exps = exp(&shifted, scale_input, scale_output);
+        if let Some(mask) = mask {
+            exps = (exps * mask.clone()).unwrap();
+        }
+
+        let mut output = exps.clone();
+        for (i, row) in exps.chunks(row_len).enumerate() {
+            let denom: Tensor<i128> = vec![row.iter().sum::<i128>()].into_iter().into();
+            let inv_denom = recip(&denom, scale_output, scale_recip)[0];
+            for j in 0..row_len {
+                output[i * row_len + j] = row[j] * inv_denom;
+            }
+        }
+        if scale_recip != scale_output {
+            output = const_div(&output, (scale_recip / scale_output) as f32);
+        }
+        output
+    }
 }
 
 /// Ops that return the transcript i.e intermediate calcs of an op
@@ -1,4 +1,7 @@
-use super::{ops::pad, *};
+use super::{
+    ops::{concat, gather, pad, permute_axes, slice, PadMode},
+    *,
+};
 use halo2_proofs::{arithmetic::Field, plonk::Instance};
 use log::warn;
 
@@ -327,6 +330,22 @@ impl<F: FieldExt + TensorType> ValTensor<F> {
         Ok(())
     }
 
+    /// Calls `expand` on the inner [Tensor].
+    pub fn expand(&mut self, shape: &[usize]) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = v.expand(shape)?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
     /// Calls `duplicate_every_n` on the inner [Tensor].
     pub fn duplicate_every_n(
         &mut self,
@@ -363,13 +382,17 @@ impl<F: FieldExt + TensorType> ValTensor<F> {
         Ok(())
     }
 
-    /// Calls `tile` on the inner [Tensor].
-    pub fn pad(&mut self, padding: (usize, usize)) -> Result<(), TensorError> {
+    /// Calls `pad` on the inner [Tensor].
+    pub fn pad(
+        &mut self,
+        padding: &[(usize, usize)],
+        mode: &PadMode<ValType<F>>,
+    ) -> Result<(), TensorError> {
         match self {
             ValTensor::Value {
                 inner: v, dims: d, ..
             } => {
-                *v = pad(v, padding)?;
+                *v = pad(v, padding, mode)?;
                 *d = v.dims().to_vec();
             }
             ValTensor::Instance { .. } => {
@@ -425,23 +448,19 @@ impl<F: FieldExt + TensorType> ValTensor<F> {
         Ok(())
     }
 
-    /// Calls `multi_ch_doubly_blocked_toeplitz` on the inner [Tensor].
-    pub fn multi_ch_blocked_toeplitz(
+    /// Calls `multi_ch_toeplitz` on the inner [Tensor].
+    pub fn multi_ch_toeplitz(
         &mut self,
-        h_blocks: usize,
-        w_blocks: usize,
-        num_rows: usize,
-        num_cols: usize,
-        h_stride: usize,
-        w_stride: usize,
+        padded_dims: &[usize],
+        stride: &[usize],
+        dilation: &[usize],
+        groups: usize,
     ) -> Result<(), TensorError> {
         match self {
             ValTensor::Value {
                 inner: v, dims: d, ..
             } => {
-                *v = v.multi_ch_doubly_blocked_toeplitz(
-                    h_blocks, w_blocks, num_rows, num_cols, h_stride, w_stride,
-                )?;
+                *v = v.multi_ch_toeplitz(padded_dims, stride, dilation, groups)?;
                 *d = v.dims().to_vec();
             }
             ValTensor::Instance { .. } => {
@@ -474,6 +493,66 @@ impl<F: FieldExt + TensorType> ValTensor<F> {
         Ok(res)
     }
 
+    /// Calls `concat` on the inner [Tensor]s.
+    pub fn concat_axis(inputs: &[Self], axis: usize) -> Result<Self, TensorError> {
+        let mut inner = vec![];
+        for input in inputs {
+            match input {
+                ValTensor::Value { inner: v, .. } => inner.push(v.clone()),
+                ValTensor::Instance { .. } => return Err(TensorError::WrongMethod),
+            }
+        }
+        Ok(concat(&inner, axis)?.into())
+    }
+
+    /// Calls `permute_axes` on the inner [Tensor].
+    pub fn permute_axes(&mut self, perm: &[usize]) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = permute_axes(v, perm)?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `slice` on the inner [Tensor].
+    pub fn slice(&mut self, axis: usize, start: usize, end: usize) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = slice(v, axis, start, end)?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `gather` on the inner [Tensor].
+    pub fn gather(&mut self, indices: &Tensor<usize>, axis: usize) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = gather(v, indices, axis)?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the `dims` attribute of the [ValTensor].
     pub fn dims(&self) -> &[usize] {
         match self {
@@ -40,7 +40,7 @@ fn init() {
     assert!(status.success());
 }
 
-const TESTS: [&str; 28] = [
+const TESTS: [&str; 37] = [
     "1l_mlp",
     "1l_flatten",
     "1l_average",
@@ -71,6 +71,15 @@ const TESTS: [&str; 28] = [
     "min",
     "max",
     "1l_max_pool",
+    "1l_transpose",
+    "1l_concat",
+    "1l_slice",
+    "1l_gather",
+    "1l_lstm",
+    "1l_argmax",
+    "1l_gru",
+    "1l_rnn",
+    "1l_lstm_hidden",
 ];
 
 const PACKING_TESTS: [&str; 14] = [
@@ -144,6 +153,17 @@ const TESTS_EVM: [&str; 18] = [
 
 const EXAMPLES: [&str; 2] = ["mlp_4d", "conv2d_mnist"];
 
+/// Models whose `input.json` holds the float outputs of the original model, which the quantized
+/// forward pass is checked against.
+const ACCURACY_TESTS: [&str; 2] = ["1l_average", "1l_avg_pool_ceil"];
+
+/// Max absolute difference between the quantized and float outputs of [ACCURACY_TESTS].
+const ACCURACY_TOL: f64 = 0.02;
+
+/// Models the calibrated quantization is tested on: lookups, convolutions, and the scans unrolled
+/// from recurrent layers (whose unrolled nodes are profiled like any other).
+const CALIBRATION_TESTS: [&str; 4] = ["1l_sigmoid", "2l_relu_sigmoid_conv", "1l_lstm", "1l_gru"];
+
 macro_rules! test_func_aggr {
     () => {
         #[cfg(test)]
@@ -203,17 +223,36 @@ macro_rules! test_func {
             use crate::mock_public_inputs;
             use crate::mock_public_params;
             use crate::forward_pass;
+            use crate::accuracy;
             use crate::kzg_prove_and_verify;
             use crate::render_circuit;
             use crate::tutorial as run_tutorial;
+            use crate::mock_batch;
+            use crate::mock_calibrated_settings;
+            use crate::mock_dump_tensors;
 
             #[test]
             fn tutorial_() {
                 run_tutorial();
             }
 
+            #[test]
+            fn mock_batch_() {
+                mock_batch("1l_batch".to_string());
+            }
+
+            #[test]
+            fn mock_calibrated_settings_() {
+                mock_calibrated_settings("1l_relu".to_string());
+            }
+
+            #[test]
+            fn mock_dump_tensors_() {
+                mock_dump_tensors("1l_relu".to_string());
+            }
+
 
-            seq!(N in 0..=27 {
+            seq!(N in 0..=36 {
 
             #(#[test_case(TESTS[N])])*
             fn render_circuit_(test: &str) {
@@ -240,6 +279,11 @@ macro_rules! test_func {
                 forward_pass(test.to_string());
             }
 
+            #(#[test_case(TESTS[N])])*
+            fn accuracy_(test: &str) {
+                accuracy(test.to_string());
+            }
+
             #(#[test_case(TESTS[N])])*
             fn kzg_prove_and_verify_(test: &str) {
                 kzg_prove_and_verify(test.to_string());
@@ -331,7 +375,45 @@ macro_rules! test_neg_examples {
     };
 }
 
+macro_rules! test_func_calibration {
+    () => {
+        #[cfg(test)]
+        mod tests_calibration {
+            use seq_macro::seq;
+            use crate::CALIBRATION_TESTS;
+            use test_case::test_case;
+            use crate::calibrated_forward_pass;
+            seq!(N in 0..=3 {
+            #(#[test_case(CALIBRATION_TESTS[N])])*
+            fn calibrated_forward_pass_(test: &str) {
+                calibrated_forward_pass(test.to_string());
+            }
+            });
+    }
+    };
+}
+
+macro_rules! test_func_accuracy {
+    () => {
+        #[cfg(test)]
+        mod tests_accuracy {
+            use seq_macro::seq;
+            use crate::ACCURACY_TESTS;
+            use test_case::test_case;
+            use crate::forward_accuracy;
+            seq!(N in 0..=1 {
+            #(#[test_case(ACCURACY_TESTS[N])])*
+            fn forward_accuracy_(test: &str) {
+                forward_accuracy(test.to_string());
+            }
+            });
+    }
+    };
+}
+
 test_func!();
+test_func_accuracy!();
+test_func_calibration!();
 test_func_aggr!();
 test_func_evm!();
 test_func_examples!();
@@ -409,6 +491,154 @@ fn forward_pass(example_name: String) {
     assert!(status.success());
 }
 
+// Compares the quantized forward pass to the float model
+fn accuracy(example_name: String) {
+    let report = format!(
+        "{}/{}_accuracy.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            "accuracy",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+            "-O",
+            report.as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+
+    let report: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(report).unwrap()).unwrap();
+    let outputs = report["outputs"].as_array().unwrap();
+    let nodes = report["nodes"].as_array().unwrap();
+    assert!(!outputs.is_empty());
+    let stats = outputs.iter().chain(nodes.iter().map(|n| &n["error"]));
+    for stat in stats {
+        for metric in ["max_abs", "mean_abs", "max_rel", "mean_rel"] {
+            // non-finite values are serialized as null
+            let value = stat[metric].as_f64().unwrap_or(f64::NAN);
+            assert!(
+                value.is_finite() && value >= 0.0,
+                "{}: {} is {}",
+                example_name,
+                metric,
+                value
+            );
+        }
+    }
+    // the quantized outputs of the models known to be accurate stay close to the float outputs
+    if ACCURACY_TESTS.contains(&example_name.as_str()) {
+        for output in outputs {
+            let max_abs = output["max_abs"].as_f64().unwrap();
+            assert!(
+                max_abs < ACCURACY_TOL,
+                "{}: max abs error {}",
+                example_name,
+                max_abs
+            );
+        }
+    }
+}
+
+// Calibrates the model's quantization, then runs a forward pass and mock proves using the profile
+fn calibrated_forward_pass(example_name: String) {
+    let profile = format!(
+        "{}/{}_profile.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    let forward = format!(
+        "{}/{}_input_calibrated.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            "calibrate",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+            "-O",
+            profile.as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            format!("--quantization-profile={}", profile).as_str(),
+            "forward",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+            "-O",
+            forward.as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            format!("--quantization-profile={}", profile).as_str(),
+            "mock",
+            "-D",
+            forward.as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+}
+
+// Compares the quantized forward pass to the float outputs recorded for the model
+fn forward_accuracy(example_name: String) {
+    forward_pass(example_name.clone());
+
+    let read_outputs = |path: String| -> Vec<Vec<f64>> {
+        let data: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap();
+        serde_json::from_value(data["output_data"].clone()).unwrap()
+    };
+    let expected = read_outputs(format!("./examples/onnx/{}/input.json", example_name));
+    let actual = read_outputs(format!(
+        "{}/{}_input_forward.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    ));
+
+    assert_eq!(expected.len(), actual.len());
+    for (e, a) in expected.iter().zip(actual.iter()) {
+        assert_eq!(e.len(), a.len());
+        for (e_i, a_i) in e.iter().zip(a.iter()) {
+            assert!(
+                (e_i - a_i).abs() < ACCURACY_TOL,
+                "{}: expected {} got {}",
+                example_name,
+                e_i,
+                a_i
+            );
+        }
+    }
+}
+
 // Mock prove (fast, but does not cover some potential issues)
 fn render_circuit(example_name: String) {
     let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
@@ -469,6 +699,101 @@ fn mock(example_name: String) {
     assert!(status.success());
 }
 
+// Mock proves while dumping the values of every node
+fn mock_dump_tensors(example_name: String) {
+    let dump = format!(
+        "{}/{}_dump.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            format!("--dump-tensors={}", dump).as_str(),
+            "mock",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+    assert!(std::path::Path::new(&dump).exists());
+}
+
+// Searches for the smallest settings for the model, then mock proves using them
+fn mock_calibrated_settings(example_name: String) {
+    let settings = format!(
+        "{}/{}_settings.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "calibrate-settings",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+            "-O",
+            settings.as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+
+    let forward = format!(
+        "{}/{}_input_settings.json",
+        TEST_DIR.path().to_str().unwrap(),
+        example_name
+    );
+    for args in [
+        vec![
+            "forward",
+            "-D",
+            &format!("./examples/onnx/{}/input.json", example_name),
+            "-M",
+            &format!("./examples/onnx/{}/network.onnx", example_name),
+            "-O",
+            forward.as_str(),
+        ],
+        vec![
+            "mock",
+            "-D",
+            forward.as_str(),
+            "-M",
+            &format!("./examples/onnx/{}/network.onnx", example_name),
+        ],
+    ] {
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .env("RUNARGS", settings.as_str())
+            .args(args)
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+    }
+}
+
+// Mock prove a batch of samples on a model with a dynamic batch dim
+fn mock_batch(example_name: String) {
+    let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+        .args([
+            "--bits=16",
+            "-K=17",
+            "--batch-size=2",
+            "mock",
+            "-D",
+            format!("./examples/onnx/{}/input.json", example_name).as_str(),
+            "-M",
+            format!("./examples/onnx/{}/network.onnx", example_name).as_str(),
+        ])
+        .status()
+        .expect("failed to execute process");
+    assert!(status.success());
+}
+
 // Mock prove (fast, but does not cover some potential issues)
 fn mock_packed_outputs(example_name: String) {
     let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))